@@ -0,0 +1,266 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkGroup, Criterion, Throughput,
+};
+use serde::de::DeserializeOwned;
+
+/// Only integers, which hits the primitive paths.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Reading {
+    sensor: u8,
+    timestamp: u64,
+    values: [i32; 4],
+    temperature: f32,
+    valid: bool,
+}
+
+/// A nested struct with borrowed data.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Message<'a> {
+    id: u32,
+    name: &'a str,
+    #[serde(with = "serde_bytes")]
+    payload: &'a [u8],
+    reading: Reading,
+    reply_to: Option<u16>,
+}
+
+/// A single large byte slice, which measures the bulk copy path.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Bulk<'a> {
+    #[serde(with = "serde_bytes")]
+    data: &'a [u8],
+}
+
+/// Many small integers, which measures the amount of calls to the writer.
+#[derive(Serialize)]
+struct Registers {
+    r0: u8,
+    r1: u16,
+    r2: u32,
+    r3: u16,
+    r4: u8,
+    r5: u16,
+    r6: u32,
+    r7: u16,
+    r8: u8,
+    r9: u16,
+    r10: u32,
+    r11: u16,
+    r12: u8,
+    r13: u16,
+    r14: u32,
+    r15: u16,
+}
+
+/// A writer that takes a lock for every call, like a UART that is shared between tasks.
+struct LockedWriter<'a>(&'a std::sync::Mutex<usize>);
+
+impl CoreWrite for LockedWriter<'_> {
+    type Error = ();
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.write_all(&[val])
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        let mut written = self.0.lock().unwrap();
+        *written += black_box(val).len();
+        Ok(())
+    }
+}
+
+const READING: Reading = Reading {
+    sensor: 3,
+    timestamp: 1_600_000_000_000,
+    values: [1, -2, 3, -4],
+    temperature: 21.5,
+    valid: true,
+};
+
+fn encode<T: serde::Serialize, B: ByteOrder + 'static>(value: &T, buffer: &mut [u8]) -> usize {
+    let mut writer = BufferWriter::new(buffer);
+    serialize::<_, _, B>(value, &mut writer).unwrap();
+    writer.written_len()
+}
+
+fn bench_owned<T: serde::Serialize + DeserializeOwned, B: ByteOrder + 'static>(
+    group: &mut BenchmarkGroup<criterion::measurement::WallTime>,
+    name: &str,
+    value: &T,
+) {
+    let mut buffer = [0u8; 1024];
+    let len = encode::<T, B>(value, &mut buffer);
+    group.throughput(Throughput::Bytes(len as u64));
+    group.bench_function(format!("serialize/{}", name), |b| {
+        b.iter(|| encode::<T, B>(black_box(value), &mut buffer))
+    });
+    let input = &buffer[..len];
+    group.bench_function(format!("deserialize/{}", name), |b| {
+        b.iter(|| deserialize::<T, _, B>(black_box(input)).unwrap())
+    });
+}
+
+fn bench_message<B: ByteOrder + 'static>(
+    group: &mut BenchmarkGroup<criterion::measurement::WallTime>,
+) {
+    let payload = [0xA5u8; 32];
+    let value = Message {
+        id: 42,
+        name: "temperature-sensor",
+        payload: &payload,
+        reading: READING,
+        reply_to: Some(7),
+    };
+    let mut buffer = [0u8; 1024];
+    let len = encode::<_, B>(&value, &mut buffer);
+    group.throughput(Throughput::Bytes(len as u64));
+    group.bench_function("serialize/message", |b| {
+        b.iter(|| encode::<_, B>(black_box(&value), &mut buffer))
+    });
+    let input = &buffer[..len];
+    group.bench_function("deserialize/message", |b| {
+        b.iter(|| deserialize::<Message, _, B>(black_box(input)).unwrap())
+    });
+}
+
+fn bench_bulk<B: ByteOrder + 'static>(
+    group: &mut BenchmarkGroup<criterion::measurement::WallTime>,
+) {
+    let data = [0x5Au8; 512];
+    let value = Bulk { data: &data };
+    let mut buffer = [0u8; 1024];
+    let len = encode::<_, B>(&value, &mut buffer);
+    group.throughput(Throughput::Bytes(len as u64));
+    group.bench_function("serialize/bulk", |b| {
+        b.iter(|| encode::<_, B>(black_box(&value), &mut buffer))
+    });
+    let input = &buffer[..len];
+    group.bench_function("deserialize/bulk", |b| {
+        b.iter(|| deserialize::<Bulk, _, B>(black_box(input)).unwrap())
+    });
+}
+
+/// An audio buffer, serialized per value and with the bulk path.
+fn bench_samples<B: ByteOrder + 'static>(
+    group: &mut BenchmarkGroup<criterion::measurement::WallTime>,
+) {
+    let mut samples = [0f32; 256];
+    for (i, sample) in samples.iter_mut().enumerate() {
+        *sample = i as f32 * 0.01;
+    }
+    let mut buffer = [0u8; 1024];
+    group.throughput(Throughput::Bytes(buffer.len() as u64));
+    group.bench_function("serialize/samples", |b| {
+        b.iter(|| {
+            let mut writer = BufferWriter::new(&mut buffer);
+            let mut serializer = Serializer::<_, B>::new(&mut writer, Config::new());
+            for sample in black_box(&samples) {
+                serde::Serialize::serialize(sample, &mut serializer).unwrap();
+            }
+            serializer.finish().unwrap();
+            writer.written_len()
+        })
+    });
+    group.bench_function("serialize/samples_bulk", |b| {
+        b.iter(|| {
+            let mut writer = BufferWriter::new(&mut buffer);
+            let mut serializer = Serializer::<_, B>::new(&mut writer, Config::new());
+            serializer.serialize_f32_slice(black_box(&samples)).unwrap();
+            serializer.finish().unwrap();
+            writer.written_len()
+        })
+    });
+}
+
+/// A firmware image, read one byte at a time and with [byte_array].
+fn bench_image<B: ByteOrder + 'static>(
+    group: &mut BenchmarkGroup<criterion::measurement::WallTime>,
+) {
+    #[derive(Deserialize)]
+    struct Image {
+        #[serde(with = "bincode_embedded::byte_array")]
+        data: [u8; 1024],
+    }
+
+    let input = [0xC3u8; 1024];
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.bench_function("deserialize/image", |b| {
+        b.iter(|| {
+            let mut deserializer = Deserializer::<_, B>::new(black_box(&input[..]), Config::new());
+            let mut data = [0u8; 1024];
+            for byte in data.iter_mut() {
+                *byte = serde::Deserialize::deserialize(&mut deserializer).unwrap();
+            }
+            data
+        })
+    });
+    group.bench_function("deserialize/image_byte_array", |b| {
+        b.iter(|| {
+            deserialize::<Image, _, B>(black_box(&input[..]))
+                .unwrap()
+                .data
+        })
+    });
+}
+
+/// Small fields written with a call per field, and coalesced into a single call.
+fn bench_registers<B: ByteOrder + 'static>(
+    group: &mut BenchmarkGroup<criterion::measurement::WallTime>,
+) {
+    let registers = Registers {
+        r0: 1,
+        r1: 2,
+        r2: 3,
+        r3: 4,
+        r4: 5,
+        r5: 6,
+        r6: 7,
+        r7: 8,
+        r8: 9,
+        r9: 10,
+        r10: 11,
+        r11: 12,
+        r12: 13,
+        r13: 14,
+        r14: 15,
+        r15: 16,
+    };
+    let lock = std::sync::Mutex::new(0);
+    group.throughput(Throughput::Bytes(
+        serialized_size(&registers).unwrap() as u64
+    ));
+    group.bench_function("serialize/registers", |b| {
+        b.iter(|| serialize::<_, _, B>(black_box(&registers), LockedWriter(&lock)).unwrap())
+    });
+    group.bench_function("serialize/registers_coalesced", |b| {
+        b.iter(|| {
+            let mut writer = CoalescingWriter::<_>::new(LockedWriter(&lock));
+            serialize::<_, _, B>(black_box(&registers), &mut writer).unwrap();
+            writer.finish().unwrap()
+        })
+    });
+}
+
+fn roundtrip<B: ByteOrder + 'static>(c: &mut Criterion, endian: &str) {
+    let mut group = c.benchmark_group(endian);
+    bench_owned::<_, B>(&mut group, "reading", &READING);
+    bench_message::<B>(&mut group);
+    bench_bulk::<B>(&mut group);
+    bench_samples::<B>(&mut group);
+    bench_image::<B>(&mut group);
+    bench_registers::<B>(&mut group);
+    group.finish();
+}
+
+fn benches(c: &mut Criterion) {
+    roundtrip::<BigEndian>(c, "big_endian");
+    roundtrip::<LittleEndian>(c, "little_endian");
+}
+
+criterion_group!(roundtrip_benches, benches);
+criterion_main!(roundtrip_benches);
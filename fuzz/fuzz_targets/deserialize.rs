@@ -0,0 +1,17 @@
+//! Deserialize random bytes. This must return an error for invalid input, and never panic.
+
+#![no_main]
+
+mod message;
+
+use bincode_embedded::{deserialize, deserialize_with_config, Config};
+use libfuzzer_sys::fuzz_target;
+use message::Message;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = deserialize::<Message, _, byteorder::NetworkEndian>(data);
+    let _ = deserialize::<(u8, &str, Option<char>), _, byteorder::LittleEndian>(data);
+
+    let config = Config::new().bit_pack_tags().limit(256);
+    let _ = deserialize_with_config::<Message, _, byteorder::LittleEndian>(data, config);
+});
@@ -0,0 +1,29 @@
+use arbitrary::Arbitrary;
+use serde_derive::{Deserialize, Serialize};
+
+/// A message that touches most of the encoding: integers, floats, `bool`, `char`, options,
+/// borrowed strings and byte slices, sequences, tuples and enums.
+#[derive(Arbitrary, Serialize, Deserialize, PartialEq, Debug)]
+pub struct Message<'a> {
+    pub id: u32,
+    pub offset: i64,
+    pub ratio: f32,
+    pub large: u128,
+    pub flag: bool,
+    pub letter: char,
+    pub name: &'a str,
+    #[serde(with = "serde_bytes")]
+    pub payload: &'a [u8],
+    pub reply_to: Option<u16>,
+    pub pair: (i8, Option<bool>),
+    pub samples: [i16; 4],
+    pub commands: Vec<Command<'a>>,
+}
+
+#[derive(Arbitrary, Serialize, Deserialize, PartialEq, Debug)]
+pub enum Command<'a> {
+    Stop,
+    Move(i16, i16),
+    Set { id: u8, value: &'a str },
+    Nested(Option<u64>),
+}
@@ -0,0 +1,29 @@
+//! Serialize an arbitrary message, and check that it deserializes to the same value.
+
+#![no_main]
+
+mod message;
+
+use bincode_embedded::{deserialize_exact, serialize, BufferWriter, SerializeError};
+use libfuzzer_sys::fuzz_target;
+use message::Message;
+
+fuzz_target!(|message: Message| {
+    // NaN is not equal to itself
+    if message.ratio.is_nan() {
+        return;
+    }
+
+    let mut buffer = [0u8; 4096];
+    let mut writer = BufferWriter::new(&mut buffer);
+    match serialize::<_, _, byteorder::NetworkEndian>(&message, &mut writer) {
+        Ok(()) => {}
+        // Lengths that do not fit in the length prefix, or values that do not fit in the buffer
+        Err(SerializeError::LengthOverflow) | Err(SerializeError::Write(_)) => return,
+        Err(e) => panic!("Unexpected error {:?}", e),
+    }
+
+    let deserialized: Message =
+        deserialize_exact::<_, _, byteorder::NetworkEndian>(writer.written_buffer()).unwrap();
+    assert_eq!(message, deserialized);
+});
@@ -0,0 +1,32 @@
+use super::*;
+use arrayvec::{ArrayVec, CapacityError};
+
+/// Write to the end of an [ArrayVec]. When the `ArrayVec` is full, a [CapacityError] is returned.
+///
+/// This is only available with the `arrayvec` feature.
+impl<const N: usize> CoreWrite for ArrayVec<u8, N> {
+    type Error = CapacityError;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.try_push(val).map_err(CapacityError::simplify)
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        self.try_extend_from_slice(val)
+    }
+}
+
+/// Write to the end of an [ArrayVec] that is owned by the caller.
+///
+/// This is only available with the `arrayvec` feature.
+impl<const N: usize> CoreWrite for &'_ mut ArrayVec<u8, N> {
+    type Error = CapacityError;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (**self).write(val)
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(val)
+    }
+}
@@ -0,0 +1,196 @@
+use super::*;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A [CoreWrite] adapter that writes standard base64 (with `=` padding) to an inner writer.
+///
+/// This allows serialized values to be sent over a channel that only accepts text. Bytes are
+/// encoded in groups of 3, so the last group is only written when [Base64Writer::finish] is called:
+///
+/// ```
+/// # use bincode_embedded::{serialize, Base64Writer, BufferWriter};
+/// let mut buffer = [0u8; 16];
+/// let mut writer = BufferWriter::new(&mut buffer);
+/// let mut base64 = Base64Writer::new(&mut writer);
+/// serialize::<_, _, byteorder::NetworkEndian>(&"hi", &mut base64).unwrap();
+/// base64.finish().unwrap();
+/// assert_eq!(b"AAJoaQ==", writer.written_buffer());
+/// ```
+pub struct Base64Writer<W: CoreWrite> {
+    inner: W,
+    group: [u8; 3],
+    len: usize,
+}
+
+impl<W: CoreWrite> Base64Writer<W> {
+    /// Create a new writer that writes base64 to `inner`.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            group: [0; 3],
+            len: 0,
+        }
+    }
+
+    /// Write the last partial group with padding, and return the inner writer.
+    pub fn finish(mut self) -> Result<W, W::Error> {
+        if self.len > 0 {
+            for byte in &mut self.group[self.len..] {
+                *byte = 0;
+            }
+            let mut chars = encode_group(self.group);
+            for c in &mut chars[self.len + 1..] {
+                *c = b'=';
+            }
+            self.inner.write_all(&chars)?;
+        }
+        Ok(self.inner)
+    }
+}
+
+fn encode_group(group: [u8; 3]) -> [u8; 4] {
+    let bits = u32::from(group[0]) << 16 | u32::from(group[1]) << 8 | u32::from(group[2]);
+    [
+        ALPHABET[(bits >> 18) as usize & 0x3F],
+        ALPHABET[(bits >> 12) as usize & 0x3F],
+        ALPHABET[(bits >> 6) as usize & 0x3F],
+        ALPHABET[bits as usize & 0x3F],
+    ]
+}
+
+impl<W: CoreWrite> CoreWrite for Base64Writer<W> {
+    type Error = W::Error;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.group[self.len] = val;
+        self.len += 1;
+        if self.len == 3 {
+            self.len = 0;
+            self.inner.write_all(&encode_group(self.group))?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the inner writer. The last partial group is only written by
+    /// [Base64Writer::finish], as base64 can not continue after padding.
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+impl<W: CoreWrite> CoreWrite for &'_ mut Base64Writer<W> {
+    type Error = W::Error;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (**self).write(val)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (**self).flush()
+    }
+}
+
+/// A [CoreRead] implementation that decodes standard base64, e.g. the output of [Base64Writer].
+///
+/// The input is decoded into a scratch buffer when the reader is created, so borrowed values like
+/// `&str` point into the scratch buffer:
+///
+/// ```
+/// # use bincode_embedded::{deserialize, Base64Reader};
+/// let mut scratch = [0u8; 16];
+/// let reader = Base64Reader::new(b"AAJoaQ==", &mut scratch).unwrap();
+/// let value: &str = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+/// assert_eq!("hi", value);
+/// ```
+pub struct Base64Reader<'a> {
+    decoded: &'a [u8],
+}
+
+impl<'a> Base64Reader<'a> {
+    /// Decode `input` into `scratch`. The scratch buffer must be at least `input.len() / 4 * 3`
+    /// bytes.
+    pub fn new(input: &[u8], scratch: &'a mut [u8]) -> Result<Self, Base64Error> {
+        if input.len() % 4 != 0 {
+            return Err(Base64Error::InvalidLength);
+        }
+        let mut len = 0;
+        let groups = input.len() / 4;
+        for (index, chunk) in input.chunks_exact(4).enumerate() {
+            let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+            if padding > 2 || (padding > 0 && index + 1 != groups) {
+                return Err(Base64Error::InvalidCharacter(b'='));
+            }
+            let mut bits = 0u32;
+            for c in &chunk[..4 - padding] {
+                bits = bits << 6 | decode_char(*c)?;
+            }
+            bits <<= 6 * padding;
+
+            let bytes = [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8];
+            let count = 3 - padding;
+            scratch
+                .get_mut(len..len + count)
+                .ok_or(Base64Error::BufferTooSmall)?
+                .copy_from_slice(&bytes[..count]);
+            len += count;
+        }
+        let scratch: &'a [u8] = scratch;
+        Ok(Self {
+            decoded: &scratch[..len],
+        })
+    }
+
+    /// The decoded bytes that are not read yet.
+    pub fn remaining_buffer(&self) -> &'a [u8] {
+        self.decoded
+    }
+}
+
+fn decode_char(c: u8) -> Result<u32, Base64Error> {
+    let value = match c {
+        b'A'..=b'Z' => c - b'A',
+        b'a'..=b'z' => c - b'a' + 26,
+        b'0'..=b'9' => c - b'0' + 52,
+        b'+' => 62,
+        b'/' => 63,
+        _ => return Err(Base64Error::InvalidCharacter(c)),
+    };
+    Ok(u32::from(value))
+}
+
+/// Errors that can be returned from a [Base64Reader].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Base64Error {
+    /// The input contains a character that is not valid base64, or padding in the wrong place.
+    InvalidCharacter(u8),
+
+    /// The length of the input is not a multiple of 4.
+    InvalidLength,
+
+    /// The scratch buffer is too small for the decoded data.
+    BufferTooSmall,
+
+    /// A read was requested past the end of the decoded data.
+    UnexpectedEnd,
+}
+
+impl<'a> CoreRead<'a> for Base64Reader<'a> {
+    type Error = Base64Error;
+
+    fn read_range(&mut self, len: usize) -> Result<&'a [u8], Self::Error> {
+        self.decoded
+            .read_range(len)
+            .map_err(|()| Base64Error::UnexpectedEnd)
+    }
+
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        buf.copy_from_slice(self.read_range(buf.len())?);
+        Ok(())
+    }
+
+    fn read_until(&mut self, delimiter: u8) -> Result<Option<&'a [u8]>, Self::Error> {
+        self.decoded
+            .read_until(delimiter)
+            .map_err(|()| Base64Error::UnexpectedEnd)
+    }
+}
@@ -0,0 +1,159 @@
+use core::{fmt, marker::PhantomData, ops::Deref};
+use serde::de::{Deserialize, Deserializer, Error, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+/// The name that [BoundedStr] passes to `deserialize_tuple_struct`. The [Deserializer] of this
+/// crate recognizes this name, and checks the length prefix before reading the string.
+pub(crate) const BOUNDED_STR_NAME: &str = "$bincode_embedded::BoundedStr";
+
+/// The name that [BoundedChars] passes to `deserialize_tuple_struct`. The [Deserializer] of this
+/// crate recognizes this name, and counts the characters of the string.
+pub(crate) const BOUNDED_CHARS_NAME: &str = "$bincode_embedded::BoundedChars";
+
+/// A `&str` that is at most `MAX` bytes long.
+///
+/// This is encoded the same as a `&str`. When deserializing, the length prefix is checked before
+/// the string itself is read. A corrupt length prefix results in
+/// [DeserializeError::StringTooLong](crate::DeserializeError::StringTooLong) instead of a large
+/// read from the reader.
+///
+/// ```
+/// # use bincode_embedded::{deserialize, BoundedStr, DeserializeError};
+/// let buffer = [0, 3, b'a', b'b', b'c'];
+/// let value: BoundedStr<4> = deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+/// assert_eq!("abc", value.as_str());
+///
+/// let result = deserialize::<BoundedStr<2>, _, byteorder::NetworkEndian>(&buffer[..]);
+/// assert!(matches!(result, Err(DeserializeError::StringTooLong { len: 3, max: 2 })));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BoundedStr<'a, const MAX: usize>(&'a str);
+
+impl<'a, const MAX: usize> BoundedStr<'a, MAX> {
+    /// Create a new bounded string. Returns `None` if `value` is longer than `MAX` bytes.
+    pub fn new(value: &'a str) -> Option<Self> {
+        if value.len() > MAX {
+            None
+        } else {
+            Some(Self(value))
+        }
+    }
+
+    /// Get the inner string.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl<const MAX: usize> Deref for BoundedStr<'_, MAX> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl<const MAX: usize> Serialize for BoundedStr<'_, MAX> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de: 'a, 'a, const MAX: usize> Deserialize<'de> for BoundedStr<'a, MAX> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BoundedStrVisitor<'a, const MAX: usize>(PhantomData<&'a str>);
+
+        impl<'de: 'a, 'a, const MAX: usize> Visitor<'de> for BoundedStrVisitor<'a, MAX> {
+            type Value = BoundedStr<'a, MAX>;
+
+            fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                write!(fmt, "a string of at most {} bytes", MAX)
+            }
+
+            fn visit_borrowed_str<E: Error>(self, value: &'de str) -> Result<Self::Value, E> {
+                BoundedStr::new(value).ok_or_else(|| E::invalid_length(value.len(), &self))
+            }
+        }
+
+        deserializer.deserialize_tuple_struct(
+            BOUNDED_STR_NAME,
+            MAX,
+            BoundedStrVisitor::<MAX>(PhantomData),
+        )
+    }
+}
+
+/// A `&str` that has at most `MAX` characters.
+///
+/// This is encoded the same as a `&str`. Unlike [BoundedStr], this limits the amount of
+/// characters instead of bytes, which differs for characters that take more than one byte in
+/// UTF-8. The length prefix only counts bytes, so the string is read before the characters are
+/// counted. A string with too many characters results in
+/// [DeserializeError::TooManyChars](crate::DeserializeError::TooManyChars).
+///
+/// ```
+/// # use bincode_embedded::{deserialize, BoundedChars, DeserializeError};
+/// let buffer = [0, 4, 0xC3, 0xA9, 0xC3, 0xA9];
+/// let value: BoundedChars<2> = deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+/// assert_eq!("éé", value.as_str());
+///
+/// let result = deserialize::<BoundedChars<1>, _, byteorder::NetworkEndian>(&buffer[..]);
+/// assert!(matches!(result, Err(DeserializeError::TooManyChars { count: 2, max: 1 })));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BoundedChars<'a, const MAX: usize>(&'a str);
+
+impl<'a, const MAX: usize> BoundedChars<'a, MAX> {
+    /// Create a new bounded string. Returns `None` if `value` has more than `MAX` characters.
+    pub fn new(value: &'a str) -> Option<Self> {
+        if value.chars().count() > MAX {
+            None
+        } else {
+            Some(Self(value))
+        }
+    }
+
+    /// Get the inner string.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl<const MAX: usize> Deref for BoundedChars<'_, MAX> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl<const MAX: usize> Serialize for BoundedChars<'_, MAX> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de: 'a, 'a, const MAX: usize> Deserialize<'de> for BoundedChars<'a, MAX> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BoundedCharsVisitor<'a, const MAX: usize>(PhantomData<&'a str>);
+
+        impl<'de: 'a, 'a, const MAX: usize> Visitor<'de> for BoundedCharsVisitor<'a, MAX> {
+            type Value = BoundedChars<'a, MAX>;
+
+            fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                write!(fmt, "a string of at most {} characters", MAX)
+            }
+
+            fn visit_borrowed_str<E: Error>(self, value: &'de str) -> Result<Self::Value, E> {
+                BoundedChars::new(value)
+                    .ok_or_else(|| E::invalid_length(value.chars().count(), &self))
+            }
+        }
+
+        deserializer.deserialize_tuple_struct(
+            BOUNDED_CHARS_NAME,
+            MAX,
+            BoundedCharsVisitor::<MAX>(PhantomData),
+        )
+    }
+}
@@ -0,0 +1,163 @@
+use core::{convert::TryFrom, fmt, marker::PhantomData, ops::Deref};
+use serde::de::{Deserialize, Deserializer, Error, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+/// The name that [byte_array::deserialize] passes to `deserialize_tuple_struct`. The
+/// [Deserializer](crate::Deserializer) of this crate recognizes this name, and reads the whole
+/// array at once instead of one byte at a time.
+pub(crate) const BYTE_ARRAY_NAME: &str = "$bincode_embedded::ByteArray";
+
+/// The name that [Bytes] passes to `deserialize_tuple_struct`. The
+/// [Deserializer](crate::Deserializer) of this crate recognizes this name, and borrows the whole
+/// array from the reader.
+pub(crate) const BYTES_NAME: &str = "$bincode_embedded::Bytes";
+
+/// A reference to a `[u8; N]` that is borrowed from the reader.
+///
+/// This is encoded the same as a `[u8; N]`, the bytes are written without a length prefix. An
+/// array can not be borrowed, so deserializing a `[u8; N]` copies every byte. This type borrows
+/// the `N` bytes with a single [CoreRead::read_range](crate::CoreRead::read_range) call instead,
+/// so the reader must return persistent data, like a `&[u8]`. See [byte_array] to copy the bytes
+/// out of the reader at once.
+///
+/// ```
+/// # use bincode_embedded::{deserialize, Bytes};
+/// let buffer = [1, 2, 3, 4, 5];
+/// let (key, rest): (Bytes<4>, u8) =
+///     deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+/// assert_eq!(&[1, 2, 3, 4], key.as_array());
+/// assert_eq!(5, rest);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bytes<'a, const N: usize>(&'a [u8; N]);
+
+impl<'a, const N: usize> Bytes<'a, N> {
+    /// Create a new reference to `value`.
+    pub fn new(value: &'a [u8; N]) -> Self {
+        Self(value)
+    }
+
+    /// Get the inner array.
+    pub fn as_array(&self) -> &'a [u8; N] {
+        self.0
+    }
+}
+
+impl<const N: usize> Deref for Bytes<'_, N> {
+    type Target = [u8; N];
+
+    fn deref(&self) -> &[u8; N] {
+        self.0
+    }
+}
+
+impl<const N: usize> Serialize for Bytes<'_, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        byte_array::serialize(self.0, serializer)
+    }
+}
+
+impl<'de: 'a, 'a, const N: usize> Deserialize<'de> for Bytes<'a, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor<'a, const N: usize>(PhantomData<&'a [u8]>);
+
+        impl<'de: 'a, 'a, const N: usize> Visitor<'de> for BytesVisitor<'a, N> {
+            type Value = Bytes<'a, N>;
+
+            fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                write!(fmt, "a borrowed array of {} bytes", N)
+            }
+
+            fn visit_borrowed_bytes<E: Error>(self, value: &'de [u8]) -> Result<Self::Value, E> {
+                <&[u8; N]>::try_from(value)
+                    .map(Bytes)
+                    .map_err(|_| E::invalid_length(value.len(), &self))
+            }
+        }
+
+        deserializer.deserialize_tuple_struct(BYTES_NAME, N, BytesVisitor::<N>(PhantomData))
+    }
+}
+
+/// (De)serialize a `[u8; N]` field, for use with `#[serde(with = "...")]`.
+///
+/// serde handles byte arrays like any other array, so every byte is deserialized on its own. This
+/// module reads the whole array with a single [CoreRead::read_transient](crate::CoreRead::read_transient)
+/// call instead, which is a lot faster for large fields like firmware images. It also works for
+/// arrays that are larger than the 32 elements serde supports.
+///
+/// This is encoded the same as an array, the bytes are written without a length prefix. The
+/// reader must support [CoreRead::read_transient](crate::CoreRead::read_transient), which is the
+/// case for every reader that implements `read_range`.
+///
+/// ```
+/// # #[macro_use] extern crate serde_derive;
+/// # use bincode_embedded::deserialize;
+/// #[derive(Deserialize)]
+/// struct Block {
+///     index: u8,
+///     #[serde(with = "bincode_embedded::byte_array")]
+///     data: [u8; 64],
+/// }
+///
+/// let mut buffer = [0xAB; 65];
+/// buffer[0] = 3;
+/// let block: Block = deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+/// assert_eq!(3, block.index);
+/// assert_eq!([0xAB; 64], block.data);
+/// ```
+pub mod byte_array {
+    use super::BYTE_ARRAY_NAME;
+    use core::fmt;
+    use serde::de::{Deserializer, Error, SeqAccess, Visitor};
+    use serde::ser::{SerializeTuple, Serializer};
+
+    /// Serialize a `[u8; N]` as an array.
+    pub fn serialize<S: Serializer, const N: usize>(
+        value: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for byte in value {
+            tuple.serialize_element(byte)?;
+        }
+        tuple.end()
+    }
+
+    /// Deserialize a `[u8; N]` by copying the bytes out of the reader.
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        struct ArrayVisitor<const N: usize>;
+
+        impl<'de, const N: usize> Visitor<'de> for ArrayVisitor<N> {
+            type Value = [u8; N];
+
+            fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                write!(fmt, "an array of {} bytes", N)
+            }
+
+            fn visit_bytes<E: Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+                let mut array = [0u8; N];
+                if value.len() != N {
+                    return Err(E::invalid_length(value.len(), &self));
+                }
+                array.copy_from_slice(value);
+                Ok(array)
+            }
+
+            // Other formats hand the array over one byte at a time
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut array = [0u8; N];
+                for (i, byte) in array.iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+                }
+                Ok(array)
+            }
+        }
+
+        deserializer.deserialize_tuple_struct(BYTE_ARRAY_NAME, N, ArrayVisitor::<N>)
+    }
+}
@@ -0,0 +1,428 @@
+use super::*;
+
+/// Configuration for the serializer and deserializer.
+///
+/// The default configuration matches the format that [serialize] and [deserialize] use. Options
+/// can be enabled with the builder methods on this type:
+///
+/// ```
+/// # use bincode_embedded::Config;
+/// let config = Config::new().copy_strings();
+/// ```
+///
+/// Options that change the encoding must be set to the same value on both the serializing and
+/// the deserializing side.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Config {
+    pub(crate) copy_strings: bool,
+    pub(crate) length_endian: Option<RuntimeEndian>,
+    pub(crate) value_endian: Option<RuntimeEndian>,
+    pub(crate) limit: Option<usize>,
+    pub(crate) read_error_offset: bool,
+    pub(crate) read_error_field: bool,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) bit_pack_tags: bool,
+    pub(crate) cstr_mode: bool,
+    pub(crate) fixed_char: bool,
+    pub(crate) canonical_nan: bool,
+    pub(crate) bool_width: BoolWidth,
+    pub(crate) struct_field_guard: bool,
+    pub(crate) field_mask: bool,
+    pub(crate) field_filter: Option<fn(&'static str, usize) -> bool>,
+    pub(crate) lenient_bool: bool,
+    pub(crate) variant_width: VariantWidth,
+    pub(crate) length_width: LengthWidth,
+    pub(crate) string_enum_tags: bool,
+    pub(crate) omit_single_variant_tag: bool,
+    #[cfg(feature = "alloc")]
+    pub(crate) sort_map_keys: bool,
+    /// Set by [serialize_bytes_only] and [deserialize_bytes_only].
+    pub(crate) bytes_only: bool,
+}
+
+impl Config {
+    /// Create a new configuration with all options disabled.
+    pub const fn new() -> Self {
+        Self {
+            copy_strings: false,
+            length_endian: None,
+            value_endian: None,
+            limit: None,
+            read_error_offset: false,
+            read_error_field: false,
+            max_depth: None,
+            bit_pack_tags: false,
+            cstr_mode: false,
+            fixed_char: false,
+            canonical_nan: false,
+            bool_width: BoolWidth::One,
+            struct_field_guard: false,
+            field_mask: false,
+            field_filter: None,
+            lenient_bool: false,
+            variant_width: VariantWidth::U8,
+            length_width: LengthWidth::Compact,
+            string_enum_tags: false,
+            omit_single_variant_tag: false,
+            #[cfg(feature = "alloc")]
+            sort_map_keys: false,
+            bytes_only: false,
+        }
+    }
+
+    /// Deserialize strings by copying them out of the reader instead of borrowing them.
+    ///
+    /// By default `&str` values are handed to the visitor with `visit_borrowed_str`, which requires
+    /// the [CoreRead] implementation to return data that lives for the full `'a` lifetime. Readers
+    /// that are backed by a transient scratch buffer (e.g. a ring buffer) can not provide this.
+    ///
+    /// With this option enabled strings are read with [CoreRead::read_transient] and handed to the
+    /// visitor with `visit_str`. The string only lives until the next read, so the target type must
+    /// own its data. This means `&str` fields can no longer be deserialized, and on alloc builds the
+    /// target should be an owned type like `String`.
+    pub const fn copy_strings(mut self) -> Self {
+        self.copy_strings = true;
+        self
+    }
+
+    /// Encode length prefixes with the given byte order, instead of the byte order that is passed
+    /// to [serialize] and [deserialize].
+    ///
+    /// This affects the lengths of strings, byte slices, sequences and maps. Together with
+    /// [Config::value_endian] this allows talking to protocols that mix byte orders, e.g. storing
+    /// lengths big-endian and the values little-endian. Values keep the byte order that is passed
+    /// to [serialize] when only this option is set:
+    ///
+    /// ```
+    /// # use bincode_embedded::{serialize_with_config, BufferWriter, Config, RuntimeEndian};
+    /// let config = Config::new().length_endian(RuntimeEndian::Big);
+    /// let mut buffer = [0u8; 8];
+    /// let mut writer = BufferWriter::new(&mut buffer);
+    /// serialize_with_config::<_, _, byteorder::LittleEndian>(&(1u16, "ab"), &mut writer, config)
+    ///     .unwrap();
+    /// assert_eq!(&[1, 0, 0, 2, b'a', b'b'], writer.written_buffer());
+    /// ```
+    pub const fn length_endian(mut self, endian: RuntimeEndian) -> Self {
+        self.length_endian = Some(endian);
+        self
+    }
+
+    /// Encode integers and floats with the given byte order, instead of the byte order that is
+    /// passed to [serialize] and [deserialize].
+    ///
+    /// See [Config::length_endian] to change the byte order of length prefixes.
+    pub const fn value_endian(mut self, endian: RuntimeEndian) -> Self {
+        self.value_endian = Some(endian);
+        self
+    }
+
+    /// Limit the total amount of bytes that can be read while deserializing a single value.
+    ///
+    /// The limit is checked before each read is done, so a corrupt length prefix (e.g. `0xFFFF`)
+    /// results in [DeserializeError::LimitExceeded] instead of a huge read from the reader. This is
+    /// useful when parsing untrusted frames. This option only affects deserializing.
+    pub const fn limit(mut self, max_bytes: usize) -> Self {
+        self.limit = Some(max_bytes);
+        self
+    }
+
+    /// Report errors of the reader as [DeserializeError::ReadAt] instead of
+    /// [DeserializeError::Read], which includes the offset of the read that failed.
+    ///
+    /// The offset is counted from the start of the deserializer. For a frame that is cut off, this
+    /// points at the first value that could not be read completely. This option only affects
+    /// deserializing.
+    pub const fn read_error_offset(mut self) -> Self {
+        self.read_error_offset = true;
+        self
+    }
+
+    /// Report errors of the reader that happen inside a struct field as
+    /// [DeserializeError::ReadInField], which includes the name of the field and the offset of the
+    /// read that failed.
+    ///
+    /// Structs are read positionally, so a frame that is cut off normally gives no indication of
+    /// the value that was being read. With nested structs the innermost field is reported. This
+    /// also applies to struct enum variants. This option only affects deserializing.
+    pub const fn read_error_field(mut self) -> Self {
+        self.read_error_field = true;
+        self
+    }
+
+    /// Limit how deeply values can be nested while deserializing.
+    ///
+    /// Every sequence, tuple, struct, map, enum and `Some` value adds a level, e.g.
+    /// `Some([1u8, 2])` is nested 2 levels deep. Deserializing recursive types like
+    /// `struct Node { next: Option<Box<Node>> }` recurses once for every level, so crafted input
+    /// could overflow a small stack. Values that are nested deeper than `max_depth` result in
+    /// [DeserializeError::DepthExceeded]. This option only affects deserializing.
+    pub const fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Pack consecutive `bool` values and `Option` tags into single bits instead of a full byte
+    /// each. This is useful on bandwidth-constrained links, e.g. a struct with five `Option<()>`
+    /// fields is encoded in a single byte instead of five.
+    ///
+    /// Tags are packed least significant bit first: the first tag is stored in bit `0` of the
+    /// packed byte, the second tag in bit `1`, and so on. A packed byte is written when it holds 8
+    /// tags, when any other value is written, or at the end of the serialized value. Unused bits are
+    /// set to `0`. This means that `(true, 5u8, false)` is encoded as `[0b1, 5, 0b0]`, and
+    /// `(true, false, true)` as `[0b101]`.
+    ///
+    /// Enum variant indices are not packed. The serializer does not know how many variants an enum
+    /// has, so a variant index always takes its full [Config::variant_width].
+    pub const fn bit_pack_tags(mut self) -> Self {
+        self.bit_pack_tags = true;
+        self
+    }
+
+    /// Encode strings as their bytes followed by a `0` terminator, instead of a length prefix and
+    /// their bytes. Byte slices still have a length prefix.
+    ///
+    /// Serializing a string that contains a `0` byte results in [SerializeError::InteriorNul].
+    /// Deserializing a string requires the reader to implement [CoreRead::read_until], which
+    /// is implemented for `&[u8]`.
+    pub const fn cstr_mode(mut self) -> Self {
+        self.cstr_mode = true;
+        self
+    }
+
+    /// Encode `char` values as their `u32` code point, instead of their UTF-8 bytes.
+    ///
+    /// This always takes 4 bytes and uses the byte order of values, which matches the in-memory
+    /// layout of a Rust `char` and protocols that store characters as UTF-32. Values that are not
+    /// a valid code point, e.g. a surrogate like `0xD800`, result in
+    /// [DeserializeError::InvalidCharEncoding]. Strings are not affected by this option.
+    pub const fn fixed_char(mut self) -> Self {
+        self.fixed_char = true;
+        self
+    }
+
+    /// Serialize every NaN float as the same bit pattern, `0x7FC0_0000` for `f32` and
+    /// `0x7FF8_0000_0000_0000` for `f64`.
+    ///
+    /// By default the sign and payload bits of a NaN are written as-is. With this option, values
+    /// that are logically the same always produce the same bytes, e.g. when the output is hashed.
+    /// This option only affects serializing.
+    pub const fn canonical_nan(mut self) -> Self {
+        self.canonical_nan = true;
+        self
+    }
+
+    /// Encode `bool` values with the given width. See [BoolWidth] for more info.
+    ///
+    /// [Config::bit_pack_tags] takes precedence over this option.
+    pub const fn bool_width(mut self, width: BoolWidth) -> Self {
+        self.bool_width = width;
+        self
+    }
+
+    /// Deserialize any non-zero `bool` value as `true`, like C does. This matches producers that
+    /// write e.g. `0xFF` for `true`.
+    ///
+    /// By default only `0` and `1` are accepted, and other values result in
+    /// [DeserializeError::InvalidBoolValue] or [DeserializeError::InvalidWideBoolValue]. This
+    /// option only affects deserializing, `true` is always serialized as `1`.
+    pub const fn lenient_bool(mut self) -> Self {
+        self.lenient_bool = true;
+        self
+    }
+
+    /// Only accept `0` and `1` as `bool` values. This is the default, and undoes
+    /// [Config::lenient_bool].
+    pub const fn strict_bool(mut self) -> Self {
+        self.lenient_bool = false;
+        self
+    }
+
+    /// Encode enum variant indices with the given width. See [VariantWidth] for more info.
+    pub const fn variant_width(mut self, width: VariantWidth) -> Self {
+        self.variant_width = width;
+        self
+    }
+
+    /// Encode length prefixes with the given width. See [LengthWidth] for more info.
+    pub const fn length_width(mut self, width: LengthWidth) -> Self {
+        self.length_width = width;
+        self
+    }
+
+    /// Encode enum variants by their name instead of their index. The name is encoded the same as
+    /// a `&str`, with a length prefix.
+    ///
+    /// This matches protocols that tag enums with the variant name, and keeps the encoding stable
+    /// when variants are reordered. Names that do not match any variant of the enum result in
+    /// [DeserializeError::UnknownVariant]. [Config::variant_width] is ignored with this option.
+    pub const fn string_enum_tags(mut self) -> Self {
+        self.string_enum_tags = true;
+        self
+    }
+
+    /// Leave out the tag of enums that have exactly one variant, which saves a byte per value.
+    ///
+    /// The serializer only gets the index of the variant, so the enum has to be wrapped in
+    /// [SingleVariant] to skip its tag, both when serializing and deserializing. Enums that are not
+    /// wrapped keep their tag. Wrapping an enum with more than one variant results in
+    /// [SerializeError::NotSupported] or [DeserializeError::NotSupported].
+    ///
+    /// ```
+    /// # #[macro_use] extern crate serde_derive;
+    /// # use bincode_embedded::{serialize_with_config, BufferWriter, Config, SingleVariant};
+    /// #[derive(Serialize)]
+    /// enum Request {
+    ///     Ping(u8),
+    /// }
+    ///
+    /// let config = Config::new().omit_single_variant_tag();
+    /// let mut buffer = [0u8; 4];
+    /// let mut writer = BufferWriter::new(&mut buffer);
+    /// let value = SingleVariant(Request::Ping(7));
+    /// serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    /// assert_eq!(&[7], writer.written_buffer());
+    /// ```
+    pub const fn omit_single_variant_tag(mut self) -> Self {
+        self.omit_single_variant_tag = true;
+        self
+    }
+
+    /// Write the entries of maps sorted by the serialized bytes of their keys, instead of in
+    /// iteration order.
+    ///
+    /// The iteration order of e.g. a `HashMap` is random, so the same map can produce different
+    /// bytes. With this option, maps with the same entries always produce the same bytes, which
+    /// is useful for reproducible frames or when the output is hashed. This option only affects
+    /// serializing.
+    ///
+    /// Sorting requires every entry to be buffered, so this is only available with the `alloc`
+    /// feature. Packed tags can not be moved between entries, so this can not be combined with
+    /// [Config::bit_pack_tags], which results in [SerializeError::NotSupported].
+    #[cfg(feature = "alloc")]
+    pub const fn sort_map_keys(mut self) -> Self {
+        self.sort_map_keys = true;
+        self
+    }
+
+    /// Prefix every struct with its field count, which is checked when deserializing.
+    ///
+    /// The format is not self-describing, so a struct that is serialized with a different field
+    /// count than the deserializing side expects is normally read misaligned. With this option
+    /// that results in [DeserializeError::FieldCountMismatch] instead, which helps to catch schema
+    /// drift during development. The field count takes a single byte, so this only supports structs
+    /// with up to 255 fields. Struct enum variants are not prefixed.
+    pub const fn struct_field_guard(mut self) -> Self {
+        self.struct_field_guard = true;
+        self
+    }
+
+    /// Write a presence mask in front of the fields of every struct, so fields can be left out,
+    /// e.g. to only send the values of a telemetry struct that changed.
+    ///
+    /// Every group of 8 fields is preceded by a byte, where bit `n` is set if the `n`th field of
+    /// the group is present. Fields are only left out with [Config::field_filter], otherwise every
+    /// bit is set.
+    ///
+    /// Absent fields get a default value. serde does not give the deserializer access to
+    /// `Default`, so the default is the value that all zero bytes decode to: `0`, `false`, empty
+    /// strings and sequences, `None` and the first variant of an enum, which is what `Default`
+    /// returns for these types as well. [Config::struct_field_guard] and
+    /// [Config::string_enum_tags] are ignored for absent fields, so these defaults are the same
+    /// with those options. Types that have no zero value, like `NonZeroU8`, result in an error
+    /// when they are absent.
+    pub const fn field_mask(mut self) -> Self {
+        self.field_mask = true;
+        self
+    }
+
+    /// Pick the fields that are written with [Config::field_mask], which this enables as well.
+    ///
+    /// `filter` is called with the name of the struct and the index of every field, and returns
+    /// `true` for the fields that are written. serde only passes the name of a field once it is
+    /// written, after the presence mask, so fields are picked by their index. Struct enum variants
+    /// are passed the name of the variant. This option only affects serializing.
+    ///
+    /// ```
+    /// # #[macro_use] extern crate serde_derive;
+    /// # use bincode_embedded::{deserialize_with_config, serialize_with_config, BufferWriter, Config};
+    /// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    /// struct Telemetry {
+    ///     temperature: i16,
+    ///     humidity: u8,
+    /// }
+    ///
+    /// let config = Config::new().field_filter(|_, index| index == 1);
+    /// let mut buffer = [0u8; 4];
+    /// let mut writer = BufferWriter::new(&mut buffer);
+    /// let value = Telemetry { temperature: 21, humidity: 40 };
+    /// serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    /// assert_eq!(&[0b10, 40], writer.written_buffer());
+    ///
+    /// let config = Config::new().field_mask();
+    /// let value: Telemetry =
+    ///     deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..2], config).unwrap();
+    /// assert_eq!(Telemetry { temperature: 0, humidity: 40 }, value);
+    /// ```
+    pub const fn field_filter(mut self, filter: fn(&'static str, usize) -> bool) -> Self {
+        self.field_mask = true;
+        self.field_filter = Some(filter);
+        self
+    }
+
+    /// Encode both lengths and values with the given byte order. This is the same as calling
+    /// [Config::length_endian] and [Config::value_endian] with the same byte order.
+    ///
+    /// This makes the byte order that is passed to [serialize] and [deserialize] irrelevant, see
+    /// [serialize_with_endian] and [deserialize_with_endian].
+    pub const fn endian(self, endian: RuntimeEndian) -> Self {
+        self.length_endian(endian).value_endian(endian)
+    }
+}
+
+/// The amount of bytes a `bool` takes up, see [Config::bool_width].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BoolWidth {
+    /// A single byte, `0` or `1`. This is the default.
+    #[default]
+    One,
+
+    /// A `u32` that is `0` or `1`, encoded with the byte order of values. This matches a C struct
+    /// with a 32-bit bool.
+    ///
+    /// Any other value is rejected with [DeserializeError::InvalidWideBoolValue], including values
+    /// with a non-zero high byte like `0x0100_0001`.
+    Four,
+}
+
+/// The amount of bytes an enum variant index takes up, see [Config::variant_width].
+///
+/// Variant indices that do not fit result in [SerializeError::LengthOverflow].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum VariantWidth {
+    /// A `u8`, which supports enums with up to 256 variants. This is the default.
+    #[default]
+    U8,
+
+    /// A `u16`, encoded with the byte order of values.
+    U16,
+
+    /// A `u32`, encoded with the byte order of values. This matches the default format of
+    /// bincode.
+    U32,
+}
+
+/// The amount of bytes a length prefix takes up, see [Config::length_width].
+///
+/// This affects the lengths of strings, byte slices, sequences and maps. Lengths that do not fit
+/// result in [SerializeError::LengthOverflow].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LengthWidth {
+    /// A `u16` for strings, byte slices and sequences, and a `u8` for maps. This is the default.
+    #[default]
+    Compact,
+
+    /// A `u64` for every length. Together with [VariantWidth::U32], this matches the default
+    /// format of bincode with fixed-size integers, so collections like `Vec<u32>` and `String` can
+    /// be exchanged with bincode.
+    U64,
+}
@@ -1,6 +1,12 @@
 use super::*;
-use core::{marker::PhantomData, str};
-use serde::{de::*, serde_if_integer128};
+use crate::bounded::{BOUNDED_CHARS_NAME, BOUNDED_STR_NAME};
+use crate::byte_array_impl::{BYTES_NAME, BYTE_ARRAY_NAME};
+use crate::endian::{read_primitive, Primitive};
+#[cfg(feature = "heapless")]
+use crate::heapless_impl::{HEAPLESS_STRING_NAME, HEAPLESS_VEC_NAME};
+use crate::single_variant::SINGLE_VARIANT_NAME;
+use core::{convert::TryFrom, marker::PhantomData, str};
+use serde::de::*;
 
 /// Deserialize a given object from the given [CoreRead] object.
 ///
@@ -38,21 +44,297 @@ pub fn deserialize<
 >(
     reader: R,
 ) -> Result<T, DeserializeError<'a, R>> {
-    let mut deserializer = Deserializer::<'a, R, B> {
-        reader,
-        pd: PhantomData,
-    };
+    deserialize_with_config::<T, R, B>(reader, Config::new())
+}
+
+/// Deserialize a given object from a byte slice. This is the same as [deserialize] with a `&[u8]`
+/// reader, but only needs the type and the byte order:
+///
+/// ```
+/// # #[macro_use] extern crate serde_derive;
+/// # use bincode_embedded::from_bytes;
+/// #[derive(Deserialize, PartialEq, Debug)]
+/// struct Greeting<'a> {
+///     id: u8,
+///     text: &'a str,
+/// }
+///
+/// let buffer = [7, 0, 2, b'h', b'i'];
+/// let greeting = from_bytes::<Greeting, byteorder::NetworkEndian>(&buffer).unwrap();
+/// assert_eq!(Greeting { id: 7, text: "hi" }, greeting);
+/// ```
+///
+/// Borrowed values like `&str` point into `bytes`, so they live as long as the buffer.
+pub fn from_bytes<'a, T: Deserialize<'a>, B: byteorder::ByteOrder + 'static>(
+    bytes: &'a [u8],
+) -> Result<T, DeserializeError<'a, &'a [u8]>> {
+    deserialize::<T, &'a [u8], B>(bytes)
+}
+
+/// Deserialize a given object from the given [CoreRead] object with the given [Config].
+///
+/// This works the same as [deserialize], but allows the format to be tweaked. The config must
+/// match the config that was used to serialize the value.
+pub fn deserialize_with_config<
+    'a,
+    T: Deserialize<'a>,
+    R: CoreRead<'a> + 'a,
+    B: byteorder::ByteOrder + 'static,
+>(
+    reader: R,
+    config: Config,
+) -> Result<T, DeserializeError<'a, R>> {
+    let mut deserializer = Deserializer::<'a, R, B>::new(reader, config);
     T::deserialize(&mut deserializer)
 }
 
+/// Deserialize a value with the given `serde::de::DeserializeSeed` instead of a `Deserialize`
+/// implementation.
+///
+/// The seed is passed to the deserializer by value, so it can carry runtime state that decides how
+/// the input is interpreted, e.g. a lookup table that was received earlier:
+///
+/// ```
+/// # use bincode_embedded::deserialize_seed;
+/// use serde::de::{Deserialize, DeserializeSeed, Deserializer};
+///
+/// struct Lookup<'t>(&'t [&'static str]);
+///
+/// impl<'de> DeserializeSeed<'de> for Lookup<'_> {
+///     type Value = &'static str;
+///
+///     fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+///         let index = u8::deserialize(deserializer)?;
+///         Ok(self.0.get(index as usize).copied().unwrap_or("unknown"))
+///     }
+/// }
+///
+/// let names = ["temperature", "humidity"];
+/// let name = deserialize_seed::<_, _, byteorder::NetworkEndian>(&[1][..], Lookup(&names)).unwrap();
+/// assert_eq!("humidity", name);
+/// ```
+pub fn deserialize_seed<
+    'a,
+    S: DeserializeSeed<'a>,
+    R: CoreRead<'a> + 'a,
+    B: byteorder::ByteOrder + 'static,
+>(
+    reader: R,
+    seed: S,
+) -> Result<S::Value, DeserializeError<'a, R>> {
+    let mut deserializer = Deserializer::<'a, R, B>::new(reader, Config::new());
+    seed.deserialize(&mut deserializer)
+}
+
+/// Deserialize a given object from the given [CoreRead] object, and copy strings and byte slices
+/// into `scratch` instead of borrowing them from the reader.
+///
+/// This allows deserializing strings and byte slices from readers that can not return persistent
+/// data, e.g. a reader that only implements [CoreRead::read]. The data is read with
+/// [CoreRead::read_bytes_into] and handed to the visitor with `visit_str` and `visit_bytes`, so the
+/// target type must own its data, e.g. `heapless::String` or `String` on alloc builds.
+///
+/// The scratch buffer is reused for every string and byte slice, so it must be at least as large as
+/// the longest one. Longer values result in [DeserializeError::ScratchTooSmall]. The scratch buffer
+/// can not be part of [Config], as a config is `Copy`.
+pub fn deserialize_with_scratch<
+    'a,
+    T: Deserialize<'a>,
+    R: CoreRead<'a> + 'a,
+    B: byteorder::ByteOrder + 'static,
+>(
+    reader: R,
+    config: Config,
+    scratch: &'a mut [u8],
+) -> Result<T, DeserializeError<'a, R>> {
+    let mut deserializer = Deserializer::<'a, R, B>::new(reader, config).with_scratch(scratch);
+    T::deserialize(&mut deserializer)
+}
+
+/// Deserialize a given object from the given [CoreRead] object with a byte order that is picked at
+/// runtime, e.g. after negotiating it with a peer.
+///
+/// Every integer that is read checks `endian`, so prefer [deserialize] when the byte order is
+/// known at compile time.
+pub fn deserialize_with_endian<'a, T: Deserialize<'a>, R: CoreRead<'a> + 'a>(
+    reader: R,
+    endian: RuntimeEndian,
+) -> Result<T, DeserializeError<'a, R>> {
+    deserialize_with_config::<T, R, byteorder::NetworkEndian>(reader, Config::new().endian(endian))
+}
+
+/// Deserialize a given object from the given [CoreRead] object, and check that the reader is fully
+/// consumed afterwards.
+///
+/// This works the same as [deserialize], but returns [DeserializeError::TrailingBytes] if there are
+/// bytes left in the reader. This catches framing mistakes that [deserialize] silently ignores.
+///
+/// ```
+/// # use bincode_embedded::{deserialize_exact, DeserializeError};
+/// let result = deserialize_exact::<u16, _, byteorder::NetworkEndian>(&[0, 1, 2][..]);
+/// assert!(matches!(result, Err(DeserializeError::TrailingBytes(1))));
+/// ```
+pub fn deserialize_exact<
+    'a,
+    T: Deserialize<'a>,
+    R: CoreRead<'a> + RemainingBytes + 'a,
+    B: byteorder::ByteOrder + 'static,
+>(
+    reader: R,
+) -> Result<T, DeserializeError<'a, R>> {
+    let (value, reader) = deserialize_and_return::<T, R, B>(reader)?;
+    match reader.remaining() {
+        0 => Ok(value),
+        remaining => Err(DeserializeError::TrailingBytes(remaining)),
+    }
+}
+
+/// Deserialize a given object that only consists of bytes, without picking a byte order. See
+/// [serialize_bytes_only] for the supported types.
+///
+/// Values that are encoded with a byte order result in [DeserializeError::ByteOrderRequired].
+pub fn deserialize_bytes_only<'a, T: Deserialize<'a>, R: CoreRead<'a> + 'a>(
+    reader: R,
+) -> Result<T, DeserializeError<'a, R>> {
+    let mut config = Config::new();
+    config.bytes_only = true;
+    deserialize_with_config::<T, R, byteorder::NetworkEndian>(reader, config)
+}
+
+/// Deserialize a value that was written with [serialize_versioned], and return the version
+/// together with the value.
+///
+/// This requires the type to be known before the version is read. When the type depends on the
+/// version, read the version with [deserialize_and_return] first:
+///
+/// ```
+/// # use bincode_embedded::{deserialize, deserialize_and_return};
+/// let buffer = [2, 0, 5];
+/// let (version, reader) =
+///     deserialize_and_return::<u8, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+/// let value = match version {
+///     1 => deserialize::<u8, _, byteorder::NetworkEndian>(reader).unwrap() as u16,
+///     _ => deserialize::<u16, _, byteorder::NetworkEndian>(reader).unwrap(),
+/// };
+/// assert_eq!(5, value);
+/// ```
+pub fn deserialize_versioned<
+    'a,
+    T: Deserialize<'a>,
+    R: CoreRead<'a> + 'a,
+    B: byteorder::ByteOrder + 'static,
+>(
+    reader: R,
+) -> Result<(u8, T), DeserializeError<'a, R>> {
+    deserialize::<(u8, T), R, B>(reader)
+}
+
+/// Deserialize a value that was written with [serialize_with_magic].
+///
+/// Exactly `magic.len()` bytes are read and compared to `magic` before the value is read. If they
+/// differ, [DeserializeError::BadMagic] is returned without reading the value. This rejects frames
+/// of another protocol early:
+///
+/// ```
+/// # use bincode_embedded::{deserialize_with_magic, DeserializeError};
+/// let value = deserialize_with_magic::<u16, _, byteorder::NetworkEndian>(b"BE", &b"BE\x00\x07"[..]);
+/// assert_eq!(7, value.unwrap());
+/// let value = deserialize_with_magic::<u16, _, byteorder::NetworkEndian>(b"BE", &b"GET "[..]);
+/// assert!(matches!(value, Err(DeserializeError::BadMagic)));
+/// ```
+pub fn deserialize_with_magic<
+    'a,
+    T: Deserialize<'a>,
+    R: CoreRead<'a> + 'a,
+    B: byteorder::ByteOrder + 'static,
+>(
+    magic: &[u8],
+    reader: R,
+) -> Result<T, DeserializeError<'a, R>> {
+    let mut deserializer = Deserializer::<'a, R, B>::new(reader, Config::new());
+    deserializer.read_magic(magic)?;
+    T::deserialize(&mut deserializer)
+}
+
+/// Deserialize a given object from the given [CoreRead] object into an existing value.
+///
+/// This uses `Deserialize::deserialize_in_place`, which updates the fields of `place` one by one
+/// instead of building a new value on the stack and moving it into `place`. This is useful for
+/// large structs on devices with a small stack. `#[derive(Deserialize)]` only implements this when
+/// the `deserialize_in_place` feature of `serde_derive` is enabled, otherwise a new value is built
+/// and assigned to `place`.
+///
+/// If an error is returned, `place` can be partially updated.
+pub fn deserialize_in_place<
+    'a,
+    T: Deserialize<'a>,
+    R: CoreRead<'a> + 'a,
+    B: byteorder::ByteOrder + 'static,
+>(
+    reader: R,
+    place: &mut T,
+) -> Result<(), DeserializeError<'a, R>> {
+    let mut deserializer = Deserializer::<'a, R, B>::new(reader, Config::new());
+    T::deserialize_in_place(&mut deserializer, place)
+}
+
+/// Deserialize a given object from the given [CoreRead] object, and return the reader afterwards.
+///
+/// This works the same as [deserialize], but gives the reader back so it can be used to read more
+/// values, or to inspect the final state of a stateful reader.
+///
+/// ```
+/// # use bincode_embedded::deserialize_and_return;
+/// let buffer: [u8; 3] = [1, 0, 2];
+/// let (first, reader) =
+///     deserialize_and_return::<u8, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+/// let (second, _) =
+///     deserialize_and_return::<u16, _, byteorder::NetworkEndian>(reader).unwrap();
+/// assert_eq!((1, 2), (first, second));
+/// ```
+pub fn deserialize_and_return<
+    'a,
+    T: Deserialize<'a>,
+    R: CoreRead<'a> + 'a,
+    B: byteorder::ByteOrder + 'static,
+>(
+    reader: R,
+) -> Result<(T, R), DeserializeError<'a, R>> {
+    let mut deserializer = Deserializer::<'a, R, B>::new(reader, Config::new());
+    let value = T::deserialize(&mut deserializer)?;
+    Ok((value, deserializer.into_reader()))
+}
+
 /// Errors that can occur while deserializing
 pub enum DeserializeError<'a, R: CoreRead<'a>> {
     /// Failed to read from the provided `CoreRead`. The inner exception is given.
     Read(R::Error),
 
+    /// Failed to read from the provided `CoreRead`, with [Config::read_error_offset] enabled.
+    ReadAt {
+        /// The error of the reader.
+        source: R::Error,
+        /// The offset of the read that failed, counted from the start of the deserializer.
+        offset: usize,
+    },
+
+    /// Failed to read a struct field from the provided `CoreRead`, with
+    /// [Config::read_error_field] enabled.
+    ReadInField {
+        /// The error of the reader.
+        source: R::Error,
+        /// The name of the field that could not be read.
+        field: &'static str,
+        /// The offset of the read that failed, counted from the start of the deserializer.
+        offset: usize,
+    },
+
     /// Invalid bool value. Only `0` and `1` are valid values.
     InvalidBoolValue(u8),
 
+    /// Invalid value for a bool with [BoolWidth::Four]. Only `0` and `1` are valid values.
+    InvalidWideBoolValue(u32),
+
     /// Invalid character encoding while trying to deserialize a `&str`.
     InvalidCharEncoding,
 
@@ -61,6 +343,156 @@ pub enum DeserializeError<'a, R: CoreRead<'a>> {
 
     /// Invalid value for the `Option` part of `Option<T>`. Only `0` and `1` are accepted values.
     InvalidOptionValue(u8),
+
+    /// An integer is `0` where a non-zero value is expected, e.g. a `core::num::NonZeroU32`.
+    InvalidNonZero,
+
+    /// The value needs more bytes than the limit that was set with [Config::limit].
+    LimitExceeded,
+
+    /// A string or byte slice does not fit in the scratch buffer that was passed to
+    /// [deserialize_with_scratch].
+    ScratchTooSmall {
+        /// The length of the string or byte slice.
+        len: usize,
+        /// The length of the scratch buffer.
+        scratch: usize,
+    },
+
+    /// The visitor of a sequence or map stopped before all elements were read. The elements can not
+    /// be skipped as their type is unknown, so the reader is left directly after the last element
+    /// that was read. The amount of elements that were not read is given.
+    UnconsumedElements(usize),
+
+    /// The reader has bytes left after the value is deserialized with [deserialize_exact]. The
+    /// amount of bytes that are left is given.
+    TrailingBytes(usize),
+
+    /// A value that is encoded with a byte order was deserialized with [deserialize_bytes_only].
+    ByteOrderRequired,
+
+    /// A delimiter was not found before the end of the reader, e.g. the `0` terminator of a string
+    /// with [Config::cstr_mode]. This is also returned if the reader does not implement
+    /// [CoreRead::read_until].
+    DelimiterNotFound,
+
+    /// The length prefix of a [BoundedStr] is larger than its maximum length. The string itself is
+    /// not read.
+    StringTooLong {
+        /// The length that was read from the length prefix.
+        len: usize,
+        /// The maximum length of the [BoundedStr].
+        max: usize,
+    },
+
+    /// A [BoundedChars] has more characters than its maximum. Characters can take more than one
+    /// byte, so this is checked after the string is read.
+    TooManyChars {
+        /// The amount of characters in the string.
+        count: usize,
+        /// The maximum amount of characters of the [BoundedChars].
+        max: usize,
+    },
+
+    /// A string does not fit in the `heapless::String` it is deserialized into, see
+    /// `heapless_string`. The string itself is not read.
+    StringCapacityExceeded {
+        /// The length that was read from the length prefix.
+        len: usize,
+        /// The capacity of the `heapless::String`.
+        capacity: usize,
+    },
+
+    /// A sequence does not fit in the `heapless::Vec` it is deserialized into, see `heapless_vec`.
+    /// None of the elements are read.
+    VecCapacityExceeded {
+        /// The length that was read from the length prefix.
+        len: usize,
+        /// The capacity of the `heapless::Vec`.
+        capacity: usize,
+    },
+
+    /// The name of an enum variant does not match any variant of the enum, see
+    /// [Config::string_enum_tags].
+    UnknownVariant,
+
+    /// The index of an enum variant is not smaller than the amount of variants of the enum.
+    /// Contains the index.
+    UnknownVariantIndex(u32),
+
+    /// Values are nested deeper than [Config::max_depth] allows. Contains the maximum depth.
+    DepthExceeded(usize),
+
+    /// A serde feature was used that needs a self-describing format, e.g. `flatten` for
+    /// `#[serde(flatten)]` fields, or `deserialize_any` for untagged enums.
+    NotSupported(&'static str),
+
+    /// The field count of a struct does not match the struct that is deserialized, see
+    /// [Config::struct_field_guard].
+    FieldCountMismatch {
+        /// The amount of fields of the struct that is deserialized.
+        expected: usize,
+        /// The amount of fields that was read from the input.
+        found: usize,
+    },
+
+    /// The magic number in front of a value does not match, see [deserialize_with_magic].
+    BadMagic,
+
+    /// A `Deserialize` implementation returned an error with `serde::de::Error::custom`, e.g. for
+    /// an invalid value. The message is not kept, as there is no allocator to store it in.
+    Custom,
+}
+
+impl<'a, R: CoreRead<'a>> DeserializeError<'a, R> {
+    /// Replace the errors of the reader with the result of `f`, and keep every other error.
+    pub(crate) fn map_read<S: CoreRead<'a>>(
+        self,
+        f: impl FnOnce(R::Error) -> DeserializeError<'a, S>,
+    ) -> DeserializeError<'a, S> {
+        match self {
+            DeserializeError::Read(source)
+            | DeserializeError::ReadAt { source, .. }
+            | DeserializeError::ReadInField { source, .. } => f(source),
+            DeserializeError::InvalidBoolValue(v) => DeserializeError::InvalidBoolValue(v),
+            DeserializeError::InvalidWideBoolValue(v) => DeserializeError::InvalidWideBoolValue(v),
+            DeserializeError::InvalidCharEncoding => DeserializeError::InvalidCharEncoding,
+            DeserializeError::Utf8(e) => DeserializeError::Utf8(e),
+            DeserializeError::InvalidOptionValue(v) => DeserializeError::InvalidOptionValue(v),
+            DeserializeError::InvalidNonZero => DeserializeError::InvalidNonZero,
+            DeserializeError::LimitExceeded => DeserializeError::LimitExceeded,
+            DeserializeError::ScratchTooSmall { len, scratch } => {
+                DeserializeError::ScratchTooSmall { len, scratch }
+            }
+            DeserializeError::UnconsumedElements(len) => DeserializeError::UnconsumedElements(len),
+            DeserializeError::TrailingBytes(len) => DeserializeError::TrailingBytes(len),
+            DeserializeError::ByteOrderRequired => DeserializeError::ByteOrderRequired,
+            DeserializeError::DelimiterNotFound => DeserializeError::DelimiterNotFound,
+            DeserializeError::StringTooLong { len, max } => {
+                DeserializeError::StringTooLong { len, max }
+            }
+            DeserializeError::TooManyChars { count, max } => {
+                DeserializeError::TooManyChars { count, max }
+            }
+            DeserializeError::StringCapacityExceeded { len, capacity } => {
+                DeserializeError::StringCapacityExceeded { len, capacity }
+            }
+            DeserializeError::VecCapacityExceeded { len, capacity } => {
+                DeserializeError::VecCapacityExceeded { len, capacity }
+            }
+            DeserializeError::UnknownVariant => DeserializeError::UnknownVariant,
+            DeserializeError::UnknownVariantIndex(index) => {
+                DeserializeError::UnknownVariantIndex(index)
+            }
+            DeserializeError::DepthExceeded(max) => DeserializeError::DepthExceeded(max),
+            DeserializeError::NotSupported(feature) => DeserializeError::NotSupported(feature),
+            DeserializeError::FieldCountMismatch { expected, found } => {
+                DeserializeError::FieldCountMismatch { expected, found }
+            }
+            DeserializeError::BadMagic => DeserializeError::BadMagic,
+            DeserializeError::Custom => DeserializeError::Custom,
+        }
+    }
 }
 
 impl<'a, R: CoreRead<'a>> From<str::Utf8Error> for DeserializeError<'a, R> {
@@ -72,181 +504,960 @@ impl<'a, R: CoreRead<'a>> From<str::Utf8Error> for DeserializeError<'a, R> {
 impl<'a, R: CoreRead<'a>> core::fmt::Debug for DeserializeError<'a, R> {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
-            DeserializeError::Read(e) => write!(fmt, "{:?}", e),
+            DeserializeError::Read(e) => fmt.debug_tuple("Read").field(e).finish(),
+            DeserializeError::ReadAt { source, offset } => fmt
+                .debug_struct("ReadAt")
+                .field("source", source)
+                .field("offset", offset)
+                .finish(),
+            DeserializeError::ReadInField {
+                source,
+                field,
+                offset,
+            } => fmt
+                .debug_struct("ReadInField")
+                .field("source", source)
+                .field("field", field)
+                .field("offset", offset)
+                .finish(),
             DeserializeError::InvalidBoolValue(v) => {
-                write!(fmt, "Unknown bool value, got {}, expected 0 or 1", v)
+                fmt.debug_tuple("InvalidBoolValue").field(v).finish()
             }
-            DeserializeError::InvalidCharEncoding => write!(fmt, "Invalid character encoding"),
-            DeserializeError::Utf8(e) => write!(
-                fmt,
-                "Could not deserialize the value as a value UTF8 string: {:?}",
-                e
-            ),
-            DeserializeError::InvalidOptionValue(e) => {
-                write!(fmt, "Invalid Option value, got {}, expected 0 or 1", e)
+            DeserializeError::InvalidWideBoolValue(v) => {
+                fmt.debug_tuple("InvalidWideBoolValue").field(v).finish()
+            }
+            DeserializeError::InvalidCharEncoding => fmt.write_str("InvalidCharEncoding"),
+            DeserializeError::Utf8(e) => fmt.debug_tuple("Utf8").field(e).finish(),
+            DeserializeError::InvalidOptionValue(v) => {
+                fmt.debug_tuple("InvalidOptionValue").field(v).finish()
+            }
+            DeserializeError::InvalidNonZero => fmt.write_str("InvalidNonZero"),
+            DeserializeError::LimitExceeded => fmt.write_str("LimitExceeded"),
+            DeserializeError::ScratchTooSmall { len, scratch } => fmt
+                .debug_struct("ScratchTooSmall")
+                .field("len", len)
+                .field("scratch", scratch)
+                .finish(),
+            DeserializeError::UnconsumedElements(len) => {
+                fmt.debug_tuple("UnconsumedElements").field(len).finish()
+            }
+            DeserializeError::TrailingBytes(len) => {
+                fmt.debug_tuple("TrailingBytes").field(len).finish()
+            }
+            DeserializeError::ByteOrderRequired => fmt.write_str("ByteOrderRequired"),
+            DeserializeError::DelimiterNotFound => fmt.write_str("DelimiterNotFound"),
+            DeserializeError::StringTooLong { len, max } => fmt
+                .debug_struct("StringTooLong")
+                .field("len", len)
+                .field("max", max)
+                .finish(),
+            DeserializeError::TooManyChars { count, max } => fmt
+                .debug_struct("TooManyChars")
+                .field("count", count)
+                .field("max", max)
+                .finish(),
+            DeserializeError::StringCapacityExceeded { len, capacity } => fmt
+                .debug_struct("StringCapacityExceeded")
+                .field("len", len)
+                .field("capacity", capacity)
+                .finish(),
+            DeserializeError::VecCapacityExceeded { len, capacity } => fmt
+                .debug_struct("VecCapacityExceeded")
+                .field("len", len)
+                .field("capacity", capacity)
+                .finish(),
+            DeserializeError::UnknownVariant => fmt.write_str("UnknownVariant"),
+            DeserializeError::UnknownVariantIndex(index) => {
+                fmt.debug_tuple("UnknownVariantIndex").field(index).finish()
+            }
+            DeserializeError::DepthExceeded(max) => {
+                fmt.debug_tuple("DepthExceeded").field(max).finish()
             }
+            DeserializeError::NotSupported(feature) => {
+                fmt.debug_tuple("NotSupported").field(feature).finish()
+            }
+            DeserializeError::FieldCountMismatch { expected, found } => fmt
+                .debug_struct("FieldCountMismatch")
+                .field("expected", expected)
+                .field("found", found)
+                .finish(),
+            DeserializeError::BadMagic => fmt.write_str("BadMagic"),
+            DeserializeError::Custom => fmt.write_str("Custom"),
         }
     }
 }
 
 impl<'a, R: CoreRead<'a>> core::fmt::Display for DeserializeError<'a, R> {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
-        write!(fmt, "{:?}", self)
+        match self {
+            DeserializeError::Read(e) => write!(fmt, "read error: {:?}", e),
+            DeserializeError::ReadAt { source, offset } => {
+                write!(fmt, "read error at offset {}: {:?}", offset, source)
+            }
+            DeserializeError::ReadInField {
+                source,
+                field,
+                offset,
+            } => write!(
+                fmt,
+                "read error in field `{}` at offset {}: {:?}",
+                field, offset, source
+            ),
+            DeserializeError::InvalidBoolValue(v) => {
+                write!(fmt, "invalid bool value {}, expected 0 or 1", v)
+            }
+            DeserializeError::InvalidWideBoolValue(v) => {
+                write!(fmt, "invalid bool value {:#x}, expected 0 or 1", v)
+            }
+            DeserializeError::InvalidCharEncoding => write!(fmt, "invalid character encoding"),
+            DeserializeError::Utf8(e) => write!(fmt, "string is not valid UTF-8: {}", e),
+            DeserializeError::InvalidOptionValue(v) => {
+                write!(fmt, "invalid Option tag {}, expected 0 or 1", v)
+            }
+            DeserializeError::InvalidNonZero => write!(fmt, "expected a non-zero integer, got 0"),
+            DeserializeError::LimitExceeded => write!(fmt, "deserialize limit exceeded"),
+            DeserializeError::ScratchTooSmall { len, scratch } => write!(
+                fmt,
+                "scratch buffer is too small, got {} bytes, expected at least {}",
+                scratch, len
+            ),
+            DeserializeError::UnconsumedElements(len) => {
+                write!(fmt, "sequence has {} elements that were not read", len)
+            }
+            DeserializeError::TrailingBytes(len) => {
+                write!(fmt, "{} trailing bytes after the value", len)
+            }
+            DeserializeError::ByteOrderRequired => write!(fmt, "value requires a byte order"),
+            DeserializeError::DelimiterNotFound => write!(fmt, "delimiter not found"),
+            DeserializeError::StringTooLong { len, max } => write!(
+                fmt,
+                "string is too long, got {} bytes, expected at most {}",
+                len, max
+            ),
+            DeserializeError::TooManyChars { count, max } => write!(
+                fmt,
+                "string has too many characters, got {}, expected at most {}",
+                count, max
+            ),
+            DeserializeError::StringCapacityExceeded { len, capacity } => write!(
+                fmt,
+                "string does not fit, got {} bytes, capacity is {}",
+                len, capacity
+            ),
+            DeserializeError::VecCapacityExceeded { len, capacity } => write!(
+                fmt,
+                "sequence does not fit, got {} elements, capacity is {}",
+                len, capacity
+            ),
+            DeserializeError::UnknownVariant => write!(fmt, "unknown enum variant name"),
+            DeserializeError::UnknownVariantIndex(index) => {
+                write!(fmt, "unknown enum variant index {}", index)
+            }
+            DeserializeError::DepthExceeded(max) => {
+                write!(fmt, "values are nested deeper than {} levels", max)
+            }
+            DeserializeError::NotSupported(feature) => write!(fmt, "{} is not supported", feature),
+            DeserializeError::FieldCountMismatch { expected, found } => {
+                write!(fmt, "struct has {} fields, expected {}", found, expected)
+            }
+            DeserializeError::BadMagic => write!(fmt, "magic number does not match"),
+            DeserializeError::Custom => write!(fmt, "custom error of a Deserialize implementation"),
+        }
     }
 }
 
+impl<'a, R: CoreRead<'a>> serde::de::StdError for DeserializeError<'a, R> {}
+
 impl<'a, R: CoreRead<'a>> Error for DeserializeError<'a, R> {
     fn custom<T: core::fmt::Display>(_cause: T) -> Self {
-        panic!("Custom error thrown: {}", _cause);
+        DeserializeError::Custom
+    }
+
+    fn invalid_value(unexp: Unexpected, exp: &dyn Expected) -> Self {
+        match unexp {
+            // serde's `NonZero*` types reject a zero value with this error
+            Unexpected::Unsigned(0) | Unexpected::Signed(0) if expects_nonzero(exp) => {
+                DeserializeError::InvalidNonZero
+            }
+            _ => Self::custom(format_args!("invalid value: {}, expected {}", unexp, exp)),
+        }
     }
 }
 
+/// Check if `exp` belongs to one of serde's `NonZero*` types, which expect "a nonzero u8" etc.
+fn expects_nonzero(exp: &dyn Expected) -> bool {
+    /// Compares the formatted text to `rest`, without storing it.
+    struct Prefix {
+        rest: &'static str,
+    }
+
+    impl core::fmt::Write for Prefix {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            let len = s.len().min(self.rest.len());
+            if s.as_bytes()[..len] != self.rest.as_bytes()[..len] {
+                return Err(core::fmt::Error);
+            }
+            self.rest = &self.rest[len..];
+            Ok(())
+        }
+    }
+
+    let mut prefix = Prefix { rest: "a nonzero " };
+    core::fmt::write(&mut prefix, format_args!("{}", exp)).is_ok() && prefix.rest.is_empty()
+}
+
 fn get_slice_length<'a, R: CoreRead<'a>, B: byteorder::ByteOrder + 'static>(
-    reader: &mut R,
-) -> Result<usize, R::Error> {
-    let buf = reader.read_range(2)?;
-    let len: SliceLenType = B::read_u16(buf);
-    Ok(len as usize)
+    deserializer: &mut Deserializer<'a, R, B>,
+) -> Result<usize, DeserializeError<'a, R>> {
+    deserializer.read_len::<SliceLenType>()
 }
 
 fn get_str_length<'a, R: CoreRead<'a>, B: byteorder::ByteOrder + 'static>(
-    reader: &mut R,
-) -> Result<usize, R::Error> {
-    let buf = reader.read_range(2)?;
-    let len: StrLenType = B::read_u16(buf);
-    Ok(len as usize)
+    deserializer: &mut Deserializer<'a, R, B>,
+) -> Result<usize, DeserializeError<'a, R>> {
+    deserializer.read_len::<StrLenType>()
 }
 
 fn get_seq_len<'a, R: CoreRead<'a>, B: byteorder::ByteOrder + 'static>(
-    reader: &mut R,
-) -> Result<usize, R::Error> {
-    let buf = reader.read_range(2)?;
-    let len: SequenceLengthType = B::read_u16(buf);
-    Ok(len as usize)
+    deserializer: &mut Deserializer<'a, R, B>,
+) -> Result<usize, DeserializeError<'a, R>> {
+    deserializer.read_len::<SequenceLengthType>()
+}
+
+fn get_map_len<'a, R: CoreRead<'a>, B: byteorder::ByteOrder + 'static>(
+    deserializer: &mut Deserializer<'a, R, B>,
+) -> Result<usize, DeserializeError<'a, R>> {
+    deserializer.read_len::<MapLenType>()
 }
 
 /// A deserializer that can be used to deserialize any `serde::Deserialize` type from a given
 /// [CoreRead] reader.
 pub struct Deserializer<'a, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> {
     reader: R,
+    config: Config,
+    bytes_read: usize,
+    /// Packed tags that are not read yet when [Config::bit_pack_tags] is enabled.
+    tag_bits: u8,
+    tag_count: u8,
+    /// The buffer that strings and byte slices are copied into, see [deserialize_with_scratch].
+    scratch: Option<&'a mut [u8]>,
+    /// The amount of sequences, structs, options and enums that are being deserialized, see
+    /// [Config::max_depth].
+    depth: usize,
+    /// Called with every primitive value that is read, see [Deserializer::with_observer].
+    observer: Option<&'a mut dyn FnMut(Observed)>,
+    /// The struct field that is being deserialized, see [Config::read_error_field].
+    field: Option<&'static str>,
+    /// The depth of the innermost [SingleVariant](crate::SingleVariant) that is being
+    /// deserialized, see [Config::omit_single_variant_tag].
+    single_variant: Option<usize>,
     pd: PhantomData<&'a B>,
 }
 
-impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> serde::Deserializer<'a>
-    for &'b mut Deserializer<'a, R, B>
+/// A primitive value that is handed to the observer of a [Deserializer], see
+/// [Deserializer::with_observer].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(missing_docs)]
+pub enum Observed<'b> {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    Str(&'b str),
+    Bytes(&'b [u8]),
+}
+
+fn observe(observer: &mut Option<&mut dyn FnMut(Observed)>, value: Observed) {
+    if let Some(observer) = observer {
+        observer(value);
+    }
+}
+
+impl<'a, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> Deserializer<'a, R, B> {
+    /// Create a new deserializer. Values can be deserialized by passing `&mut deserializer` to
+    /// `serde::Deserialize::deserialize`.
+    ///
+    /// Most code should use [deserialize] or [deserialize_with_config] instead. This is useful to
+    /// mix serde values with the inherent methods on this type:
+    ///
+    /// ```
+    /// # use bincode_embedded::{Config, Deserializer};
+    /// # use serde::Deserialize;
+    /// let buffer = [5, b'a', b'b', b'c'];
+    /// let mut deserializer =
+    ///     Deserializer::<_, byteorder::NetworkEndian>::new(&buffer[..], Config::new());
+    /// let len = u8::deserialize(&mut deserializer).unwrap();
+    /// assert_eq!(5, len);
+    /// assert_eq!("abc", deserializer.deserialize_str_len(3).unwrap());
+    /// ```
+    pub fn new(reader: R, config: Config) -> Self {
+        Self {
+            reader,
+            config,
+            bytes_read: 0,
+            tag_bits: 0,
+            tag_count: 0,
+            scratch: None,
+            depth: 0,
+            observer: None,
+            field: None,
+            single_variant: None,
+            pd: PhantomData,
+        }
+    }
+
+    /// Copy strings and byte slices into `scratch`, see [deserialize_with_scratch].
+    pub fn with_scratch(mut self, scratch: &'a mut [u8]) -> Self {
+        self.scratch = Some(scratch);
+        self
+    }
+
+    /// Call `observer` with every primitive value that is read, before it is handed to the
+    /// visitor. This is useful to log or check a stream without writing a custom `Deserialize`
+    /// implementation.
+    ///
+    /// Integers, floats, `bool`, `char`, strings and byte slices are observed. Length prefixes,
+    /// `Option` tags and enum variant indices are not. Without an observer, this costs a single
+    /// check for every value.
+    ///
+    /// ```
+    /// # use bincode_embedded::{Config, Deserializer, Observed};
+    /// # use serde::Deserialize;
+    /// let buffer = [1, 0, 2, 0, 1, b'a'];
+    /// let mut count = 0;
+    /// let mut observer = |value: Observed| {
+    ///     if let Observed::U16(_) = value {
+    ///         count += 1;
+    ///     }
+    /// };
+    /// let mut deserializer =
+    ///     Deserializer::<_, byteorder::NetworkEndian>::new(&buffer[..], Config::new())
+    ///         .with_observer(&mut observer);
+    /// let value = <(u8, u16, &str)>::deserialize(&mut deserializer).unwrap();
+    /// assert_eq!((1, 2, "a"), value);
+    /// assert_eq!(1, count);
+    /// ```
+    pub fn with_observer(mut self, observer: &'a mut dyn FnMut(Observed)) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Get the reader back.
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    /// Read a string of `len` bytes that has no length prefix, e.g. a string that was written with
+    /// [Serializer::serialize_str_unprefixed].
+    ///
+    /// The string is borrowed from the reader, [Config::copy_strings] does not affect this method.
+    pub fn deserialize_str_len(&mut self, len: usize) -> Result<&'a str, DeserializeError<'a, R>> {
+        if len == 0 {
+            // The serializer ends the byte of packed tags for an empty string as well
+            self.claim(0)?;
+            return Ok("");
+        }
+        let buf = self.read_range(len)?;
+        Ok(str::from_utf8(buf)?)
+    }
+
+    /// Read a sequence of `len` elements that has no length prefix, e.g. a sequence that was
+    /// written with [Serializer::serialize_seq_no_len]. The elements are handed to the `visit_seq`
+    /// method of `visitor`.
+    ///
+    /// Fixed-size arrays are encoded the same way, so this is only needed when the length is not
+    /// known at compile time.
+    pub fn deserialize_seq_with_len<V: Visitor<'a>>(
+        &mut self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError<'a, R>> {
+        serde::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    /// Read a byte slice up to the given `delimiter`, see [CoreRead::read_until]. The delimiter is
+    /// consumed, but is not part of the returned slice.
+    ///
+    /// Returns [DeserializeError::DelimiterNotFound] if the reader ends before the delimiter.
+    pub fn deserialize_until(
+        &mut self,
+        delimiter: u8,
+    ) -> Result<&'a [u8], DeserializeError<'a, R>> {
+        self.read_until(delimiter)
+    }
+
+    /// Skip bytes until the amount of bytes that is read by this deserializer is a multiple of
+    /// `alignment`. An `alignment` of `0` or `1` does nothing.
+    ///
+    /// This skips the padding that is written by [BufferWriter::align_to]. The skipped bytes are
+    /// not checked. Offsets are counted from the start of this deserializer, so the writer and the
+    /// deserializer must start at the same position.
+    pub fn align_to(&mut self, alignment: usize) -> Result<(), DeserializeError<'a, R>> {
+        if alignment <= 1 {
+            return Ok(());
+        }
+        let padding = (alignment - self.bytes_read % alignment) % alignment;
+        for _ in 0..padding {
+            self.read_byte()?;
+        }
+        Ok(())
+    }
+
+    /// Read the index of an enum variant, and check it against the variants of the enum. See
+    /// [Config::variant_width].
+    fn read_variant_index(
+        &mut self,
+        variants: &'static [&'static str],
+    ) -> Result<u32, DeserializeError<'a, R>> {
+        let index = match self.config.variant_width {
+            VariantWidth::U8 => self.read_value::<u8>()? as u32,
+            VariantWidth::U16 => self.read_value::<u16>()? as u32,
+            VariantWidth::U32 => self.read_value::<u32>()?,
+        };
+        if index as usize >= variants.len() {
+            return Err(DeserializeError::UnknownVariantIndex(index));
+        }
+        Ok(index)
+    }
+
+    /// Read the name of an enum variant and return its index in `variants`, see
+    /// [Config::string_enum_tags].
+    fn read_variant_name(
+        &mut self,
+        variants: &'static [&'static str],
+    ) -> Result<u32, DeserializeError<'a, R>> {
+        let length = get_str_length(self)?;
+        let name = if length == 0 {
+            &[][..]
+        } else {
+            self.read_transient(length)?
+        };
+        variants
+            .iter()
+            .position(|variant| variant.as_bytes() == name)
+            .map(|index| index as u32)
+            .ok_or(DeserializeError::UnknownVariant)
+    }
+
+    /// Start deserializing a nested value, and check the depth against [Config::max_depth].
+    fn enter(&mut self) -> Result<(), DeserializeError<'a, R>> {
+        self.depth += 1;
+        match self.config.max_depth {
+            Some(max) if self.depth > max => {
+                self.depth -= 1;
+                Err(DeserializeError::DepthExceeded(max))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Finish deserializing a nested value that was started with [Deserializer::enter].
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Register that `len` bytes are about to be read, and check this against the configured limit.
+    fn claim(&mut self, len: usize) -> Result<(), DeserializeError<'a, R>> {
+        // Any read ends the current byte of packed tags, see `Config::bit_pack_tags`
+        self.tag_count = 0;
+
+        let total = self.bytes_read.saturating_add(len);
+        if let Some(limit) = self.config.limit {
+            if total > limit {
+                return Err(DeserializeError::LimitExceeded);
+            }
+        }
+        self.bytes_read = total;
+        Ok(())
+    }
+
+    /// Read a `bool` or an `Option` tag. With [Config::bit_pack_tags] this takes the next bit of
+    /// the packed byte, otherwise this reads a full byte.
+    fn read_tag(&mut self) -> Result<u8, DeserializeError<'a, R>> {
+        if !self.config.bit_pack_tags {
+            return self.read_byte();
+        }
+        if self.tag_count == 0 {
+            self.tag_bits = self.read_byte()?;
+            self.tag_count = 8;
+        }
+        let tag = self.tag_bits & 1;
+        self.tag_bits >>= 1;
+        self.tag_count -= 1;
+        Ok(tag)
+    }
+
+    /// Wrap an error of the reader, for a read that starts at the current offset. With
+    /// [Config::read_error_offset] this includes the offset.
+    fn read_error(&self) -> impl FnOnce(R::Error) -> DeserializeError<'a, R> {
+        let offset = self.bytes_read;
+        let with_offset = self.config.read_error_offset;
+        let field = self.field;
+        move |source| {
+            if let Some(field) = field {
+                DeserializeError::ReadInField {
+                    source,
+                    field,
+                    offset,
+                }
+            } else if with_offset {
+                DeserializeError::ReadAt { source, offset }
+            } else {
+                DeserializeError::Read(source)
+            }
+        }
+    }
+
+    /// Read `magic.len()` bytes and check that they are equal to `magic`.
+    fn read_magic(&mut self, magic: &[u8]) -> Result<(), DeserializeError<'a, R>> {
+        if magic.is_empty() {
+            return Ok(());
+        }
+        let read_error = self.read_error();
+        self.claim(magic.len())?;
+        let buf = self
+            .reader
+            .read_transient(magic.len())
+            .map_err(read_error)?;
+        if buf != magic {
+            return Err(DeserializeError::BadMagic);
+        }
+        Ok(())
+    }
+
+    fn read_byte(&mut self) -> Result<u8, DeserializeError<'a, R>> {
+        let read_error = self.read_error();
+        self.claim(1)?;
+        self.reader.read().map_err(read_error)
+    }
+
+    fn read_range(&mut self, len: usize) -> Result<&'a [u8], DeserializeError<'a, R>> {
+        let read_error = self.read_error();
+        self.claim(len)?;
+        self.reader.read_range(len).map_err(read_error)
+    }
+
+    fn read_until(&mut self, delimiter: u8) -> Result<&'a [u8], DeserializeError<'a, R>> {
+        let read_error = self.read_error();
+        let buf = self
+            .reader
+            .read_until(delimiter)
+            .map_err(read_error)?
+            .ok_or(DeserializeError::DelimiterNotFound)?;
+        // The length is only known after the read, the limit includes the delimiter
+        self.claim(buf.len() + 1)?;
+        Ok(buf)
+    }
+
+    fn read_transient(&mut self, len: usize) -> Result<&[u8], DeserializeError<'a, R>> {
+        let read_error = self.read_error();
+        self.claim(len)?;
+        self.reader.read_transient(len).map_err(read_error)
+    }
+
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), DeserializeError<'a, R>> {
+        let read_error = self.read_error();
+        self.claim(buf.len())?;
+        self.reader.read_bytes_into(buf).map_err(read_error)
+    }
+
+    /// Copy `length` bytes into the scratch buffer and pass them to `visit`.
+    fn visit_scratch<T>(
+        &mut self,
+        length: usize,
+        visit: impl FnOnce(&mut Self, &[u8]) -> Result<T, DeserializeError<'a, R>>,
+    ) -> Result<T, DeserializeError<'a, R>> {
+        let scratch = match self.scratch.take() {
+            Some(scratch) => scratch,
+            None => {
+                return Err(DeserializeError::ScratchTooSmall {
+                    len: length,
+                    scratch: 0,
+                })
+            }
+        };
+        let result = if length > scratch.len() {
+            Err(DeserializeError::ScratchTooSmall {
+                len: length,
+                scratch: scratch.len(),
+            })
+        } else {
+            self.read_bytes_into(&mut scratch[..length])
+                .and_then(|()| visit(self, &scratch[..length]))
+        };
+        self.scratch = Some(scratch);
+        result
+    }
+
+    /// Read a string of at most `max` bytes and hand it to the visitor. Longer strings result in
+    /// the error of `too_long`, which is called with the length of the string and `max`.
+    fn visit_bounded_str<V: Visitor<'a>>(
+        &mut self,
+        max: usize,
+        visitor: V,
+        too_long: fn(usize, usize) -> DeserializeError<'a, R>,
+    ) -> Result<V::Value, DeserializeError<'a, R>> {
+        if self.config.cstr_mode {
+            // There is no length prefix, so the string has to be read to check the length
+            let buf = self.read_until(0)?;
+            if buf.len() > max {
+                return Err(too_long(buf.len(), max));
+            }
+            let res = str::from_utf8(buf)?;
+            self.observe(Observed::Str(res));
+            return visitor.visit_borrowed_str(res);
+        }
+        // Check the length prefix before reading the string
+        let length = get_str_length(self)?;
+        if length > max {
+            return Err(too_long(length, max));
+        }
+        self.visit_str(length, visitor)
+    }
+
+    /// Read a string of `length` bytes and hand it to the visitor.
+    fn visit_str<V: Visitor<'a>>(
+        &mut self,
+        length: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError<'a, R>> {
+        if length == 0 {
+            // Don't bother the reader with an empty read, some readers do not handle this well
+            self.observe(Observed::Str(""));
+            return visitor.visit_borrowed_str("");
+        }
+        if self.scratch.is_some() {
+            return self.visit_scratch(length, |de, buf| {
+                let res = str::from_utf8(buf)?;
+                de.observe(Observed::Str(res));
+                visitor.visit_str(res)
+            });
+        }
+        if self.config.copy_strings {
+            // Not `read_transient`, the string would borrow the observer as well
+            let read_error = self.read_error();
+            self.claim(length)?;
+            let buf = self.reader.read_transient(length).map_err(read_error)?;
+            let res = str::from_utf8(buf)?;
+            observe(&mut self.observer, Observed::Str(res));
+
+            return visitor.visit_str(res);
+        }
+        let buf = self.read_range(length)?;
+        let res = str::from_utf8(buf)?;
+        self.observe(Observed::Str(res));
+
+        visitor.visit_borrowed_str(res)
+    }
+
+    /// Hand `len` consecutive elements to the visitor. The elements are named after `fields` while
+    /// they are read, see [Config::read_error_field]. If `masked` is set, the elements are preceded
+    /// by presence masks, see [Config::field_mask].
+    fn visit_elements<V: Visitor<'a>>(
+        &mut self,
+        len: usize,
+        fields: &'static [&'static str],
+        masked: bool,
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError<'a, R>> {
+        struct Access<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> {
+            deserializer: &'b mut Deserializer<'a, R, B>,
+            len: usize,
+            fields: &'static [&'static str],
+            index: usize,
+            masked: bool,
+            mask: u8,
+        }
+
+        impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> Access<'a, 'b, R, B> {
+            /// Check if the next element is present, and read the next mask if needed.
+            fn present(&mut self) -> Result<bool, DeserializeError<'a, R>> {
+                if !self.masked {
+                    return Ok(true);
+                }
+                if self.index % 8 == 0 {
+                    self.mask = self.deserializer.read_value()?;
+                }
+                Ok(self.mask & 1 << (self.index % 8) != 0)
+            }
+        }
+
+        impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static>
+            serde::de::SeqAccess<'a> for Access<'a, 'b, R, B>
+        {
+            type Error = DeserializeError<'a, R>;
+
+            fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+            where
+                T: serde::de::DeserializeSeed<'a>,
+            {
+                if self.len > 0 {
+                    self.len -= 1;
+                    let outer = self.deserializer.field;
+                    if let Some(field) = self.fields.get(self.index) {
+                        self.deserializer.field = Some(field);
+                    }
+                    let value = match self.present() {
+                        Ok(true) => {
+                            serde::de::DeserializeSeed::deserialize(seed, &mut *self.deserializer)
+                        }
+                        Ok(false) => self.deserializer.deserialize_absent(seed),
+                        Err(e) => Err(e),
+                    };
+                    self.index += 1;
+                    self.deserializer.field = outer;
+                    Ok(Some(value?))
+                } else {
+                    Ok(None)
+                }
+            }
+
+            fn size_hint(&self) -> Option<usize> {
+                Some(self.len)
+            }
+        }
+
+        self.enter()?;
+        let mut access: Access<'a, '_, R, B> = Access {
+            deserializer: self,
+            len,
+            fields,
+            index: 0,
+            masked,
+            mask: 0,
+        };
+
+        let value = visitor.visit_seq(&mut access);
+        access.deserializer.leave();
+        let value = value?;
+        if access.len > 0 {
+            return Err(DeserializeError::UnconsumedElements(access.len));
+        }
+        Ok(value)
+    }
+
+    /// Hand the fields of a struct to the visitor.
+    fn visit_struct<V: Visitor<'a>>(
+        &mut self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError<'a, R>> {
+        let names: &'static [&'static str] = if self.config.read_error_field {
+            fields
+        } else {
+            &[]
+        };
+        self.visit_elements(fields.len(), names, self.config.field_mask, visitor)
+    }
+
+    /// Deserialize a field that is left out by [Config::field_mask], as if its bytes were all zero.
+    fn deserialize_absent<T: DeserializeSeed<'a>>(
+        &mut self,
+        seed: T,
+    ) -> Result<T::Value, DeserializeError<'a, R>> {
+        // Nothing is read from the reader, so the limit does not apply. Options that frame values
+        // with data that is checked are turned off, zero bytes would not pass those checks.
+        let mut config = self.config;
+        config.limit = None;
+        config.struct_field_guard = false;
+        config.string_enum_tags = false;
+        let mut zeroes = Deserializer::<'a, ZeroReader, B>::new(ZeroReader, config);
+        zeroes.depth = self.depth;
+        seed.deserialize(&mut zeroes).map_err(|e| {
+            e.map_read(|()| DeserializeError::NotSupported("absent byte arrays over 256 bytes"))
+        })
+    }
+
+    fn observe(&mut self, value: Observed) {
+        observe(&mut self.observer, value);
+    }
+
+    fn read_value<T: Primitive>(&mut self) -> Result<T, DeserializeError<'a, R>> {
+        self.read_primitive(self.config.value_endian)
+    }
+
+    fn read_length<T: Primitive>(&mut self) -> Result<T, DeserializeError<'a, R>> {
+        self.read_primitive(self.config.length_endian)
+    }
+
+    /// Read a length prefix as a `T`, or as a `u64` with [LengthWidth::U64].
+    fn read_len<T: Primitive + Into<usize>>(&mut self) -> Result<usize, DeserializeError<'a, R>> {
+        match self.config.length_width {
+            LengthWidth::Compact => Ok(self.read_length::<T>()?.into()),
+            LengthWidth::U64 => {
+                // A length that does not fit in memory can not be read anyway
+                let len: u64 = self.read_length()?;
+                Ok(usize::try_from(len).unwrap_or(usize::MAX))
+            }
+        }
+    }
+
+    fn read_primitive<T: Primitive>(
+        &mut self,
+        order: Option<RuntimeEndian>,
+    ) -> Result<T, DeserializeError<'a, R>> {
+        if self.config.bytes_only && T::SIZE > 1 {
+            return Err(DeserializeError::ByteOrderRequired);
+        }
+        let read_error = self.read_error();
+        self.claim(T::SIZE)?;
+        let buf = T::read_bytes(&mut self.reader).map_err(read_error)?;
+        Ok(read_primitive::<B, T>(order, buf.as_ref()))
+    }
+}
+
+impl<'a, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> serde::Deserializer<'a>
+    for &mut Deserializer<'a, R, B>
 {
     type Error = DeserializeError<'a, R>;
 
     fn deserialize_any<V: Visitor<'a>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
-        panic!("Deserialize any not supported")
+        Err(DeserializeError::NotSupported("deserialize_any"))
     }
 
     fn deserialize_bool<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let value: u8 = serde::Deserialize::deserialize(self)?;
-        match value {
-            1 => visitor.visit_bool(true),
-            0 => visitor.visit_bool(false),
-            value => Err(DeserializeError::InvalidBoolValue(value)),
-        }
+        let value = if self.config.bool_width == BoolWidth::Four && !self.config.bit_pack_tags {
+            match self.read_value::<u32>()? {
+                1 => true,
+                0 => false,
+                _ if self.config.lenient_bool => true,
+                value => return Err(DeserializeError::InvalidWideBoolValue(value)),
+            }
+        } else {
+            match self.read_tag()? {
+                1 => true,
+                0 => false,
+                _ if self.config.lenient_bool => true,
+                value => return Err(DeserializeError::InvalidBoolValue(value)),
+            }
+        };
+        self.observe(Observed::Bool(value));
+        visitor.visit_bool(value)
     }
 
     fn deserialize_i8<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let val = self.reader.read().map_err(DeserializeError::Read)?;
-        visitor.visit_i8(val as i8)
+        let val = self.read_byte()? as i8;
+        self.observe(Observed::I8(val));
+        visitor.visit_i8(val)
     }
 
     fn deserialize_i16<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let buffer = self.reader.read_range(2).map_err(DeserializeError::Read)?;
-        visitor.visit_i16(B::read_i16(&buffer))
+        let val = self.read_value()?;
+        self.observe(Observed::I16(val));
+        visitor.visit_i16(val)
     }
 
     fn deserialize_i32<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let buffer = self.reader.read_range(4).map_err(DeserializeError::Read)?;
-        visitor.visit_i32(B::read_i32(&buffer))
+        let val = self.read_value()?;
+        self.observe(Observed::I32(val));
+        visitor.visit_i32(val)
     }
 
     fn deserialize_i64<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let buffer = self.reader.read_range(8).map_err(DeserializeError::Read)?;
-        visitor.visit_i64(B::read_i64(&buffer))
+        let val = self.read_value()?;
+        self.observe(Observed::I64(val));
+        visitor.visit_i64(val)
     }
 
-    serde_if_integer128! {
-        fn deserialize_i128<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-            let buffer = self.reader.read_range(16).map_err(DeserializeError::Read)?;
-            visitor.visit_i128(B::read_i128(&buffer))
-        }
+    fn deserialize_i128<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let val = self.read_value()?;
+        self.observe(Observed::I128(val));
+        visitor.visit_i128(val)
     }
 
     fn deserialize_u8<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let val = self.reader.read().map_err(DeserializeError::Read)?;
+        let val = self.read_byte()?;
+        self.observe(Observed::U8(val));
         visitor.visit_u8(val)
     }
 
     fn deserialize_u16<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let buffer = self.reader.read_range(2).map_err(DeserializeError::Read)?;
-        visitor.visit_u16(B::read_u16(&buffer))
+        let val = self.read_value()?;
+        self.observe(Observed::U16(val));
+        visitor.visit_u16(val)
     }
 
     fn deserialize_u32<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let buffer = self.reader.read_range(4).map_err(DeserializeError::Read)?;
-        visitor.visit_u32(B::read_u32(&buffer))
+        let val = self.read_value()?;
+        self.observe(Observed::U32(val));
+        visitor.visit_u32(val)
     }
 
     fn deserialize_u64<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let buffer = self.reader.read_range(8).map_err(DeserializeError::Read)?;
-        visitor.visit_u64(B::read_u64(&buffer))
+        let val = self.read_value()?;
+        self.observe(Observed::U64(val));
+        visitor.visit_u64(val)
     }
 
-    serde_if_integer128! {
-        fn deserialize_u128<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-            let buffer = self.reader.read_range(16).map_err(DeserializeError::Read)?;
-            visitor.visit_u128(B::read_u128(&buffer))
-        }
+    fn deserialize_u128<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let val = self.read_value()?;
+        self.observe(Observed::U128(val));
+        visitor.visit_u128(val)
     }
 
     fn deserialize_f32<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let buffer = self.reader.read_range(4).map_err(DeserializeError::Read)?;
-        visitor.visit_f32(B::read_f32(&buffer))
+        let val = self.read_value()?;
+        self.observe(Observed::F32(val));
+        visitor.visit_f32(val)
     }
 
     fn deserialize_f64<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let buffer = self.reader.read_range(8).map_err(DeserializeError::Read)?;
-        visitor.visit_f64(B::read_f64(&buffer))
+        let val = self.read_value()?;
+        self.observe(Observed::F64(val));
+        visitor.visit_f64(val)
     }
 
     fn deserialize_char<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.config.fixed_char {
+            let code_point: u32 = self.read_value()?;
+            let res = char::from_u32(code_point).ok_or(DeserializeError::InvalidCharEncoding)?;
+            self.observe(Observed::Char(res));
+            return visitor.visit_char(res);
+        }
         let mut buf = [0u8; 4];
 
         // Look at the first byte to see how many bytes must be read
-        buf[0] = self.reader.read().map_err(DeserializeError::Read)?;
+        buf[0] = self.read_byte()?;
         let width = utf8_char_width(buf[0]);
         if width == 1 {
+            self.observe(Observed::Char(buf[0] as char));
             return visitor.visit_char(buf[0] as char);
         }
         if width == 0 {
             return Err(DeserializeError::InvalidCharEncoding);
         }
 
-        for byte in buf.iter_mut().take(width).skip(1) {
-            *byte = self.reader.read().map_err(DeserializeError::Read)?;
-        }
+        // Read the rest of the character at once, a truncated character is a read error and never
+        // reaches `from_utf8`
+        self.read_bytes_into(&mut buf[1..width])?;
 
         let res = str::from_utf8(&buf[..width])?
             .chars()
             .next()
             .ok_or(DeserializeError::InvalidCharEncoding)?;
+        self.observe(Observed::Char(res));
         visitor.visit_char(res)
     }
 
     fn deserialize_str<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let length = get_str_length::<R, B>(&mut self.reader).map_err(DeserializeError::Read)?;
-        let buf = self
-            .reader
-            .read_range(length)
-            .map_err(DeserializeError::Read)?;
-        let res = str::from_utf8(buf)?;
-
-        visitor.visit_borrowed_str(res)
+        if self.config.cstr_mode {
+            let buf = self.read_until(0)?;
+            let res = str::from_utf8(buf)?;
+            self.observe(Observed::Str(res));
+            return visitor.visit_borrowed_str(res);
+        }
+        let length = get_str_length(self)?;
+        self.visit_str(length, visitor)
     }
 
     fn deserialize_string<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
@@ -254,11 +1465,20 @@ impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> serde::Des
     }
 
     fn deserialize_bytes<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let length = get_slice_length::<R, B>(&mut self.reader).map_err(DeserializeError::Read)?;
-        let buf = self
-            .reader
-            .read_range(length)
-            .map_err(DeserializeError::Read)?;
+        let length = get_slice_length(self)?;
+        if length == 0 {
+            // Don't bother the reader with an empty read, some readers do not handle this well
+            self.observe(Observed::Bytes(&[]));
+            return visitor.visit_borrowed_bytes(&[]);
+        }
+        if self.scratch.is_some() {
+            return self.visit_scratch(length, |de, buf| {
+                de.observe(Observed::Bytes(buf));
+                visitor.visit_bytes(buf)
+            });
+        }
+        let buf = self.read_range(length)?;
+        self.observe(Observed::Bytes(buf));
         visitor.visit_borrowed_bytes(buf)
     }
 
@@ -267,11 +1487,14 @@ impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> serde::Des
     }
 
     fn deserialize_option<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let val = self.reader.read().map_err(DeserializeError::Read)?;
+        let val = self.read_tag()?;
         if val == 0 {
             visitor.visit_none()
         } else if val == 1 {
-            visitor.visit_some(self)
+            self.enter()?;
+            let value = visitor.visit_some(&mut *self);
+            self.leave();
+            value
         } else {
             Err(DeserializeError::InvalidOptionValue(val))
         }
@@ -291,14 +1514,20 @@ impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> serde::Des
 
     fn deserialize_newtype_struct<V: Visitor<'a>>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
+        if name == SINGLE_VARIANT_NAME && self.config.omit_single_variant_tag {
+            let outer = self.single_variant.replace(self.depth);
+            let value = visitor.visit_newtype_struct(&mut *self);
+            self.single_variant = outer;
+            return value;
+        }
         visitor.visit_newtype_struct(self)
     }
 
     fn deserialize_seq<V: Visitor<'a>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        let len = get_seq_len::<R, B>(&mut self.reader).map_err(DeserializeError::Read)?;
+        let len = get_seq_len(self)?;
         self.deserialize_tuple(len, visitor)
     }
 
@@ -307,49 +1536,78 @@ impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> serde::Des
         len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        struct Access<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> {
-            deserializer: &'b mut Deserializer<'a, R, B>,
-            len: usize,
-        }
-
-        impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static>
-            serde::de::SeqAccess<'a> for Access<'a, 'b, R, B>
-        {
-            type Error = DeserializeError<'a, R>;
-
-            fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
-            where
-                T: serde::de::DeserializeSeed<'a>,
-            {
-                if self.len > 0 {
-                    self.len -= 1;
-                    let value =
-                        serde::de::DeserializeSeed::deserialize(seed, &mut *self.deserializer)?;
-                    Ok(Some(value))
-                } else {
-                    Ok(None)
-                }
-            }
-
-            fn size_hint(&self) -> Option<usize> {
-                Some(self.len)
-            }
-        }
-
-        let access: Access<'a, 'b, R, B> = Access {
-            deserializer: self,
-            len,
-        };
-
-        visitor.visit_seq(access)
+        self.visit_elements(len, &[], false, visitor)
     }
 
     fn deserialize_tuple_struct<V: Visitor<'a>>(
         self,
-        _name: &'static str,
+        name: &'static str,
         len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
+        if name == BOUNDED_STR_NAME {
+            return self.visit_bounded_str(len, visitor, |len, max| {
+                DeserializeError::StringTooLong { len, max }
+            });
+        }
+        if name == BOUNDED_CHARS_NAME {
+            // The length prefix counts bytes, so the string has to be read to count the characters
+            let buf = if self.config.cstr_mode {
+                self.read_until(0)?
+            } else {
+                match get_str_length(self)? {
+                    0 => &[],
+                    length => self.read_range(length)?,
+                }
+            };
+            let res = str::from_utf8(buf)?;
+            let count = res.chars().count();
+            if count > len {
+                return Err(DeserializeError::TooManyChars { count, max: len });
+            }
+            self.observe(Observed::Str(res));
+            return visitor.visit_borrowed_str(res);
+        }
+        if name == BYTE_ARRAY_NAME {
+            // `len` is the length of the array, which has no length prefix
+            if len == 0 {
+                self.observe(Observed::Bytes(&[]));
+                return visitor.visit_bytes(&[]);
+            }
+            // Not `read_transient`, the bytes would borrow the observer as well
+            let read_error = self.read_error();
+            self.claim(len)?;
+            let buf = self.reader.read_transient(len).map_err(read_error)?;
+            observe(&mut self.observer, Observed::Bytes(buf));
+            return visitor.visit_bytes(buf);
+        }
+        if name == BYTES_NAME {
+            if len == 0 {
+                self.observe(Observed::Bytes(&[]));
+                return visitor.visit_borrowed_bytes(&[]);
+            }
+            let buf = self.read_range(len)?;
+            self.observe(Observed::Bytes(buf));
+            return visitor.visit_borrowed_bytes(buf);
+        }
+        #[cfg(feature = "heapless")]
+        if name == HEAPLESS_STRING_NAME {
+            return self.visit_bounded_str(len, visitor, |len, capacity| {
+                DeserializeError::StringCapacityExceeded { len, capacity }
+            });
+        }
+        #[cfg(feature = "heapless")]
+        if name == HEAPLESS_VEC_NAME {
+            // `len` is the capacity of the vec, check it before reading any element
+            let length = get_seq_len(self)?;
+            if length > len {
+                return Err(DeserializeError::VecCapacityExceeded {
+                    len: length,
+                    capacity: len,
+                });
+            }
+            return self.deserialize_tuple(length, visitor);
+        }
         self.deserialize_tuple(len, visitor)
     }
 
@@ -391,12 +1649,20 @@ impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> serde::Des
             }
         }
 
-        let len = serde::Deserialize::deserialize(&mut *self)?;
-
-        visitor.visit_map(Access {
+        let len = get_map_len(self)?;
+        self.enter()?;
+        let mut access = Access {
             deserializer: self,
             len,
-        })
+        };
+
+        let value = visitor.visit_map(&mut access);
+        access.deserializer.leave();
+        let value = value?;
+        if access.len > 0 {
+            return Err(DeserializeError::UnconsumedElements(access.len));
+        }
+        Ok(value)
     }
 
     /// Hint that the `Deserialize` type is expecting a struct with a particular
@@ -407,7 +1673,16 @@ impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> serde::Des
         fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        self.deserialize_tuple(fields.len(), visitor)
+        if self.config.struct_field_guard {
+            let found: u8 = self.read_value()?;
+            if found as usize != fields.len() {
+                return Err(DeserializeError::FieldCountMismatch {
+                    expected: fields.len(),
+                    found: found as usize,
+                });
+            }
+        }
+        self.visit_struct(fields, visitor)
     }
 
     /// Hint that the `Deserialize` type is expecting an enum value with a
@@ -415,24 +1690,50 @@ impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> serde::Des
     fn deserialize_enum<V: Visitor<'a>>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
-        _visitor: V,
+        variants: &'static [&'static str],
+        visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        unimplemented!()
+        // Only the enum that is directly wrapped in a SingleVariant has no tag
+        let wrapped = self.single_variant == Some(self.depth);
+        self.enter()?;
+        // The tag is read before the content of the variant, which is read by the visitor
+        let index = if wrapped && variants.len() == 1 {
+            Ok(0)
+        } else if wrapped {
+            Err(DeserializeError::NotSupported(
+                "SingleVariant of an enum with more than one variant",
+            ))
+        } else if self.config.string_enum_tags {
+            self.read_variant_name(variants)
+        } else {
+            self.read_variant_index(variants)
+        };
+        let value = index.and_then(|index| {
+            visitor.visit_enum(KnownVariant {
+                de: &mut *self,
+                index,
+            })
+        });
+        self.leave();
+        value
     }
 
     /// Hint that the `Deserialize` type is expecting the name of a struct
     /// field or the discriminant of an enum variant.
+    ///
+    /// The format does not contain field names. Structs with `#[serde(flatten)]` fields are
+    /// deserialized as a map of field names, which results in [DeserializeError::NotSupported].
     fn deserialize_identifier<V: Visitor<'a>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
-        panic!("Deserialize_identifier not supported")
+        Err(DeserializeError::NotSupported("flatten"))
     }
 
     /// Hint that the `Deserialize` type needs to deserialize a value whose type
     /// doesn't matter because it is ignored.
     ///
-    /// Deserializers for non-self-describing formats may not support this mode.
+    /// The format does not contain the types of values, so the size of the value is not known and
+    /// this results in [DeserializeError::NotSupported].
     fn deserialize_ignored_any<V: Visitor<'a>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
-        panic!("Deserialize_ignored_any not supported")
+        Err(DeserializeError::NotSupported("deserialize_ignored_any"))
     }
 
     fn is_human_readable(&self) -> bool {
@@ -440,6 +1741,89 @@ impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> serde::Des
     }
 }
 
+/// An enum variant whose index is already read and checked against the variants of the enum. The
+/// index can also be resolved from the name of the variant, see [Config::string_enum_tags], or be
+/// the only variant of the enum, see [Config::omit_single_variant_tag].
+struct KnownVariant<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> {
+    de: &'b mut Deserializer<'a, R, B>,
+    index: u32,
+}
+
+impl<'a, 'b, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> serde::de::EnumAccess<'a>
+    for KnownVariant<'a, 'b, R, B>
+{
+    type Error = DeserializeError<'a, R>;
+    type Variant = &'b mut Deserializer<'a, R, B>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: serde::de::DeserializeSeed<'a>,
+    {
+        let index: value::U32Deserializer<Self::Error> = self.index.into_deserializer();
+        let value = seed.deserialize(index)?;
+        Ok((value, self.de))
+    }
+}
+
+impl<'a, R: CoreRead<'a> + 'a, B: byteorder::ByteOrder + 'static> serde::de::VariantAccess<'a>
+    for &mut Deserializer<'a, R, B>
+{
+    type Error = DeserializeError<'a, R>;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'a>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'a>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        serde::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'a>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.visit_struct(fields, visitor)
+    }
+}
+
+/// A reader that only contains zeroes, for fields that are left out by [Config::field_mask].
+struct ZeroReader;
+
+static ZEROES: [u8; 256] = [0; 256];
+
+impl<'a> CoreRead<'a> for ZeroReader {
+    type Error = ();
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        Ok(0)
+    }
+
+    fn read_range(&mut self, len: usize) -> Result<&'a [u8], Self::Error> {
+        ZEROES.get(..len).ok_or(())
+    }
+
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        buf.fill(0);
+        Ok(())
+    }
+
+    fn read_until(&mut self, delimiter: u8) -> Result<Option<&'a [u8]>, Self::Error> {
+        Ok(if delimiter == 0 { Some(&[]) } else { None })
+    }
+}
+
 const UTF8_CHAR_WIDTH: [u8; 256] = [
     1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
     1, // 0x1F
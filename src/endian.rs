@@ -0,0 +1,175 @@
+use crate::CoreRead;
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A byte order that is picked at runtime instead of through a generic argument.
+///
+/// This can be used to override the byte order for lengths or values in a [Config](crate::Config).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RuntimeEndian {
+    /// Most significant byte first, the same as [byteorder::BigEndian].
+    Big,
+
+    /// Least significant byte first, the same as [byteorder::LittleEndian].
+    Little,
+}
+
+/// A primitive value that is encoded with a byte order.
+pub(crate) trait Primitive: Sized {
+    /// The amount of bytes this value takes up. This is at most 16.
+    const SIZE: usize;
+
+    /// The encoded bytes of this value, a `[u8; SIZE]`.
+    type Bytes: AsRef<[u8]>;
+
+    fn write<B: ByteOrder>(self, buf: &mut [u8]);
+    fn read<B: ByteOrder>(buf: &[u8]) -> Self;
+
+    /// Read the encoded bytes of this value with [CoreRead::read_array].
+    fn read_bytes<'a, R: CoreRead<'a>>(reader: &mut R) -> Result<Self::Bytes, R::Error>;
+}
+
+macro_rules! impl_primitive {
+    ($($ty:ty => $size:expr, $write:ident, $read:ident;)*) => {
+        $(
+            impl Primitive for $ty {
+                const SIZE: usize = $size;
+                type Bytes = [u8; $size];
+
+                fn write<B: ByteOrder>(self, buf: &mut [u8]) {
+                    B::$write(buf, self)
+                }
+
+                fn read<B: ByteOrder>(buf: &[u8]) -> Self {
+                    B::$read(buf)
+                }
+
+                fn read_bytes<'a, R: CoreRead<'a>>(reader: &mut R) -> Result<Self::Bytes, R::Error> {
+                    reader.read_array::<$size>()
+                }
+            }
+        )*
+    };
+}
+
+impl Primitive for u8 {
+    const SIZE: usize = 1;
+    type Bytes = [u8; 1];
+
+    fn write<B: ByteOrder>(self, buf: &mut [u8]) {
+        buf[0] = self;
+    }
+
+    fn read<B: ByteOrder>(buf: &[u8]) -> Self {
+        buf[0]
+    }
+
+    fn read_bytes<'a, R: CoreRead<'a>>(reader: &mut R) -> Result<Self::Bytes, R::Error> {
+        Ok([reader.read()?])
+    }
+}
+
+impl_primitive! {
+    u16 => 2, write_u16, read_u16;
+    u32 => 4, write_u32, read_u32;
+    u64 => 8, write_u64, read_u64;
+    u128 => 16, write_u128, read_u128;
+    i16 => 2, write_i16, read_i16;
+    i32 => 4, write_i32, read_i32;
+    i64 => 8, write_i64, read_i64;
+    i128 => 16, write_i128, read_i128;
+    f32 => 4, write_f32, read_f32;
+    f64 => 8, write_f64, read_f64;
+}
+
+/// Write `value` into `buf` with the given `order`, or with `B` if no order is given.
+pub(crate) fn write_primitive<B: ByteOrder, T: Primitive>(
+    order: Option<RuntimeEndian>,
+    value: T,
+    buf: &mut [u8],
+) {
+    match order {
+        None => value.write::<B>(buf),
+        Some(RuntimeEndian::Big) => value.write::<BigEndian>(buf),
+        Some(RuntimeEndian::Little) => value.write::<LittleEndian>(buf),
+    }
+}
+
+/// Read a value from `buf` with the given `order`, or with `B` if no order is given.
+pub(crate) fn read_primitive<B: ByteOrder, T: Primitive>(
+    order: Option<RuntimeEndian>,
+    buf: &[u8],
+) -> T {
+    match order {
+        None => T::read::<B>(buf),
+        Some(RuntimeEndian::Big) => T::read::<BigEndian>(buf),
+        Some(RuntimeEndian::Little) => T::read::<LittleEndian>(buf),
+    }
+}
+
+/// A value that is always encoded little-endian, regardless of the byte order that is passed to
+/// [serialize](crate::serialize) and [deserialize](crate::deserialize).
+///
+/// This is useful for single fields in a fixed byte order, see [Config](crate::Config) to change
+/// the byte order of all values instead.
+///
+/// ```
+/// # use bincode_embedded::{serialize, BufferWriter, Le};
+/// let mut buffer = [0u8; 8];
+/// let mut writer = BufferWriter::new(&mut buffer);
+/// serialize::<_, _, byteorder::BigEndian>(&(0x0102u16, Le(0x0304u16)), &mut writer).unwrap();
+/// assert_eq!(&[1, 2, 4, 3], writer.written_buffer());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Le<T>(pub T);
+
+/// A value that is always encoded big-endian, regardless of the byte order that is passed to
+/// [serialize](crate::serialize) and [deserialize](crate::deserialize).
+///
+/// See [Le] for more info.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Be<T>(pub T);
+
+// These are encoded as a tuple of bytes, which has no length prefix.
+macro_rules! impl_fixed_endian {
+    ($($ty:ty => $size:expr;)*) => {
+        $(
+            impl Serialize for Le<$ty> {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    self.0.to_le_bytes().serialize(serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for Le<$ty> {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    <[u8; $size]>::deserialize(deserializer).map(|bytes| Le(<$ty>::from_le_bytes(bytes)))
+                }
+            }
+
+            impl Serialize for Be<$ty> {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    self.0.to_be_bytes().serialize(serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for Be<$ty> {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    <[u8; $size]>::deserialize(deserializer).map(|bytes| Be(<$ty>::from_be_bytes(bytes)))
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_endian! {
+    u16 => 2;
+    u32 => 4;
+    u64 => 8;
+    u128 => 16;
+    i16 => 2;
+    i32 => 4;
+    i64 => 8;
+    i128 => 16;
+    f32 => 4;
+    f64 => 8;
+}
@@ -0,0 +1,103 @@
+use super::*;
+use embedded_storage::nor_flash::ReadNorFlash;
+
+/// A [CoreRead] implementation that reads a region of an `embedded_storage` NOR flash.
+///
+/// The region starts at `offset` and is `N` bytes long. It is copied into the scratch buffer on the
+/// first read, and every value is deserialized from the scratch buffer after that. This means that
+/// borrowed values like `&str` point into the scratch buffer. The offset and `N` must be aligned to
+/// the `READ_SIZE` of the flash, otherwise the flash returns an error.
+///
+/// This is only available with the `embedded-storage` feature.
+///
+/// ```
+/// # use bincode_embedded::{deserialize, FlashReader};
+/// # use embedded_storage::nor_flash::{ErrorType, NorFlashErrorKind, ReadNorFlash};
+/// # struct Flash([u8; 64]);
+/// # impl ErrorType for Flash {
+/// #     type Error = NorFlashErrorKind;
+/// # }
+/// # impl ReadNorFlash for Flash {
+/// #     const READ_SIZE: usize = 1;
+/// #     fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+/// #         let offset = offset as usize;
+/// #         bytes.copy_from_slice(&self.0[offset..offset + bytes.len()]);
+/// #         Ok(())
+/// #     }
+/// #     fn capacity(&self) -> usize {
+/// #         self.0.len()
+/// #     }
+/// # }
+/// # let mut flash = Flash([0; 64]);
+/// # flash.0[32..37].copy_from_slice(&[0, 3, b'a', b'b', b'c']);
+/// let mut scratch = [0u8; 16];
+/// let reader = FlashReader::new(&mut flash, 32, &mut scratch);
+/// let name: &str = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+/// assert_eq!("abc", name);
+/// ```
+pub struct FlashReader<'a, F: ReadNorFlash, const N: usize> {
+    flash: F,
+    offset: u32,
+    scratch: Option<&'a mut [u8; N]>,
+    data: &'a [u8],
+}
+
+impl<'a, F: ReadNorFlash, const N: usize> FlashReader<'a, F, N> {
+    /// Create a new reader for the `N` bytes at `offset` in `flash`, which are read into `scratch`.
+    pub fn new(flash: F, offset: u32, scratch: &'a mut [u8; N]) -> Self {
+        Self {
+            flash,
+            offset,
+            scratch: Some(scratch),
+            data: &[],
+        }
+    }
+
+    /// Get the flash back.
+    pub fn into_inner(self) -> F {
+        self.flash
+    }
+
+    /// Copy the region into the scratch buffer, if this is not done yet.
+    fn load(&mut self) -> Result<(), FlashReaderError<F::Error>> {
+        if let Some(scratch) = self.scratch.take() {
+            if let Err(e) = self.flash.read(self.offset, &mut scratch[..]) {
+                self.scratch = Some(scratch);
+                return Err(FlashReaderError::Flash(e));
+            }
+            self.data = scratch;
+        }
+        Ok(())
+    }
+}
+
+/// Errors that can be returned from a [FlashReader].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlashReaderError<E> {
+    /// The flash returned an error while reading the region.
+    Flash(E),
+
+    /// A read was requested past the end of the region.
+    UnexpectedEnd,
+}
+
+impl<'a, F: ReadNorFlash, const N: usize> CoreRead<'a> for FlashReader<'a, F, N> {
+    type Error = FlashReaderError<F::Error>;
+
+    fn read_range(&mut self, len: usize) -> Result<&'a [u8], Self::Error> {
+        self.load()?;
+        self.data
+            .read_range(len)
+            .map_err(|()| FlashReaderError::UnexpectedEnd)
+    }
+
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        buf.copy_from_slice(self.read_range(buf.len())?);
+        Ok(())
+    }
+
+    fn read_until(&mut self, delimiter: u8) -> Result<Option<&'a [u8]>, Self::Error> {
+        self.load()?;
+        Ok(self.data.read_until(delimiter).unwrap_or(None))
+    }
+}
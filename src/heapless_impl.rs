@@ -0,0 +1,137 @@
+/// The name that [heapless_string::deserialize] passes to `deserialize_tuple_struct`. The
+/// [Deserializer](crate::Deserializer) of this crate recognizes this name, and checks the length
+/// prefix against the capacity before reading the string.
+pub(crate) const HEAPLESS_STRING_NAME: &str = "$bincode_embedded::HeaplessString";
+
+/// The name that [heapless_vec::deserialize] passes to `deserialize_tuple_struct`. The
+/// [Deserializer](crate::Deserializer) of this crate recognizes this name, and checks the length
+/// prefix against the capacity before reading any element.
+pub(crate) const HEAPLESS_VEC_NAME: &str = "$bincode_embedded::HeaplessVec";
+
+/// (De)serialize a `heapless::String<N>` field, for use with `#[serde(with = "...")]`.
+///
+/// This is encoded the same as a `&str`. When deserializing, the string is copied out of the
+/// reader, so the value does not borrow from the input. A string that is longer than `N` bytes
+/// results in [DeserializeError::StringCapacityExceeded](crate::DeserializeError::StringCapacityExceeded)
+/// before the string itself is read.
+///
+/// This is only available with the `heapless` feature.
+///
+/// ```
+/// # #[macro_use] extern crate serde_derive;
+/// # use bincode_embedded::{deserialize, DeserializeError};
+/// #[derive(Deserialize)]
+/// struct Device {
+///     #[serde(with = "bincode_embedded::heapless_string")]
+///     name: heapless::String<4>,
+/// }
+///
+/// let buffer = [0, 3, b'a', b'b', b'c'];
+/// let device: Device = deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+/// assert_eq!("abc", device.name);
+/// ```
+pub mod heapless_string {
+    use super::HEAPLESS_STRING_NAME;
+    use core::fmt;
+    use heapless::String;
+    use serde::de::{Deserializer, Error, Visitor};
+    use serde::ser::Serializer;
+
+    /// Serialize a `heapless::String` as a `&str`.
+    pub fn serialize<S: Serializer, const N: usize>(
+        value: &String<N>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(value)
+    }
+
+    /// Deserialize a `heapless::String` by copying the string out of the reader.
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<String<N>, D::Error> {
+        struct StringVisitor<const N: usize>;
+
+        impl<const N: usize> Visitor<'_> for StringVisitor<N> {
+            type Value = String<N>;
+
+            fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                write!(fmt, "a string of at most {} bytes", N)
+            }
+
+            fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+                let mut string = String::new();
+                string
+                    .push_str(value)
+                    .map_err(|()| E::invalid_length(value.len(), &self))?;
+                Ok(string)
+            }
+        }
+
+        deserializer.deserialize_tuple_struct(HEAPLESS_STRING_NAME, N, StringVisitor::<N>)
+    }
+}
+
+/// (De)serialize a `heapless::Vec<T, N>` field, for use with `#[serde(with = "...")]`.
+///
+/// This is encoded the same as a sequence, e.g. a `&[T]`. When deserializing, a sequence that is
+/// longer than `N` elements results in
+/// [DeserializeError::VecCapacityExceeded](crate::DeserializeError::VecCapacityExceeded) before any
+/// element is read.
+///
+/// This is only available with the `heapless` feature.
+///
+/// ```
+/// # #[macro_use] extern crate serde_derive;
+/// # use bincode_embedded::deserialize;
+/// #[derive(Deserialize)]
+/// struct Samples {
+///     #[serde(with = "bincode_embedded::heapless_vec")]
+///     values: heapless::Vec<u16, 4>,
+/// }
+///
+/// let buffer = [0, 2, 0, 5, 0, 6];
+/// let samples: Samples = deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+/// assert_eq!(&[5, 6], &samples.values[..]);
+/// ```
+pub mod heapless_vec {
+    use super::HEAPLESS_VEC_NAME;
+    use core::{fmt, marker::PhantomData};
+    use heapless::Vec;
+    use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+    use serde::ser::{Serialize, Serializer};
+
+    /// Serialize a `heapless::Vec` as a sequence.
+    pub fn serialize<S: Serializer, T: Serialize, const N: usize>(
+        value: &Vec<T, N>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(value.iter())
+    }
+
+    /// Deserialize a `heapless::Vec` from a sequence.
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<Vec<T, N>, D::Error> {
+        struct VecVisitor<T, const N: usize>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>, const N: usize> Visitor<'de> for VecVisitor<T, N> {
+            type Value = Vec<T, N>;
+
+            fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                write!(fmt, "a sequence of at most {} elements", N)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut values = Vec::new();
+                while let Some(value) = seq.next_element()? {
+                    if values.push(value).is_err() {
+                        return Err(A::Error::invalid_length(N + 1, &self));
+                    }
+                }
+                Ok(values)
+            }
+        }
+
+        deserializer.deserialize_tuple_struct(HEAPLESS_VEC_NAME, N, VecVisitor::<T, N>(PhantomData))
+    }
+}
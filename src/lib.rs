@@ -1,5 +1,16 @@
 #![warn(missing_docs)]
 #![no_std]
+#![cfg_attr(
+    feature = "forbid-panic",
+    deny(
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::unimplemented,
+        clippy::todo,
+        clippy::unreachable
+    )
+)]
 
 //! Embedded bincode
 //!
@@ -16,18 +27,114 @@
 //! requirement that the data being read, has to be persisted somewhere. Usually this is done by a
 //! fixed-size backing array. The `&str` and `&[u8]` then simply point to a position in that
 //! buffer.
+//!
+//! serde serializes `&[u8]` fields as a sequence, which writes every byte with a separate call to
+//! the writer. Annotating these fields with `#[serde(with = "serde_bytes")]` writes the slice in a
+//! single call instead. Both produce the same bytes, so this annotation can be added to one side of
+//! a protocol without changing the other side.
+//!
+//! Only `&[u8]` is deserialized zero-copy, by borrowing the bytes from the reader. serde has no
+//! byte path for other single-byte types, so `&[i8]` can not be borrowed, and a `Vec<i8>` or
+//! `[i8; N]` is read one element at a time. The encoding is the same as `&[u8]`: a length prefix
+//! followed by one byte per element.
+//!
+//! Slices of other types, e.g. `&[Point]` for a struct `Point`, are encoded as a length prefix
+//! followed by every element. These can not be borrowed when deserializing, as a struct is not
+//! stored byte-for-byte in the input. Deserialize them element by element into a type that owns
+//! them instead, like `heapless::Vec<Point, N>`. A `[Point; N]` has no length prefix, so the
+//! length has to be read first, e.g. by deserializing a `(u16, [Point; N])`.
+//!
+//! References like `&T` are serialized the same as `T`. With the `alloc` feature, `Box<T>`,
+//! `Cow<'a, str>` and `Cow<'a, [u8]>` fields can be deserialized as well. `Cow` fields that are
+//! annotated with `#[serde(borrow)]` borrow from the reader, and are only copied when
+//! [Config::copy_strings] is enabled.
+//!
+//! An `Option<T>` is encoded as a presence byte, `0` for `None` and `1` for `Some`, followed by
+//! the value if it is present. Zero-sized types like `()` and unit structs take no bytes, so
+//! `Option<()>` is only the presence byte. Nested options write a presence byte per level, e.g.
+//! `Some(None::<u8>)` is encoded as `[1, 0]` and `Some(Some(5u8))` as `[1, 1, 5]`. See
+//! [Config::bit_pack_tags] to pack presence bytes into single bits.
+//!
+//! # Compatibility with bincode
+//!
+//! Integers, floats, `bool`, `char`, `Option`, tuples, fixed-size arrays and structs are encoded
+//! the same as bincode with fixed-size integers, e.g.
+//! `bincode::DefaultOptions::new().with_fixint_encoding().with_big_endian()` matches
+//! `serialize::<_, _, byteorder::BigEndian>`. The following types are encoded differently:
+//! - The length of strings, byte slices and sequences is a `u16` instead of a `u64`. This can be
+//!   changed with [Config::length_width].
+//! - The length of maps is a `u8` instead of a `u64`. This can be changed with
+//!   [Config::length_width].
+//! - Enum variant indices are a `u8` instead of a `u32`. This can be changed with
+//!   [Config::variant_width].
+//!
+//! Like bincode, the format does not contain field names or types. serde features that rely on
+//! those are not supported, e.g. `#[serde(flatten)]` and untagged enums. These result in
+//! [SerializeError::NotSupported] or [DeserializeError::NotSupported] instead of wrong bytes.
+//!
+//! # Panics
+//!
+//! Serializing and deserializing report every failure as an error, including errors that a
+//! `Serialize` or `Deserialize` implementation raises with `serde::ser::Error::custom`. There is no
+//! allocator to store their message, so these become [SerializeError::Custom] and
+//! [DeserializeError::Custom]. The `forbid-panic` feature denies clippy's lints for `panic!`,
+//! `unwrap`, `expect`, `unimplemented!`, `todo!` and `unreachable!` in this crate, so
+//! `cargo clippy --features forbid-panic` fails if one of these is added. A [CoreRead] or
+//! [CoreWrite] implementation that does not follow its documented contract, e.g. a
+//! [CoreRead::read_range] that returns a slice of the wrong length, can still panic.
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
+#[cfg(feature = "arrayvec")]
+mod arrayvec_impl;
+mod base64;
+mod bounded;
+mod byte_array_impl;
+mod config;
 mod deserialize;
+mod endian;
+#[cfg(feature = "embedded-storage")]
+mod flash_impl;
+#[cfg(feature = "heapless")]
+mod heapless_impl;
+mod macros;
+#[cfg(feature = "nb")]
+mod nb_impl;
+mod readers;
+mod schema;
 mod serialize;
+mod single_variant;
+mod writers;
 
+pub use base64::*;
+pub use bounded::{BoundedChars, BoundedStr};
+pub use byte_array_impl::*;
+pub use config::*;
 pub use deserialize::*;
+pub use endian::{Be, Le, RuntimeEndian};
+#[cfg(feature = "embedded-storage")]
+pub use flash_impl::*;
+#[cfg(feature = "heapless")]
+pub use heapless_impl::*;
+#[cfg(feature = "nb")]
+pub use nb_impl::*;
+pub use readers::*;
+pub use schema::schema_hash;
 pub use serialize::*;
+pub use single_variant::SingleVariant;
+pub use writers::*;
 
 /// A target that can be written to. This is similar to `std::io::Write`, but the std trait is not
 /// available in `#![no_std]` projects.
 ///
 /// This trait is auto-implemented for [BufferWriter], but can also be implemented to write to an e.g.
 /// `embedded_hal::serial::Write`.
+///
+/// The serializer writes everything through [write_all], including single bytes like a `u8` or
+/// an `Option` tag. Implementing [write] is enough, as the default [write_all] calls it for every
+/// byte. Writers that batch their output, e.g. a packetizing transport, can implement [write_all]
+/// instead and never see a call to [write].
 pub trait CoreWrite {
     /// The error that this writer can encounter
     type Error: core::fmt::Debug;
@@ -58,8 +165,13 @@ pub trait CoreWrite {
 /// This trait is auto-implemented for `&[u8]`.
 ///
 /// Because the deserialization is done in-place, any object implementing this trait MUST return a
-/// persistent reference to the original data. This allows (de)serialization from e.g. `&str` and
-/// `&[u8]` without an allocator.
+/// persistent reference to the original data from [read_range]. This allows (de)serialization from
+/// e.g. `&str` and `&[u8]` without an allocator.
+///
+/// Only borrowed values like `&str` and `&[u8]` use [read_range]. Integers, floats and length
+/// prefixes are read with [read_bytes_into], which copies the data. A reader that can not return
+/// persistent data can implement [read] and return an error from [read_range], and still
+/// deserialize every type that does not borrow from the reader.
 ///
 /// The easiest way to implement this would be by reading data into a fixed-size array and reading
 /// from there.
@@ -70,7 +182,7 @@ pub trait CoreRead<'a> {
     type Error: core::fmt::Debug;
 
     /// Read a single byte from the current buffer. This is auto-implemented to read a &[u8; 1]
-    /// from [read_range] and return the first value.
+    /// from [read_transient] and return the first value.
     ///
     /// This method can be overwritten to allow for more efficient implementations.
     ///
@@ -78,7 +190,7 @@ pub trait CoreRead<'a> {
     /// a persistent buffer. Implementors of this function are free to discard the data that is
     /// returned from this function.
     fn read(&mut self) -> Result<u8, Self::Error> {
-        let buff = self.read_range(1)?;
+        let buff = self.read_transient(1)?;
         Ok(buff[0])
     }
 
@@ -90,72 +202,112 @@ pub trait CoreRead<'a> {
     /// The returned slice MUST be exactly the size that is requested. The deserializer will
     /// panic when a differently sized slice is returned.
     fn read_range(&mut self, len: usize) -> Result<&'a [u8], Self::Error>;
+
+    /// Read a byte slice from this reader that only has to live until the next read.
+    ///
+    /// This is used for data that is not handed out as a borrowed value, e.g. strings when
+    /// [Config::copy_strings] is enabled. The default implementation calls [read_range].
+    ///
+    /// Readers that can not return persistent data from [read_range], e.g. readers that are backed
+    /// by a ring buffer, can implement this method to return a slice of their scratch buffer.
+    ///
+    /// The returned slice MUST be exactly the size that is requested.
+    fn read_transient<'s>(&'s mut self, len: usize) -> Result<&'s [u8], Self::Error>
+    where
+        'a: 's,
+    {
+        self.read_range(len)
+    }
+
+    /// Read exactly `N` bytes from this reader into a stack array.
+    ///
+    /// This is used to read integers, floats and length prefixes, which never borrow from the
+    /// reader. The default implementation calls [read_bytes_into].
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], Self::Error> {
+        let mut result = [0u8; N];
+        self.read_bytes_into(&mut result)?;
+        Ok(result)
+    }
+
+    /// Fill the given buffer with bytes from this reader. Unlike [read_range], this does not require
+    /// the data to be persistent.
+    ///
+    /// The default implementation calls [read] for every byte in the buffer.
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        for byte in buf {
+            *byte = self.read()?;
+        }
+        Ok(())
+    }
+
+    /// Read a byte slice up to the given `delimiter`. The delimiter is consumed, but is not part of
+    /// the returned slice. Like [read_range], the returned slice MUST be a reference to a persistent
+    /// buffer.
+    ///
+    /// Returns `Ok(None)` if the delimiter is not found. The default implementation always returns
+    /// `Ok(None)`, as a persistent slice can not be built from single byte reads.
+    ///
+    /// A missing delimiter is not reported as an error, because [Self::Error] is picked by every
+    /// reader: the trait can not construct a `DelimiterNotFound` value of it, and most readers,
+    /// like `&[u8]`, have no error of their own for it. The [Deserializer] reports `None` as
+    /// [DeserializeError::DelimiterNotFound], see [Deserializer::deserialize_until].
+    ///
+    /// This can be used for delimiter-based framing, e.g. NUL-terminated or newline-terminated
+    /// fields:
+    /// ```
+    /// # use bincode_embedded::CoreRead;
+    /// let mut reader = &b"name\0value\0"[..];
+    /// assert_eq!(Some(&b"name"[..]), reader.read_until(0).unwrap());
+    /// assert_eq!(Some(&b"value"[..]), reader.read_until(0).unwrap());
+    /// assert_eq!(None, reader.read_until(0).unwrap());
+    /// ```
+    fn read_until(&mut self, delimiter: u8) -> Result<Option<&'a [u8]>, Self::Error> {
+        let _ = delimiter;
+        Ok(None)
+    }
 }
 
 // These are the data types for metadata that is added to serializing and deserializing.
 // To change these values, please clone the project and modify them here.
-// Make sure to update src/serializer.rs and src/deserializer.rs as well.
-// This library shouldn't compile if these data types don't match the size of the data types.
-//
-// An example error:
 //
-// Changing
-// `pub(crate) type StrLenType = u16;`
-// to
-// `pub(crate) type StrLenType = u8;`
+// The length types are written and read through `Serializer::write_length` and
+// `Deserializer::read_length`, which pick the amount of bytes based on the type. Changing e.g.
+// `pub(crate) type StrLenType = u16;` to `pub(crate) type StrLenType = u8;` is enough to change
+// the wire format of string lengths.
 //
-// Raises compiler errors:
-// ```
-// error[E0308]: mismatched types
-//   --> libs/bincode_embedded/src/deserialize.rs:66:27
-//    |
-// 66 |     let len: StrLenType = B::read_u16(buf);
-//    |              ----------   ^^^^^^^^^^^^^^^^ expected `u8`, found `u16`
-//    |              |
-//    |              expected due to this
-//    |
-// help: you can convert an `u16` to `u8` and panic if the converted value wouldn't fit
-//    |
-// 66 |     let len: StrLenType = B::read_u16(buf).try_into().unwrap();
-//    |                           ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
-//
-// error[E0308]: mismatched types
-//   --> libs/bincode_embedded/src/serialize.rs:43:30
-//    |
-// 43 |     serializer.serialize_u16(str_len as StrLenType)
-//    |                              ^^^^^^^^^^^^^^^^^^^^^
-//    |                              |
-//    |                              expected `u16`, found `u8`
-//    |                              help: you can convert an `u8` to `u16`: `(str_len as StrLenType).into()`
-// ```
-//
-// To fix these errors, change:
-//   src/deserializer.rs - `read_u16` into `read_u8`
-//   src/serializer.rs   - `serialize_u16` into `serializer_u8`
+// Lengths that do not fit in these types result in `SerializeError::LengthOverflow`. The width of
+// enum variant indices is picked at runtime with `Config::variant_width`.
 
-pub(crate) type EnumVariantType = u8;
-pub(crate) type UnitVariantType = u8;
 pub(crate) type SequenceLengthType = u16;
 pub(crate) type StrLenType = u16;
 pub(crate) type SliceLenType = u16;
 pub(crate) type MapLenType = u8;
-pub(crate) type StructVariantType = u8;
+
+// serde serializes `&[u8]` as a sequence of `u8`, but deserializes it with `deserialize_bytes`.
+// These only produce the same bytes when sequences and slices use the same length prefix.
+const _: () = assert!(
+    core::mem::size_of::<SequenceLengthType>() == core::mem::size_of::<SliceLenType>(),
+    "SequenceLengthType and SliceLenType must be the same size"
+);
 
 /// An implementation of [CoreWrite]. This buffer writer will write data to a backing `&mut [u8]`.
+///
+/// This is a [Cursor] that only writes forward, see [Cursor] to move the write position.
 pub struct BufferWriter<'a> {
-    buffer: &'a mut [u8],
-    index: usize,
+    cursor: Cursor<'a>,
 }
 
 impl<'a> BufferWriter<'a> {
     /// Create a new writer with a backing buffer.
     pub fn new(buffer: &'a mut [u8]) -> Self {
-        Self { buffer, index: 0 }
+        Self {
+            cursor: Cursor::new(buffer),
+        }
     }
 
     /// The bytes count written to the backing buffer.
     pub fn written_len(&self) -> usize {
-        self.index
+        self.cursor.written_len()
     }
 
     /// A slice of the buffer that is in this writer. This is equivalent to getting a slice of the
@@ -172,7 +324,97 @@ impl<'a> BufferWriter<'a> {
     /// assert_eq!(buffer_slice, writer_slice);
     /// ```
     pub fn written_buffer(&self) -> &[u8] {
-        &self.buffer[..self.index]
+        self.cursor.written_buffer()
+    }
+
+    /// The part of the buffer that is not written yet. Bytes that are copied into this slice
+    /// directly, e.g. by DMA, can be added to the written bytes with [BufferWriter::advance].
+    ///
+    /// This allows serializing a header and then copying a large payload behind it at once:
+    /// ```
+    /// # use bincode_embedded::{serialize, BufferWriter};
+    /// let payload = [0xAB; 4];
+    /// let mut buffer = [0u8; 16];
+    /// let mut writer = BufferWriter::new(&mut buffer);
+    /// serialize::<_, _, byteorder::NetworkEndian>(&(payload.len() as u16), &mut writer).unwrap();
+    /// writer.unused_mut()[..payload.len()].copy_from_slice(&payload);
+    /// writer.advance(payload.len()).unwrap();
+    ///
+    /// assert_eq!(&[0, 4, 0xAB, 0xAB, 0xAB, 0xAB], writer.written_buffer());
+    /// ```
+    pub fn unused_mut(&mut self) -> &mut [u8] {
+        let start = self.cursor.position();
+        &mut self.cursor.get_mut()[start..]
+    }
+
+    /// Add `n` bytes of [BufferWriter::unused_mut] to the written bytes. Returns
+    /// [BufferWriterError::BufferTooSmall] if there are less than `n` unused bytes.
+    pub fn advance(&mut self, n: usize) -> Result<(), BufferWriterError> {
+        self.cursor.advance(n)
+    }
+
+    /// Reserve `n` bytes in the buffer, to be filled in later with [BufferWriter::patch]. The reserved bytes
+    /// are set to `0`. Returns the offset of the reserved region.
+    ///
+    /// This allows writing a header that depends on the body, e.g. its length:
+    /// ```
+    /// # use bincode_embedded::{serialize, BufferWriter};
+    /// let mut buffer = [0u8; 16];
+    /// let mut writer = BufferWriter::new(&mut buffer);
+    /// let header = writer.reserve(1).unwrap();
+    /// serialize::<_, _, byteorder::NetworkEndian>(&"body", &mut writer).unwrap();
+    /// let body_len = writer.written_len() - header - 1;
+    /// writer.patch(header, &[body_len as u8]).unwrap();
+    ///
+    /// assert_eq!(&[6, 0, 4, b'b', b'o', b'd', b'y'], writer.written_buffer());
+    /// ```
+    pub fn reserve(&mut self, n: usize) -> Result<usize, BufferWriterError> {
+        let start = self.cursor.position();
+        if n > self.cursor.get_ref().len() - start {
+            return Err(BufferWriterError::BufferTooSmall);
+        }
+        for _ in 0..n {
+            self.cursor.write(0)?;
+        }
+        Ok(start)
+    }
+
+    /// Overwrite bytes that are already written at the given `offset`, e.g. a region that was
+    /// reserved with [BufferWriter::reserve]. The patched region must be within
+    /// [BufferWriter::written_len].
+    pub fn patch(&mut self, offset: usize, bytes: &[u8]) -> Result<(), BufferWriterError> {
+        let end = self.cursor.position();
+        offset
+            .checked_add(bytes.len())
+            .filter(|patch_end| *patch_end <= end)
+            .ok_or(BufferWriterError::OutOfBounds)?;
+        self.cursor.seek(offset)?;
+        self.cursor.write_all(bytes)?;
+        self.cursor.seek(end)
+    }
+
+    /// Write `0` bytes until [BufferWriter::written_len] is a multiple of `alignment`. An
+    /// `alignment` of `0` or `1` does nothing.
+    ///
+    /// This is used for protocols that require fields at aligned offsets, e.g. structures that are
+    /// shared with DMA. The padding can be skipped with [Deserializer::align_to] on the reading
+    /// side:
+    /// ```
+    /// # use bincode_embedded::{serialize, BufferWriter};
+    /// let mut buffer = [0u8; 16];
+    /// let mut writer = BufferWriter::new(&mut buffer);
+    /// serialize::<_, _, byteorder::NetworkEndian>(&1u8, &mut writer).unwrap();
+    /// writer.align_to(4).unwrap();
+    /// serialize::<_, _, byteorder::NetworkEndian>(&2u32, &mut writer).unwrap();
+    ///
+    /// assert_eq!(&[1, 0, 0, 0, 0, 0, 0, 2], writer.written_buffer());
+    /// ```
+    pub fn align_to(&mut self, alignment: usize) -> Result<(), BufferWriterError> {
+        if alignment <= 1 {
+            return Ok(());
+        }
+        let padding = (alignment - self.written_len() % alignment) % alignment;
+        self.reserve(padding).map(|_| ())
     }
 }
 
@@ -181,27 +423,32 @@ impl<'a> BufferWriter<'a> {
 pub enum BufferWriterError {
     /// The backing buffer of the [BufferWriter] is too small.
     BufferTooSmall,
+
+    /// A patch was requested outside of the bytes that are written to the [BufferWriter].
+    OutOfBounds,
 }
 
 impl CoreWrite for &'_ mut BufferWriter<'_> {
     type Error = BufferWriterError;
 
     fn write(&mut self, val: u8) -> Result<(), Self::Error> {
-        if self.index >= self.buffer.len() {
-            return Err(BufferWriterError::BufferTooSmall);
-        }
-        self.buffer[self.index] = val;
-        self.index += 1;
-        Ok(())
+        self.cursor.write(val)
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        self.cursor.write_all(val)
     }
 }
 
 impl CoreWrite for BufferWriter<'_> {
-    type Error = ();
+    type Error = BufferWriterError;
+
     fn write(&mut self, val: u8) -> Result<(), Self::Error> {
-        self.buffer[self.index] = val;
-        self.index += 1;
-        Ok(())
+        self.cursor.write(val)
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        self.cursor.write_all(val)
     }
 }
 
@@ -209,8 +456,52 @@ impl<'a> CoreRead<'a> for &'a [u8] {
     type Error = ();
 
     fn read_range(&mut self, len: usize) -> Result<&'a [u8], Self::Error> {
+        if len > self.len() {
+            return Err(());
+        }
         let result = &self[..len];
         *self = &self[len..];
         Ok(result)
     }
+
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        buf.copy_from_slice(self.read_range(buf.len())?);
+        Ok(())
+    }
+
+    fn read_until(&mut self, delimiter: u8) -> Result<Option<&'a [u8]>, Self::Error> {
+        let index = match memchr(delimiter, self) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let result = &self[..index];
+        *self = &self[index + 1..];
+        Ok(Some(result))
+    }
+}
+
+/// Find the index of the first `needle` in `haystack`. Like `memchr`, this checks a `usize` worth of
+/// bytes at a time before looking at single bytes.
+fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    use core::mem::size_of;
+
+    const LO: usize = usize::MAX / 0xFF;
+    const HI: usize = LO << 7;
+    let repeated = LO * needle as usize;
+
+    let mut offset = 0;
+    for chunk in haystack.chunks_exact(size_of::<usize>()) {
+        let mut bytes = [0u8; size_of::<usize>()];
+        bytes.copy_from_slice(chunk);
+        let word = usize::from_ne_bytes(bytes) ^ repeated;
+        // A byte in `word` is zero if it matches `needle`
+        if word.wrapping_sub(LO) & !word & HI != 0 {
+            break;
+        }
+        offset += chunk.len();
+    }
+    haystack[offset..]
+        .iter()
+        .position(|b| *b == needle)
+        .map(|index| index + offset)
 }
@@ -0,0 +1,128 @@
+/// Implement [CoreWrite](crate::CoreWrite) for a wrapper type by forwarding every method to a
+/// field that implements `CoreWrite`.
+///
+/// The macro takes the wrapper type, followed by the path of the field and its type. Lifetimes of
+/// the wrapper type are declared in front of it:
+///
+/// ```
+/// # use bincode_embedded::{impl_core_write, serialize, BufferWriter};
+/// struct Uart<'a> {
+///     port: BufferWriter<'a>,
+/// }
+///
+/// struct Console<'a> {
+///     uart: Uart<'a>,
+///     lines: usize,
+/// }
+///
+/// impl_core_write!(<'a> Console<'a>, uart.port: BufferWriter<'a>);
+///
+/// let mut buffer = [0u8; 4];
+/// let mut console = Console {
+///     uart: Uart { port: BufferWriter::new(&mut buffer) },
+///     lines: 0,
+/// };
+/// serialize::<_, _, byteorder::NetworkEndian>(&5u16, &mut console).unwrap();
+/// assert_eq!(&[0, 5], console.uart.port.written_buffer());
+/// ```
+///
+/// A wrapper type without lifetimes is written without the `<>` prefix, e.g.
+/// `impl_core_write!(Uart, port: HalSerial)`. This implements `CoreWrite` for both the wrapper
+/// type and a mutable reference to it.
+#[macro_export]
+macro_rules! impl_core_write {
+    ($ty:ident, $($field:tt).+ : $inner:ty) => {
+        $crate::impl_core_write!(<> $ty, $($field).+ : $inner);
+    };
+    (<$($lt:lifetime),*> $ty:ty, $($field:tt).+ : $inner:ty) => {
+        impl<$($lt),*> $crate::CoreWrite for $ty {
+            type Error = <$inner as $crate::CoreWrite>::Error;
+
+            fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+                $crate::CoreWrite::write(&mut self.$($field).+, val)
+            }
+
+            fn flush(&mut self) -> Result<(), Self::Error> {
+                $crate::CoreWrite::flush(&mut self.$($field).+)
+            }
+
+            fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+                $crate::CoreWrite::write_all(&mut self.$($field).+, val)
+            }
+        }
+
+        impl<$($lt),*> $crate::CoreWrite for &mut $ty {
+            type Error = <$inner as $crate::CoreWrite>::Error;
+
+            fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+                $crate::CoreWrite::write(&mut self.$($field).+, val)
+            }
+
+            fn flush(&mut self) -> Result<(), Self::Error> {
+                $crate::CoreWrite::flush(&mut self.$($field).+)
+            }
+
+            fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+                $crate::CoreWrite::write_all(&mut self.$($field).+, val)
+            }
+        }
+    };
+}
+
+/// Implement [CoreRead](crate::CoreRead) for a wrapper type by forwarding every method to a field
+/// that implements `CoreRead`.
+///
+/// The macro takes the lifetime of the read data, the wrapper type, and the path of the field and
+/// its type:
+///
+/// ```
+/// # use bincode_embedded::{deserialize, impl_core_read};
+/// struct Frame<'a> {
+///     body: &'a [u8],
+///     checksum: u8,
+/// }
+///
+/// impl_core_read!('a, Frame<'a>, body: &'a [u8]);
+///
+/// let frame = Frame { body: &[0, 2, b'h', b'i'], checksum: 0 };
+/// let value: &str = deserialize::<_, _, byteorder::NetworkEndian>(frame).unwrap();
+/// assert_eq!("hi", value);
+/// ```
+#[macro_export]
+macro_rules! impl_core_read {
+    ($lt:lifetime, $ty:ty, $($field:tt).+ : $inner:ty) => {
+        impl<$lt> $crate::CoreRead<$lt> for $ty
+        where
+            $inner: $crate::CoreRead<$lt>,
+        {
+            type Error = <$inner as $crate::CoreRead<$lt>>::Error;
+
+            fn read(&mut self) -> Result<u8, Self::Error> {
+                $crate::CoreRead::read(&mut self.$($field).+)
+            }
+
+            fn read_range(&mut self, len: usize) -> Result<&$lt [u8], Self::Error> {
+                $crate::CoreRead::read_range(&mut self.$($field).+, len)
+            }
+
+            fn read_transient<'s>(&'s mut self, len: usize) -> Result<&'s [u8], Self::Error>
+            where
+                $lt: 's,
+            {
+                $crate::CoreRead::read_transient(&mut self.$($field).+, len)
+            }
+
+            fn read_array<const N: usize>(&mut self) -> Result<[u8; N], Self::Error> {
+                $crate::CoreRead::read_array(&mut self.$($field).+)
+            }
+
+            fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+                $crate::CoreRead::read_bytes_into(&mut self.$($field).+, buf)
+            }
+
+            fn read_until(&mut self, delimiter: u8) -> Result<Option<&$lt [u8]>, Self::Error> {
+                $crate::CoreRead::read_until(&mut self.$($field).+, delimiter)
+            }
+        }
+    };
+}
@@ -0,0 +1,110 @@
+use super::*;
+use core::marker::PhantomData;
+
+/// A [CoreWrite] adapter for `nb`-style drivers, e.g. the `write` method of a serial port.
+///
+/// The closure is called with every byte. When it returns [nb::Error::WouldBlock], it is called
+/// again with the same byte until it succeeds or fails. This means that writing **blocks** until
+/// the driver accepted every byte.
+///
+/// This is only available with the `nb` feature.
+///
+/// ```
+/// # use bincode_embedded::{serialize, NbWriter};
+/// let mut output = Vec::new();
+/// let writer = NbWriter::new(|byte| -> nb::Result<(), ()> {
+///     output.push(byte);
+///     Ok(())
+/// });
+/// serialize::<_, _, byteorder::NetworkEndian>(&5u16, writer).unwrap();
+/// assert_eq!(&[0, 5], &output[..]);
+/// ```
+pub struct NbWriter<F, E> {
+    f: F,
+    pd: PhantomData<E>,
+}
+
+impl<F: FnMut(u8) -> nb::Result<(), E>, E: core::fmt::Debug> NbWriter<F, E> {
+    /// Create a new writer that calls `f` with every written byte.
+    pub fn new(f: F) -> Self {
+        Self { f, pd: PhantomData }
+    }
+
+    /// Get the closure back.
+    pub fn into_inner(self) -> F {
+        self.f
+    }
+}
+
+impl<F: FnMut(u8) -> nb::Result<(), E>, E: core::fmt::Debug> CoreWrite for NbWriter<F, E> {
+    type Error = E;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        nb::block!((self.f)(val))
+    }
+}
+
+/// A [CoreRead] adapter for `nb`-style drivers, e.g. the `read` method of a serial port.
+///
+/// The closure is called for every byte. When it returns [nb::Error::WouldBlock], it is called
+/// again until it succeeds or fails. This means that reading **blocks** until enough bytes are
+/// received.
+///
+/// The bytes are not stored, so borrowed values like `&str` can not be deserialized and result in
+/// [NbReaderError::NotPersistent]. Owned values can be deserialized, and strings and byte slices can
+/// be copied into a scratch buffer with [deserialize_with_scratch].
+///
+/// This is only available with the `nb` feature.
+///
+/// ```
+/// # use bincode_embedded::{deserialize, NbReader};
+/// let mut input = [0u8, 5].iter().copied();
+/// let reader = NbReader::new(|| -> nb::Result<u8, ()> { input.next().ok_or(nb::Error::Other(())) });
+/// let value: u16 = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+/// assert_eq!(5, value);
+/// ```
+pub struct NbReader<F, E> {
+    f: F,
+    pd: PhantomData<E>,
+}
+
+impl<F: FnMut() -> nb::Result<u8, E>, E: core::fmt::Debug> NbReader<F, E> {
+    /// Create a new reader that calls `f` for every byte that is read.
+    pub fn new(f: F) -> Self {
+        Self { f, pd: PhantomData }
+    }
+
+    /// Get the closure back.
+    pub fn into_inner(self) -> F {
+        self.f
+    }
+}
+
+/// Errors that can be returned from a [NbReader].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NbReaderError<E> {
+    /// The closure of the reader returned an error.
+    Read(E),
+
+    /// A borrowed value was deserialized, which requires a persistent buffer.
+    NotPersistent,
+}
+
+impl<'a, F: FnMut() -> nb::Result<u8, E>, E: core::fmt::Debug> CoreRead<'a> for NbReader<F, E> {
+    type Error = NbReaderError<E>;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        nb::block!((self.f)()).map_err(NbReaderError::Read)
+    }
+
+    fn read_range(&mut self, _len: usize) -> Result<&'a [u8], Self::Error> {
+        Err(NbReaderError::NotPersistent)
+    }
+
+    fn read_transient<'s>(&'s mut self, _len: usize) -> Result<&'s [u8], Self::Error>
+    where
+        'a: 's,
+    {
+        Err(NbReaderError::NotPersistent)
+    }
+}
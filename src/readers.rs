@@ -0,0 +1,572 @@
+use super::*;
+use crate::endian::{read_primitive, Primitive};
+use core::{cell::Cell, convert::TryFrom, marker::PhantomData};
+
+/// A reader that knows how many bytes it has left.
+///
+/// This can be used to check that a frame is fully consumed after deserializing, e.g. with
+/// [deserialize_and_return]:
+///
+/// ```
+/// # use bincode_embedded::{deserialize_and_return, RemainingBytes};
+/// let buffer = [0, 1, 0xFF];
+/// let (value, reader) =
+///     deserialize_and_return::<u16, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+/// assert_eq!(1, value);
+/// assert_eq!(1, reader.remaining());
+/// ```
+pub trait RemainingBytes {
+    /// The amount of bytes that can still be read.
+    fn remaining(&self) -> usize;
+}
+
+impl RemainingBytes for &'_ [u8] {
+    fn remaining(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<const N: usize> RemainingBytes for StackReader<N> {
+    fn remaining(&self) -> usize {
+        self.len - self.cursor.get()
+    }
+}
+
+impl<const N: usize> RemainingBytes for &'_ StackReader<N> {
+    fn remaining(&self) -> usize {
+        (**self).remaining()
+    }
+}
+
+impl RemainingBytes for Base64Reader<'_> {
+    fn remaining(&self) -> usize {
+        self.remaining_buffer().len()
+    }
+}
+
+/// A [CoreRead] implementation that owns a fixed-size buffer.
+///
+/// This bundles the storage and the reader for the common pattern of receiving a frame into a
+/// local `[u8; N]`. [CoreRead] is implemented for `&StackReader`, so borrowed values like `&str`
+/// point into the buffer of the reader:
+///
+/// ```
+/// # use bincode_embedded::{deserialize, StackReader};
+/// let reader = StackReader::<16>::from_slice(&[0, 3, b'a', b'b', b'c']).unwrap();
+/// let value: &str = deserialize::<_, _, byteorder::NetworkEndian>(&reader).unwrap();
+/// assert_eq!("abc", value);
+/// ```
+pub struct StackReader<const N: usize> {
+    buffer: [u8; N],
+    len: usize,
+    cursor: Cell<usize>,
+}
+
+impl<const N: usize> StackReader<N> {
+    /// Create a reader from the first `len` bytes of `buffer`. `len` is limited to `N`.
+    pub fn new(buffer: [u8; N], len: usize) -> Self {
+        Self {
+            buffer,
+            len: len.min(N),
+            cursor: Cell::new(0),
+        }
+    }
+
+    /// Create a reader by copying the bytes of `src`. Returns
+    /// [StackReaderError::BufferTooSmall] if `src` is longer than `N` bytes.
+    pub fn from_slice(src: &[u8]) -> Result<Self, StackReaderError> {
+        if src.len() > N {
+            return Err(StackReaderError::BufferTooSmall);
+        }
+        let mut buffer = [0u8; N];
+        buffer[..src.len()].copy_from_slice(src);
+        Ok(Self::new(buffer, src.len()))
+    }
+
+    /// The bytes that are not read yet.
+    pub fn remaining_buffer(&self) -> &[u8] {
+        &self.buffer[self.cursor.get()..self.len]
+    }
+}
+
+/// Errors that can be returned from a [StackReader].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StackReaderError {
+    /// The data does not fit in the buffer of the [StackReader].
+    BufferTooSmall,
+
+    /// A read was requested past the end of the data in the [StackReader].
+    UnexpectedEnd,
+}
+
+impl<'a, const N: usize> CoreRead<'a> for &'a StackReader<N> {
+    type Error = StackReaderError;
+
+    fn read_range(&mut self, len: usize) -> Result<&'a [u8], Self::Error> {
+        let reader: &'a StackReader<N> = self;
+        let start = reader.cursor.get();
+        if len > reader.remaining() {
+            return Err(StackReaderError::UnexpectedEnd);
+        }
+        reader.cursor.set(start + len);
+        Ok(&reader.buffer[start..start + len])
+    }
+
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        buf.copy_from_slice(self.read_range(buf.len())?);
+        Ok(())
+    }
+
+    fn read_until(&mut self, delimiter: u8) -> Result<Option<&'a [u8]>, Self::Error> {
+        let reader: &'a StackReader<N> = self;
+        let mut remaining = reader.remaining_buffer();
+        let result = remaining.read_until(delimiter).unwrap_or(None);
+        if result.is_some() {
+            reader.cursor.set(reader.len - remaining.len());
+        }
+        Ok(result)
+    }
+}
+
+/// An iterator that splits a buffer into length-prefixed frames.
+///
+/// Every frame is encoded the same as a byte slice: a length prefix followed by the bytes of the
+/// frame. The length prefix uses the byte order `B`, or [Config::length_endian] when it is set.
+/// Every frame can then be deserialized on its own:
+///
+/// ```
+/// # use bincode_embedded::{deserialize, FrameReader};
+/// let buffer = [0, 1, 5, 0, 2, 0, 6];
+/// let mut frames = FrameReader::<byteorder::NetworkEndian>::new(&buffer);
+/// let first = frames.next().unwrap().unwrap();
+/// assert_eq!(5, deserialize::<u8, _, byteorder::NetworkEndian>(first).unwrap());
+/// let second = frames.next().unwrap().unwrap();
+/// assert_eq!(6, deserialize::<u16, _, byteorder::NetworkEndian>(second).unwrap());
+/// assert!(frames.next().is_none());
+/// ```
+///
+/// A frame that is cut off at the end of the buffer results in a [FrameError], after which the
+/// iterator ends.
+pub struct FrameReader<'a, B: byteorder::ByteOrder> {
+    buffer: &'a [u8],
+    config: Config,
+    pd: PhantomData<B>,
+}
+
+impl<'a, B: byteorder::ByteOrder> FrameReader<'a, B> {
+    /// Create a reader that splits `buffer` into frames.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        Self::with_config(buffer, Config::new())
+    }
+
+    /// Create a reader that splits `buffer` into frames, and reads the length prefixes with the
+    /// byte order of [Config::length_endian] and the width of [Config::length_width].
+    pub fn with_config(buffer: &'a [u8], config: Config) -> Self {
+        Self {
+            buffer,
+            config,
+            pd: PhantomData,
+        }
+    }
+
+    /// The bytes that are not read yet.
+    pub fn remaining_buffer(&self) -> &'a [u8] {
+        self.buffer
+    }
+}
+
+/// Errors that can be returned from a [FrameReader].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameError {
+    /// The buffer ends in the middle of a length prefix.
+    TruncatedLength,
+
+    /// The length prefix of a frame is larger than the remaining bytes in the buffer.
+    TruncatedFrame {
+        /// The length of the frame that was read from the length prefix.
+        len: usize,
+        /// The amount of bytes that are left in the buffer.
+        remaining: usize,
+    },
+}
+
+impl<'a, B: byteorder::ByteOrder> Iterator for FrameReader<'a, B> {
+    type Item = Result<&'a [u8], FrameError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.is_empty() {
+            return None;
+        }
+        let prefix_len = match self.config.length_width {
+            LengthWidth::Compact => SliceLenType::SIZE,
+            LengthWidth::U64 => u64::SIZE,
+        };
+        if self.buffer.len() < prefix_len {
+            self.buffer = &[];
+            return Some(Err(FrameError::TruncatedLength));
+        }
+        let (prefix, rest) = self.buffer.split_at(prefix_len);
+        let len = match self.config.length_width {
+            LengthWidth::Compact => {
+                read_primitive::<B, SliceLenType>(self.config.length_endian, prefix) as usize
+            }
+            LengthWidth::U64 => {
+                let len = read_primitive::<B, u64>(self.config.length_endian, prefix);
+                usize::try_from(len).unwrap_or(usize::MAX)
+            }
+        };
+        if len > rest.len() {
+            self.buffer = &[];
+            return Some(Err(FrameError::TruncatedFrame {
+                len,
+                remaining: rest.len(),
+            }));
+        }
+        let (frame, rest) = rest.split_at(len);
+        self.buffer = rest;
+        Some(Ok(frame))
+    }
+}
+
+/// A [CoreRead] adapter that reports every read to a closure, for debugging how a frame is
+/// consumed.
+///
+/// The closure is called with the name of the [CoreRead] method and the amount of bytes that is
+/// requested, before the read is forwarded to the inner reader. [CoreRead::read_until] does not
+/// know the length up front, so it is reported after the read with the length of the returned
+/// slice. See [TracingWriter] for the write side.
+///
+/// ```
+/// # use bincode_embedded::{deserialize, TracingReader};
+/// let mut trace = Vec::new();
+/// let reader = TracingReader::new(&[0, 5, 1][..], |op: &str, len| trace.push((op.to_owned(), len)));
+/// let value: (u16, u8) = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+/// assert_eq!((5, 1), value);
+/// assert_eq!(vec![("read_array".to_owned(), 2), ("read".to_owned(), 1)], trace);
+/// ```
+pub struct TracingReader<'a, R: CoreRead<'a>, F: FnMut(&str, usize)> {
+    inner: R,
+    f: F,
+    pd: PhantomData<&'a ()>,
+}
+
+impl<'a, R: CoreRead<'a>, F: FnMut(&str, usize)> TracingReader<'a, R, F> {
+    /// Create a new reader that reports every read from `inner` to `f`.
+    pub fn new(inner: R, f: F) -> Self {
+        Self {
+            inner,
+            f,
+            pd: PhantomData,
+        }
+    }
+
+    /// Get the inner reader back.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<'a, R: CoreRead<'a>, F: FnMut(&str, usize)> CoreRead<'a> for TracingReader<'a, R, F> {
+    type Error = R::Error;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        (self.f)("read", 1);
+        self.inner.read()
+    }
+
+    fn read_range(&mut self, len: usize) -> Result<&'a [u8], Self::Error> {
+        (self.f)("read_range", len);
+        self.inner.read_range(len)
+    }
+
+    fn read_transient<'s>(&'s mut self, len: usize) -> Result<&'s [u8], Self::Error>
+    where
+        'a: 's,
+    {
+        (self.f)("read_transient", len);
+        self.inner.read_transient(len)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], Self::Error> {
+        (self.f)("read_array", N);
+        self.inner.read_array()
+    }
+
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        (self.f)("read_bytes_into", buf.len());
+        self.inner.read_bytes_into(buf)
+    }
+
+    fn read_until(&mut self, delimiter: u8) -> Result<Option<&'a [u8]>, Self::Error> {
+        let result = self.inner.read_until(delimiter)?;
+        (self.f)("read_until", result.map_or(0, <[u8]>::len));
+        Ok(result)
+    }
+}
+
+/// A reader that reads from `A` until it is exhausted, and then from `B`.
+///
+/// This allows deserializing a frame whose header and body arrive in separate buffers, without
+/// copying them together first:
+///
+/// ```
+/// # use bincode_embedded::{deserialize, ChainReader};
+/// let header = [0, 7, 0, 2];
+/// let body = [b'h', b'i'];
+/// let reader = ChainReader::new(&header[..], &body[..]);
+/// let value: (u16, &str) = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+/// assert_eq!((7, "hi"), value);
+/// ```
+///
+/// Values that are copied out of the reader, like integers, can span both readers. Borrowed
+/// values like `&str` must be a single persistent slice, so a [CoreRead::read_range] that spans
+/// both readers is copied into the scratch buffer that is set with [ChainReader::with_scratch].
+/// Every spanning read takes up its own part of the scratch buffer, as the returned slice stays
+/// borrowed. Without a scratch buffer, or when it is too small, this results in
+/// [ChainReaderError::ScratchTooSmall]. [CoreRead::read_until] does not look past the end of `A`.
+pub struct ChainReader<'a, A, B> {
+    first: A,
+    second: B,
+    scratch: &'a mut [u8],
+}
+
+impl<'a, A, B> ChainReader<'a, A, B> {
+    /// Create a reader that reads from `first` and then from `second`.
+    pub fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            scratch: &mut [],
+        }
+    }
+
+    /// Copy reads that span both readers into `scratch`.
+    pub fn with_scratch(mut self, scratch: &'a mut [u8]) -> Self {
+        self.scratch = scratch;
+        self
+    }
+
+    /// Get the inner readers back.
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+/// Errors that can be returned from a [ChainReader].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChainReaderError<A, B> {
+    /// The first reader returned an error.
+    First(A),
+
+    /// The second reader returned an error.
+    Second(B),
+
+    /// A read of `len` bytes spans both readers, and the scratch buffer only has `scratch` bytes
+    /// left.
+    ScratchTooSmall {
+        /// The length of the read.
+        len: usize,
+        /// The amount of bytes that are left in the scratch buffer.
+        scratch: usize,
+    },
+}
+
+impl<A: RemainingBytes, B: RemainingBytes> RemainingBytes for ChainReader<'_, A, B> {
+    fn remaining(&self) -> usize {
+        self.first.remaining() + self.second.remaining()
+    }
+}
+
+impl<'a, A, B> CoreRead<'a> for ChainReader<'a, A, B>
+where
+    A: CoreRead<'a> + RemainingBytes,
+    B: CoreRead<'a>,
+{
+    type Error = ChainReaderError<A::Error, B::Error>;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        if self.first.remaining() > 0 {
+            self.first.read().map_err(ChainReaderError::First)
+        } else {
+            self.second.read().map_err(ChainReaderError::Second)
+        }
+    }
+
+    fn read_range(&mut self, len: usize) -> Result<&'a [u8], Self::Error> {
+        let remaining = self.first.remaining();
+        if len <= remaining {
+            return self.first.read_range(len).map_err(ChainReaderError::First);
+        }
+        if remaining == 0 {
+            return self
+                .second
+                .read_range(len)
+                .map_err(ChainReaderError::Second);
+        }
+        if len > self.scratch.len() {
+            return Err(ChainReaderError::ScratchTooSmall {
+                len,
+                scratch: self.scratch.len(),
+            });
+        }
+        let (buf, rest) = core::mem::take(&mut self.scratch).split_at_mut(len);
+        self.scratch = rest;
+        self.read_bytes_into(buf)?;
+        Ok(buf)
+    }
+
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let split = self.first.remaining().min(buf.len());
+        let (head, tail) = buf.split_at_mut(split);
+        if !head.is_empty() {
+            self.first
+                .read_bytes_into(head)
+                .map_err(ChainReaderError::First)?;
+        }
+        if !tail.is_empty() {
+            self.second
+                .read_bytes_into(tail)
+                .map_err(ChainReaderError::Second)?;
+        }
+        Ok(())
+    }
+
+    fn read_until(&mut self, delimiter: u8) -> Result<Option<&'a [u8]>, Self::Error> {
+        if self.first.remaining() > 0 {
+            self.first
+                .read_until(delimiter)
+                .map_err(ChainReaderError::First)
+        } else {
+            self.second
+                .read_until(delimiter)
+                .map_err(ChainReaderError::Second)
+        }
+    }
+}
+
+/// A reader that XORs its input with a repeating key, for links that obfuscate their frames. See
+/// [XorWriter] for the write side.
+///
+/// **This is obfuscation, not encryption.** A repeating XOR key is trivially recovered from a few
+/// known bytes, e.g. a fixed header, so it must not be used to protect secrets or to authenticate
+/// frames.
+///
+/// Values that are copied out of the reader, like integers, are decrypted directly into the value.
+/// Borrowed values like `&str` must point into a persistent buffer, so [CoreRead::read_range] and
+/// [CoreRead::read_until] decrypt into the scratch buffer that is given to [XorReader::new].
+/// Every borrowed read takes up its own part of the scratch buffer, as the returned slice stays
+/// borrowed. When the scratch buffer is too small, this results in
+/// [XorReaderError::ScratchTooSmall].
+///
+/// ```
+/// # use bincode_embedded::{deserialize, XorReader};
+/// let key = [0x5A, 0xA5];
+/// let input = [0x5A, 0xA7, 0x32, 0xCC];
+/// let mut scratch = [0u8; 8];
+/// let reader = XorReader::new(&input, key, &mut scratch);
+/// let value: &str = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+/// assert_eq!("hi", value);
+/// ```
+pub struct XorReader<'a, const N: usize> {
+    input: &'a [u8],
+    key: [u8; N],
+    position: usize,
+    scratch: &'a mut [u8],
+}
+
+impl<'a, const N: usize> XorReader<'a, N> {
+    /// Create a reader that XORs `input` with `key`, and decrypts borrowed values into `scratch`.
+    pub fn new(input: &'a [u8], key: [u8; N], scratch: &'a mut [u8]) -> Self {
+        Self {
+            input,
+            key,
+            position: 0,
+            scratch,
+        }
+    }
+
+    /// The key byte for the next byte of the input.
+    fn key_at(&self, offset: usize) -> u8 {
+        if N == 0 {
+            0
+        } else {
+            self.key[(self.position + offset) % N]
+        }
+    }
+}
+
+/// Errors that can be returned from a [XorReader].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XorReaderError {
+    /// A read was requested past the end of the input.
+    UnexpectedEnd,
+
+    /// A borrowed read of `len` bytes was requested, and the scratch buffer only has `scratch`
+    /// bytes left.
+    ScratchTooSmall {
+        /// The length of the read.
+        len: usize,
+        /// The amount of bytes that are left in the scratch buffer.
+        scratch: usize,
+    },
+}
+
+impl<const N: usize> RemainingBytes for XorReader<'_, N> {
+    fn remaining(&self) -> usize {
+        self.input.len()
+    }
+}
+
+impl<'a, const N: usize> CoreRead<'a> for XorReader<'a, N> {
+    type Error = XorReaderError;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        let mut byte = [0];
+        self.read_bytes_into(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn read_range(&mut self, len: usize) -> Result<&'a [u8], Self::Error> {
+        if len > self.input.len() {
+            return Err(XorReaderError::UnexpectedEnd);
+        }
+        if len > self.scratch.len() {
+            return Err(XorReaderError::ScratchTooSmall {
+                len,
+                scratch: self.scratch.len(),
+            });
+        }
+        let (buf, rest) = core::mem::take(&mut self.scratch).split_at_mut(len);
+        self.scratch = rest;
+        self.read_bytes_into(buf)?;
+        Ok(buf)
+    }
+
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        if buf.len() > self.input.len() {
+            return Err(XorReaderError::UnexpectedEnd);
+        }
+        let (input, rest) = self.input.split_at(buf.len());
+        for (i, (byte, encrypted)) in buf.iter_mut().zip(input).enumerate() {
+            *byte = encrypted ^ self.key_at(i);
+        }
+        self.input = rest;
+        self.position = self.position.wrapping_add(buf.len());
+        Ok(())
+    }
+
+    fn read_until(&mut self, delimiter: u8) -> Result<Option<&'a [u8]>, Self::Error> {
+        let index = self
+            .input
+            .iter()
+            .enumerate()
+            .position(|(i, encrypted)| encrypted ^ self.key_at(i) == delimiter);
+        let index = match index {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let result = self.read_range(index)?;
+        self.read()?;
+        Ok(Some(result))
+    }
+}
@@ -0,0 +1,414 @@
+use crate::bounded::{BOUNDED_CHARS_NAME, BOUNDED_STR_NAME};
+use crate::byte_array_impl::BYTES_NAME;
+#[cfg(feature = "heapless")]
+use crate::heapless_impl::{HEAPLESS_STRING_NAME, HEAPLESS_VEC_NAME};
+use core::fmt;
+use serde::de::{
+    DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use serde::Deserialize;
+
+/// How many levels of options, sequences and maps are walked before they are treated as empty.
+/// This stops recursive types like `struct Node { next: Option<Box<Node>> }`.
+const MAX_DEPTH: usize = 16;
+
+/// A zeroed buffer that is handed out for borrowed byte arrays, see [Bytes](crate::Bytes).
+static ZEROES: [u8; 256] = [0; 256];
+
+/// Compute a fingerprint of the serialized layout of `T`.
+///
+/// The format does not describe itself, so a device that reads a value with a different layout
+/// than the one it was written with silently reads garbage. Sending this hash along with a
+/// protocol (e.g. in a handshake or a header) allows both sides to detect such a mismatch.
+///
+/// The hash covers the order of the fields and the kind and width of every primitive, so e.g.
+/// changing a field from `u8` to `u16` or swapping two fields of a different type changes the
+/// hash. Field and type names are not part of the encoding, so renaming them does not.
+///
+/// This is a best-effort hash that is computed by walking `T` with a dry-run deserializer:
+/// - Only the first variant of an enum is walked, the other variants only add their count.
+/// - Sequences and maps are walked with a single element, and options are walked as `Some`.
+///   Recursive types stop after a few levels.
+/// - Types that reject the placeholder values that are handed out end the walk early, e.g. a
+///   type that only accepts a range of integers. Integers are handed `1`, so `NonZero*` types are
+///   walked.
+/// - Options from [Config](crate::Config) are not taken into account.
+///
+/// ```
+/// # #[macro_use] extern crate serde_derive;
+/// # use bincode_embedded::schema_hash;
+/// #[derive(Deserialize)]
+/// struct V1 {
+///     id: u8,
+///     temperature: i16,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct V2 {
+///     id: u8,
+///     temperature: i32,
+/// }
+///
+/// assert_ne!(schema_hash::<V1>(), schema_hash::<V2>());
+/// ```
+pub fn schema_hash<T: Deserialize<'static>>() -> u64 {
+    let mut hasher = SchemaHasher {
+        hash: FNV_OFFSET,
+        depth: 0,
+    };
+    // An error only ends the walk, the hash of everything up to that point is still usable
+    let _ = T::deserialize(&mut hasher);
+    hasher.hash
+}
+
+const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+/// The kinds of values that are fed to the hash.
+#[derive(Clone, Copy)]
+enum Kind {
+    Bool = 1,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    Str,
+    Bytes,
+    Option,
+    Unit,
+    Seq,
+    Tuple,
+    Map,
+    Enum,
+    UnitVariant,
+    NewtypeVariant,
+    TupleVariant,
+    StructVariant,
+}
+
+/// A dry-run deserializer that feeds the structure of a type into an FNV-1a hash.
+struct SchemaHasher {
+    hash: u64,
+    depth: usize,
+}
+
+impl SchemaHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= u64::from(*byte);
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn kind(&mut self, kind: Kind) {
+        self.write(&[kind as u8]);
+    }
+
+    fn kind_with_len(&mut self, kind: Kind, len: usize) {
+        self.kind(kind);
+        self.write(&(len as u64).to_le_bytes());
+    }
+
+    /// Returns the amount of elements to walk for a sequence or map, `0` once the walk is nested
+    /// too deep.
+    fn sample_len(&self) -> usize {
+        if self.depth < MAX_DEPTH {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn visit_elements<'de, V: Visitor<'de>>(
+        &mut self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SchemaError> {
+        self.depth += 1;
+        let value = visitor.visit_seq(Elements { hasher: self, len });
+        self.depth -= 1;
+        value
+    }
+}
+
+/// The error of [SchemaHasher], which ends the walk.
+#[derive(Debug)]
+struct SchemaError;
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("type can not be walked")
+    }
+}
+
+impl serde::de::StdError for SchemaError {}
+
+impl serde::de::Error for SchemaError {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        SchemaError
+    }
+}
+
+macro_rules! hash_primitive {
+    ($fn:ident, $visit:ident, $kind:ident, $value:expr) => {
+        fn $fn<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SchemaError> {
+            self.kind(Kind::$kind);
+            visitor.$visit($value)
+        }
+    };
+}
+
+impl<'de> Deserializer<'de> for &mut SchemaHasher {
+    type Error = SchemaError;
+
+    hash_primitive!(deserialize_bool, visit_bool, Bool, false);
+    hash_primitive!(deserialize_i8, visit_i8, I8, 1);
+    hash_primitive!(deserialize_i16, visit_i16, I16, 1);
+    hash_primitive!(deserialize_i32, visit_i32, I32, 1);
+    hash_primitive!(deserialize_i64, visit_i64, I64, 1);
+    hash_primitive!(deserialize_i128, visit_i128, I128, 1);
+    hash_primitive!(deserialize_u8, visit_u8, U8, 1);
+    hash_primitive!(deserialize_u16, visit_u16, U16, 1);
+    hash_primitive!(deserialize_u32, visit_u32, U32, 1);
+    hash_primitive!(deserialize_u64, visit_u64, U64, 1);
+    hash_primitive!(deserialize_u128, visit_u128, U128, 1);
+    hash_primitive!(deserialize_f32, visit_f32, F32, 0.0);
+    hash_primitive!(deserialize_f64, visit_f64, F64, 0.0);
+    hash_primitive!(deserialize_char, visit_char, Char, '\0');
+    hash_primitive!(deserialize_str, visit_borrowed_str, Str, "");
+    hash_primitive!(deserialize_string, visit_borrowed_str, Str, "");
+    hash_primitive!(deserialize_bytes, visit_borrowed_bytes, Bytes, &[]);
+    hash_primitive!(deserialize_byte_buf, visit_borrowed_bytes, Bytes, &[]);
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SchemaError> {
+        self.kind(Kind::Unit);
+        visitor.visit_unit()
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, SchemaError> {
+        Err(SchemaError)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SchemaError> {
+        self.kind(Kind::Option);
+        if self.sample_len() == 0 {
+            return visitor.visit_none();
+        }
+        self.depth += 1;
+        let value = visitor.visit_some(&mut *self);
+        self.depth -= 1;
+        value
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SchemaError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SchemaError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SchemaError> {
+        self.kind(Kind::Seq);
+        let len = self.sample_len();
+        self.visit_elements(len, visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SchemaError> {
+        self.kind_with_len(Kind::Tuple, len);
+        self.visit_elements(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SchemaError> {
+        // These types are encoded differently than a tuple, see `Deserializer::deserialize_tuple_struct`
+        if name == BOUNDED_STR_NAME || name == BOUNDED_CHARS_NAME {
+            return self.deserialize_str(visitor);
+        }
+        #[cfg(feature = "heapless")]
+        if name == HEAPLESS_STRING_NAME {
+            return self.deserialize_str(visitor);
+        }
+        #[cfg(feature = "heapless")]
+        if name == HEAPLESS_VEC_NAME {
+            return self.deserialize_seq(visitor);
+        }
+        if name == BYTES_NAME {
+            // Hashed the same as `[u8; N]`, which has the same encoding
+            self.kind_with_len(Kind::Tuple, len);
+            for _ in 0..len {
+                self.kind(Kind::U8);
+            }
+            return match ZEROES.get(..len) {
+                Some(bytes) => visitor.visit_borrowed_bytes(bytes),
+                None => Err(SchemaError),
+            };
+        }
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SchemaError> {
+        self.kind(Kind::Map);
+        let len = self.sample_len();
+        self.depth += 1;
+        let value = visitor.visit_map(Elements {
+            hasher: &mut *self,
+            len,
+        });
+        self.depth -= 1;
+        value
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SchemaError> {
+        // Structs are encoded the same as a tuple of their fields
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SchemaError> {
+        self.kind_with_len(Kind::Enum, variants.len());
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SchemaError> {
+        visitor.visit_u32(0)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SchemaError> {
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<'de> EnumAccess<'de> for &mut SchemaHasher {
+    type Error = SchemaError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), SchemaError> {
+        let value = seed.deserialize(&mut *self)?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for &mut SchemaHasher {
+    type Error = SchemaError;
+
+    fn unit_variant(self) -> Result<(), SchemaError> {
+        self.kind(Kind::UnitVariant);
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SchemaError> {
+        self.kind(Kind::NewtypeVariant);
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SchemaError> {
+        self.kind(Kind::TupleVariant);
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SchemaError> {
+        self.kind(Kind::StructVariant);
+        self.deserialize_struct("", fields, visitor)
+    }
+}
+
+/// Hands out `len` elements of a sequence or entries of a map.
+struct Elements<'a> {
+    hasher: &'a mut SchemaHasher,
+    len: usize,
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_> {
+    type Error = SchemaError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SchemaError> {
+        if self.len > 0 {
+            self.len -= 1;
+            seed.deserialize(&mut *self.hasher).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de> MapAccess<'de> for Elements<'_> {
+    type Error = SchemaError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SchemaError> {
+        self.next_element_seed(seed)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SchemaError> {
+        seed.deserialize(&mut *self.hasher)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
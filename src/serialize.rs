@@ -1,9 +1,8 @@
 use super::*;
-use core::marker::PhantomData;
-use serde::{
-    ser::{Serializer as _, *},
-    serde_if_integer128,
-};
+use crate::endian::{write_primitive, Primitive};
+use crate::single_variant::{is_variant, SINGLE_VARIANT_NAME};
+use core::{convert::TryFrom, marker::PhantomData};
+use serde::ser::*;
 
 /// Serialize a given `T` type into a given `CoreWrite` writer with the given `B` byte order.
 ///
@@ -20,25 +19,216 @@ pub fn serialize<T: serde::Serialize, W: CoreWrite, B: byteorder::ByteOrder + 's
     value: &T,
     writer: W,
 ) -> Result<(), SerializeError<W>> {
-    let mut serializer = Serializer::<W, B> {
+    serialize_with_config::<T, W, B>(value, writer, Config::new())
+}
+
+/// Serialize a given `T` type into a byte slice, and return the amount of bytes that are written.
+/// This is the same as [serialize] with a [BufferWriter]:
+///
+/// ```
+/// # use bincode_embedded::to_bytes;
+/// let mut buffer = [0u8; 16];
+/// let len = to_bytes::<_, byteorder::NetworkEndian>(&(7u8, "hi"), &mut buffer).unwrap();
+/// assert_eq!(&[7, 0, 2, b'h', b'i'], &buffer[..len]);
+/// ```
+///
+/// A value that does not fit results in [BufferWriterError::BufferTooSmall]. The bytes that are
+/// written up to that point stay in `buf`, see [fits_in].
+pub fn to_bytes<'b, T: serde::Serialize, B: byteorder::ByteOrder + 'static>(
+    value: &T,
+    buf: &'b mut [u8],
+) -> Result<usize, SerializeError<&'b mut BufferWriter<'b>>> {
+    let mut writer = BufferWriter::new(buf);
+    serialize::<T, _, B>(value, &mut writer).map_err(|e| e.map_write(|e| e))?;
+    Ok(writer.written_len())
+}
+
+/// Serialize a given `T` type into a given `CoreWrite` writer with the given `B` byte order and
+/// [Config].
+///
+/// This works the same as [serialize], but allows the format to be tweaked. The same config must
+/// be used to deserialize the value.
+pub fn serialize_with_config<
+    T: serde::Serialize,
+    W: CoreWrite,
+    B: byteorder::ByteOrder + 'static,
+>(
+    value: &T,
+    writer: W,
+    config: Config,
+) -> Result<(), SerializeError<W>> {
+    let mut serializer = Serializer::<W, B>::new(writer, config);
+    value.serialize(&mut serializer)?;
+    serializer.finish()?;
+    Ok(())
+}
+
+/// Serialize a given `T` type into a given `CoreWrite` writer with a byte order that is picked at
+/// runtime, e.g. after negotiating it with a peer.
+///
+/// Every integer that is written checks `endian`, so prefer [serialize] when the byte order is
+/// known at compile time.
+pub fn serialize_with_endian<T: serde::Serialize, W: CoreWrite>(
+    value: &T,
+    writer: W,
+    endian: RuntimeEndian,
+) -> Result<(), SerializeError<W>> {
+    serialize_with_config::<T, W, byteorder::NetworkEndian>(
+        value,
         writer,
-        pd: PhantomData,
-    };
-    value.serialize(&mut serializer)
+        Config::new().endian(endian),
+    )
+}
+
+/// Convert a length or variant index to the type that is written, e.g. [StrLenType]. Returns
+/// [SerializeError::LengthOverflow] instead of silently truncating the value.
+fn narrow<T: TryFrom<usize>, W: CoreWrite>(len: usize) -> Result<T, SerializeError<W>> {
+    T::try_from(len).map_err(|_| SerializeError::LengthOverflow)
+}
+
+/// Serialize a given `T` type that only consists of bytes, without picking a byte order.
+///
+/// This supports `u8`, `i8`, `bool`, `char`, `Option`, fixed-size arrays, tuples, structs and
+/// enums. Values that are encoded with a byte order, e.g. `u16` or the length prefix of a `&str`,
+/// result in [SerializeError::ByteOrderRequired].
+///
+/// ```
+/// # use bincode_embedded::{serialize_bytes_only, BufferWriter};
+/// let mut buffer = [0u8; 4];
+/// let mut writer = BufferWriter::new(&mut buffer);
+/// serialize_bytes_only(&(1u8, [2u8, 3]), &mut writer).unwrap();
+/// assert_eq!(&[1, 2, 3], writer.written_buffer());
+/// ```
+pub fn serialize_bytes_only<T: serde::Serialize, W: CoreWrite>(
+    value: &T,
+    writer: W,
+) -> Result<(), SerializeError<W>> {
+    let mut config = Config::new();
+    config.bytes_only = true;
+    serialize_with_config::<T, W, byteorder::NetworkEndian>(value, writer, config)
+}
+
+/// Serialize a given `T` type after a single `version` byte, see [deserialize_versioned].
+///
+/// This allows the format of a value to change between firmware versions, as the receiver can
+/// pick the type to deserialize based on the version.
+pub fn serialize_versioned<T: serde::Serialize, W: CoreWrite, B: byteorder::ByteOrder + 'static>(
+    version: u8,
+    value: &T,
+    writer: W,
+) -> Result<(), SerializeError<W>> {
+    serialize::<_, W, B>(&(version, value), writer)
+}
+
+/// Serialize a given `T` type after the bytes of `magic`, see [deserialize_with_magic].
+///
+/// The magic number is written as-is, without a length prefix. This allows a receiver to check
+/// that a frame belongs to the expected protocol before the value is read.
+pub fn serialize_with_magic<
+    T: serde::Serialize,
+    W: CoreWrite,
+    B: byteorder::ByteOrder + 'static,
+>(
+    magic: &[u8],
+    value: &T,
+    writer: W,
+) -> Result<(), SerializeError<W>> {
+    let mut serializer = Serializer::<W, B>::new(writer, Config::new());
+    serializer.write_raw(magic)?;
+    value.serialize(&mut serializer)?;
+    serializer.finish()?;
+    Ok(())
+}
+
+/// Serialize a given `T` type with the given `B` byte order, and pass the bytes to `f` as they are
+/// produced.
+///
+/// This does not need an intermediate buffer, which suits transports that send data in chunks.
+/// The chunks are as large as a single value, e.g. 4 bytes for a `u32` or the full contents of a
+/// `&str`, so `f` may be called with chunks that are as small as one byte.
+///
+/// ```
+/// # use bincode_embedded::serialize_with;
+/// let mut sent = 0;
+/// serialize_with::<_, byteorder::NetworkEndian, (), _>(&(1u8, 2u32), |chunk| {
+///     sent += chunk.len();
+///     Ok(())
+/// })
+/// .unwrap();
+/// assert_eq!(5, sent);
+/// ```
+pub fn serialize_with<
+    T: serde::Serialize,
+    B: byteorder::ByteOrder + 'static,
+    E: core::fmt::Debug,
+    F: FnMut(&[u8]) -> Result<(), E>,
+>(
+    value: &T,
+    f: F,
+) -> Result<(), SerializeError<FnWriter<F, E>>> {
+    serialize::<T, _, B>(value, FnWriter::new(f))
+}
+
+/// Get the amount of bytes that `value` takes up when it is serialized with [serialize].
+///
+/// This serializes the value into a [CountWriter]. The byte order does not change the size of a
+/// value.
+pub fn serialized_size<T: serde::Serialize>(
+    value: &T,
+) -> Result<usize, SerializeError<CountWriter>> {
+    serialized_size_with_config(value, Config::new())
+}
+
+/// Get the amount of bytes that `value` takes up when it is serialized with
+/// [serialize_with_config] and the given `config`.
+pub fn serialized_size_with_config<T: serde::Serialize>(
+    value: &T,
+    config: Config,
+) -> Result<usize, SerializeError<CountWriter>> {
+    let mut serializer = Serializer::<_, byteorder::NetworkEndian>::new(CountWriter::new(), config);
+    value.serialize(&mut serializer)?;
+    Ok(serializer.finish()?.count())
+}
+
+/// Check if `value` can be serialized with [serialize] into a buffer of `capacity` bytes.
+///
+/// [BufferWriter] returns an error when it runs out of space, but the bytes that are written up to
+/// that point stay in the buffer. Checking this first prevents half-written frames:
+///
+/// ```
+/// # use bincode_embedded::{fits_in, serialize, BufferWriter};
+/// let mut buffer = [0u8; 4];
+/// let value = (1u8, "abc");
+/// if fits_in(&value, buffer.len()) {
+///     serialize::<_, _, byteorder::NetworkEndian>(&value, &mut BufferWriter::new(&mut buffer))
+///         .unwrap();
+/// }
+/// # assert_eq!([0; 4], buffer);
+/// ```
+///
+/// This also returns `false` if the value can not be serialized at all, e.g. because a length does
+/// not fit in its length type.
+pub fn fits_in<T: serde::Serialize>(value: &T, capacity: usize) -> bool {
+    match serialized_size(value) {
+        Ok(size) => size <= capacity,
+        Err(_) => false,
+    }
 }
 
 fn serialize_enum_variant_index<W: CoreWrite, B: byteorder::ByteOrder + 'static>(
     serializer: &mut Serializer<W, B>,
     variant_index: u32,
+    variant: &'static str,
 ) -> Result<(), SerializeError<W>> {
-    serializer.serialize_u8(variant_index as EnumVariantType)
+    serializer.write_variant(variant_index, variant)
 }
 
 fn serialize_unit_variant<W: CoreWrite, B: byteorder::ByteOrder + 'static>(
     serializer: &mut Serializer<W, B>,
     variant_index: u32,
+    variant: &'static str,
 ) -> Result<(), SerializeError<W>> {
-    serializer.serialize_u8(variant_index as UnitVariantType)
+    serializer.write_variant(variant_index, variant)
 }
 
 fn serialize_seq_len<W: CoreWrite, B: byteorder::ByteOrder + 'static>(
@@ -46,36 +236,38 @@ fn serialize_seq_len<W: CoreWrite, B: byteorder::ByteOrder + 'static>(
     seq_len: Option<usize>,
 ) -> Result<(), SerializeError<W>> {
     let len = seq_len.ok_or(SerializeError::SequenceMustHaveLength)?;
-    serializer.serialize_u16(len as SequenceLengthType)
+    serializer.write_len::<SequenceLengthType>(len)
 }
 
 fn serialize_str_len<W: CoreWrite, B: byteorder::ByteOrder + 'static>(
     serializer: &mut Serializer<W, B>,
     str_len: usize,
 ) -> Result<(), SerializeError<W>> {
-    serializer.serialize_u16(str_len as StrLenType)
+    serializer.write_len::<StrLenType>(str_len)
 }
 
 fn serialize_slice_len<W: CoreWrite, B: byteorder::ByteOrder + 'static>(
     serializer: &mut Serializer<W, B>,
     slice_len: usize,
 ) -> Result<(), SerializeError<W>> {
-    serializer.serialize_u16(slice_len as SliceLenType)
+    serializer.write_len::<SliceLenType>(slice_len)
 }
 
 fn serialize_map_len<W: CoreWrite, B: byteorder::ByteOrder + 'static>(
     serializer: &mut Serializer<W, B>,
     map_len: Option<usize>,
 ) -> Result<(), SerializeError<W>> {
-    let len = map_len.ok_or(SerializeError::SequenceMustHaveLength)?;
-    serializer.serialize_u8(len as MapLenType)
+    // serde writes `#[serde(flatten)]` fields as a map without a length
+    let len = map_len.ok_or(SerializeError::NotSupported("flatten"))?;
+    serializer.write_len::<MapLenType>(len)
 }
 
 fn serialize_struct_variant<W: CoreWrite, B: byteorder::ByteOrder + 'static>(
     serializer: &mut Serializer<W, B>,
     variant_index: u32,
+    variant: &'static str,
 ) -> Result<(), SerializeError<W>> {
-    serializer.serialize_u8(variant_index as StructVariantType)
+    serializer.write_variant(variant_index, variant)
 }
 
 /// Any error that can be thrown while serializing a type
@@ -85,26 +277,81 @@ pub enum SerializeError<W: CoreWrite> {
 
     /// A sequence (e.g. `&str` or `&[u8]`) was requested to serialize, but it has no length.
     SequenceMustHaveLength,
+
+    /// A length or enum variant index does not fit in the type that is written, e.g. a string that
+    /// is longer than `u16::MAX` bytes, or an enum with more than 256 variants.
+    LengthOverflow,
+
+    /// A value that is encoded with a byte order was serialized with [serialize_bytes_only].
+    ByteOrderRequired,
+
+    /// A string that contains a `0` byte was serialized with [Config::cstr_mode].
+    InteriorNul,
+
+    /// A serde feature was used that can not be encoded in this format, e.g. `flatten` for
+    /// `#[serde(flatten)]` fields. These are written as a map without a length.
+    NotSupported(&'static str),
+
+    /// A `Serialize` implementation returned an error with `serde::ser::Error::custom`. The
+    /// message is not kept, as there is no allocator to store it in.
+    Custom,
+}
+
+impl<W: CoreWrite> SerializeError<W> {
+    /// Convert the error of the writer with `f`, and keep every other error.
+    pub(crate) fn map_write<V: CoreWrite>(
+        self,
+        f: impl FnOnce(W::Error) -> V::Error,
+    ) -> SerializeError<V> {
+        match self {
+            SerializeError::Write(e) => SerializeError::Write(f(e)),
+            SerializeError::SequenceMustHaveLength => SerializeError::SequenceMustHaveLength,
+            SerializeError::LengthOverflow => SerializeError::LengthOverflow,
+            SerializeError::ByteOrderRequired => SerializeError::ByteOrderRequired,
+            SerializeError::InteriorNul => SerializeError::InteriorNul,
+            SerializeError::NotSupported(feature) => SerializeError::NotSupported(feature),
+            SerializeError::Custom => SerializeError::Custom,
+        }
+    }
 }
 
 impl<W: CoreWrite> core::fmt::Debug for SerializeError<W> {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
-            SerializeError::Write(w) => write!(fmt, "Write error {:?}", w),
-            SerializeError::SequenceMustHaveLength => write!(fmt, "Sequence does not have length"),
+            SerializeError::Write(e) => fmt.debug_tuple("Write").field(e).finish(),
+            SerializeError::SequenceMustHaveLength => fmt.write_str("SequenceMustHaveLength"),
+            SerializeError::LengthOverflow => fmt.write_str("LengthOverflow"),
+            SerializeError::ByteOrderRequired => fmt.write_str("ByteOrderRequired"),
+            SerializeError::InteriorNul => fmt.write_str("InteriorNul"),
+            SerializeError::NotSupported(feature) => {
+                fmt.debug_tuple("NotSupported").field(feature).finish()
+            }
+            SerializeError::Custom => fmt.write_str("Custom"),
         }
     }
 }
 
 impl<W: CoreWrite> core::fmt::Display for SerializeError<W> {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
-        core::fmt::Debug::fmt(self, fmt)
+        match self {
+            SerializeError::Write(e) => write!(fmt, "write error: {:?}", e),
+            SerializeError::SequenceMustHaveLength => {
+                write!(fmt, "sequence must have a known length")
+            }
+            SerializeError::LengthOverflow => write!(fmt, "length does not fit the length type"),
+            SerializeError::ByteOrderRequired => write!(fmt, "value requires a byte order"),
+            SerializeError::InteriorNul => write!(fmt, "string contains a NUL byte"),
+            SerializeError::NotSupported(feature) => write!(fmt, "{} is not supported", feature),
+            SerializeError::Custom => write!(fmt, "custom error of a Serialize implementation"),
+        }
     }
 }
 
+impl<W: CoreWrite> serde::ser::StdError for SerializeError<W> {}
+
 impl<W: CoreWrite> Error for SerializeError<W> {
     fn custom<T: core::fmt::Display>(_cause: T) -> Self {
-        panic!("Custom error: {}", _cause);
+        SerializeError::Custom
     }
 }
 
@@ -112,9 +359,217 @@ impl<W: CoreWrite> Error for SerializeError<W> {
 /// [CoreWrite] writer.
 pub struct Serializer<W: CoreWrite, B: byteorder::ByteOrder + 'static> {
     writer: W,
+    config: Config,
+    /// Tags that are waiting to be written when [Config::bit_pack_tags] is enabled.
+    tag_bits: u8,
+    tag_count: u8,
+    /// Skip the tag of the variant that is serialized next, see [Config::omit_single_variant_tag].
+    /// Only set when the next value is known to be a variant.
+    omit_variant: bool,
     pd: PhantomData<B>,
 }
 
+impl<W: CoreWrite, B: byteorder::ByteOrder + 'static> Serializer<W, B> {
+    /// Create a new serializer. Values can be serialized by passing `&mut serializer` to
+    /// `serde::Serialize::serialize`. Call [Serializer::finish] when all values are serialized.
+    ///
+    /// Most code should use [serialize] or [serialize_with_config] instead. This is useful to mix
+    /// serde values with the inherent methods on this type:
+    ///
+    /// ```
+    /// # use bincode_embedded::{BufferWriter, Config, Serializer};
+    /// # use serde::Serialize;
+    /// let mut buffer = [0u8; 16];
+    /// let mut writer = BufferWriter::new(&mut buffer);
+    /// let mut serializer = Serializer::<_, byteorder::NetworkEndian>::new(&mut writer, Config::new());
+    /// 5u8.serialize(&mut serializer).unwrap();
+    /// serializer.serialize_str_unprefixed("abc").unwrap();
+    /// serializer.finish().unwrap();
+    /// assert_eq!(&[5, b'a', b'b', b'c'], writer.written_buffer());
+    /// ```
+    pub fn new(writer: W, config: Config) -> Self {
+        Self {
+            writer,
+            config,
+            tag_bits: 0,
+            tag_count: 0,
+            omit_variant: false,
+            pd: PhantomData,
+        }
+    }
+
+    /// Write any pending data and return the writer.
+    pub fn finish(mut self) -> Result<W, SerializeError<W>> {
+        self.flush_tags()?;
+        Ok(self.writer)
+    }
+
+    /// Write the bytes of a string without a length prefix. This can be used for fixed-size text
+    /// fields whose length is known to both sides, see [Deserializer::deserialize_str_len].
+    pub fn serialize_str_unprefixed(&mut self, value: &str) -> Result<(), SerializeError<W>> {
+        self.write_bytes(value.as_bytes())
+    }
+
+    /// Write the elements of a sequence without a length prefix. This can be used for protocols
+    /// that carry the element count elsewhere, e.g. in a header field, and saves the two bytes of
+    /// the length prefix. See [Deserializer::deserialize_seq_with_len] to read the sequence back.
+    ///
+    /// ```
+    /// # use bincode_embedded::{BufferWriter, Config, Serializer};
+    /// let mut buffer = [0u8; 16];
+    /// let mut writer = BufferWriter::new(&mut buffer);
+    /// let mut serializer = Serializer::<_, byteorder::NetworkEndian>::new(&mut writer, Config::new());
+    /// serializer.serialize_seq_no_len(&[1u16, 2, 3]).unwrap();
+    /// serializer.finish().unwrap();
+    /// assert_eq!(&[0, 1, 0, 2, 0, 3], writer.written_buffer());
+    /// ```
+    pub fn serialize_seq_no_len<I>(&mut self, values: I) -> Result<(), SerializeError<W>>
+    where
+        I: IntoIterator,
+        I::Item: Serialize,
+    {
+        for value in values {
+            value.serialize(&mut *self)?;
+        }
+        Ok(())
+    }
+
+    /// Write a slice of `f32` without a length prefix. This is encoded the same as an array or
+    /// tuple of `f32`, but converts the values in chunks and hands every chunk to the writer in a
+    /// single [CoreWrite::write_all] call, instead of one call per value.
+    ///
+    /// ```
+    /// # use bincode_embedded::{deserialize, BufferWriter, Config, Serializer};
+    /// let samples = [0.5f32; 32];
+    /// let mut buffer = [0u8; 128];
+    /// let mut writer = BufferWriter::new(&mut buffer);
+    /// let mut serializer = Serializer::<_, byteorder::LittleEndian>::new(&mut writer, Config::new());
+    /// serializer.serialize_f32_slice(&samples).unwrap();
+    /// serializer.finish().unwrap();
+    /// let value: [f32; 32] =
+    ///     deserialize::<_, _, byteorder::LittleEndian>(writer.written_buffer()).unwrap();
+    /// assert_eq!(samples, value);
+    /// ```
+    pub fn serialize_f32_slice(&mut self, values: &[f32]) -> Result<(), SerializeError<W>> {
+        const CHUNK: usize = 16;
+        if self.config.bytes_only && !values.is_empty() {
+            return Err(SerializeError::ByteOrderRequired);
+        }
+        let mut buf = [0u8; CHUNK * 4];
+        for chunk in values.chunks(CHUNK) {
+            for (&value, out) in chunk.iter().zip(buf.chunks_exact_mut(4)) {
+                let value = if self.config.canonical_nan && value.is_nan() {
+                    f32::NAN
+                } else {
+                    value
+                };
+                write_primitive::<B, f32>(self.config.value_endian, value, out);
+            }
+            self.write_bytes(&buf[..chunk.len() * 4])?;
+        }
+        Ok(())
+    }
+
+    /// Write a `bool` or an `Option` tag. With [Config::bit_pack_tags] this is collected into a
+    /// packed byte, otherwise this writes a full byte.
+    fn write_tag(&mut self, tag: bool) -> Result<(), SerializeError<W>> {
+        if !self.config.bit_pack_tags {
+            return self.write_byte(tag as u8);
+        }
+        self.tag_bits |= (tag as u8) << self.tag_count;
+        self.tag_count += 1;
+        if self.tag_count == 8 {
+            self.flush_tags()?;
+        }
+        Ok(())
+    }
+
+    /// Write the pending packed tags, if there are any.
+    fn flush_tags(&mut self) -> Result<(), SerializeError<W>> {
+        if self.tag_count == 0 {
+            return Ok(());
+        }
+        let bits = self.tag_bits;
+        self.tag_bits = 0;
+        self.tag_count = 0;
+        self.write_raw(&[bits])
+    }
+
+    /// Write `bytes` without flushing the packed tags.
+    fn write_raw(&mut self, bytes: &[u8]) -> Result<(), SerializeError<W>> {
+        self.writer.write_all(bytes).map_err(SerializeError::Write)
+    }
+
+    /// Write a single byte. This uses [CoreWrite::write_all] like every other write, so a writer
+    /// only has to optimize one method.
+    fn write_byte(&mut self, byte: u8) -> Result<(), SerializeError<W>> {
+        self.write_bytes(&[byte])
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SerializeError<W>> {
+        self.flush_tags()?;
+        self.write_raw(bytes)
+    }
+
+    fn write_value<T: Primitive>(&mut self, value: T) -> Result<(), SerializeError<W>> {
+        self.write_primitive(self.config.value_endian, value)
+    }
+
+    /// Write the tag of an enum variant, see [Config::variant_width] and
+    /// [Config::string_enum_tags].
+    fn write_variant(&mut self, index: u32, name: &'static str) -> Result<(), SerializeError<W>> {
+        if core::mem::take(&mut self.omit_variant) {
+            if index != 0 {
+                return Err(SerializeError::NotSupported(
+                    "SingleVariant of an enum with more than one variant",
+                ));
+            }
+            return Ok(());
+        }
+        if self.config.string_enum_tags {
+            serialize_str_len(self, name.len())?;
+            return self.write_bytes(name.as_bytes());
+        }
+        match self.config.variant_width {
+            VariantWidth::U8 => self.write_value(narrow::<u8, W>(index as usize)?),
+            VariantWidth::U16 => self.write_value(narrow::<u16, W>(index as usize)?),
+            VariantWidth::U32 => self.write_value(index),
+        }
+    }
+
+    fn write_length<T: Primitive>(&mut self, len: T) -> Result<(), SerializeError<W>> {
+        self.write_primitive(self.config.length_endian, len)
+    }
+
+    /// Write a length prefix as a `T`, or as a `u64` with [LengthWidth::U64].
+    fn write_len<T: Primitive + TryFrom<usize>>(
+        &mut self,
+        len: usize,
+    ) -> Result<(), SerializeError<W>> {
+        match self.config.length_width {
+            LengthWidth::Compact => {
+                let len: T = narrow(len)?;
+                self.write_length(len)
+            }
+            LengthWidth::U64 => self.write_length(len as u64),
+        }
+    }
+
+    fn write_primitive<T: Primitive>(
+        &mut self,
+        order: Option<RuntimeEndian>,
+        value: T,
+    ) -> Result<(), SerializeError<W>> {
+        if self.config.bytes_only && T::SIZE > 1 {
+            return Err(SerializeError::ByteOrderRequired);
+        }
+        let mut buf = [0u8; 16];
+        let buf = &mut buf[..T::SIZE];
+        write_primitive::<B, T>(order, value, buf);
+        self.write_bytes(buf)
+    }
+}
+
 impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> serde::Serializer
     for &'a mut Serializer<W, B>
 {
@@ -124,113 +579,101 @@ impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> serde::Serializer
     type SerializeTuple = Compound<'a, W, B>;
     type SerializeTupleStruct = Compound<'a, W, B>;
     type SerializeTupleVariant = Compound<'a, W, B>;
-    type SerializeMap = Compound<'a, W, B>;
-    type SerializeStruct = Compound<'a, W, B>;
-    type SerializeStructVariant = Compound<'a, W, B>;
+    type SerializeMap = MapCompound<'a, W, B>;
+    type SerializeStruct = StructCompound<'a, W, B>;
+    type SerializeStructVariant = StructCompound<'a, W, B>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
-        self.writer.write(v as u8).map_err(SerializeError::Write)
+        if self.config.bool_width == BoolWidth::Four && !self.config.bit_pack_tags {
+            return self.write_value(v as u32);
+        }
+        self.write_tag(v)
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
-        self.writer.write(v as u8).map_err(SerializeError::Write)
+        self.write_byte(v as u8)
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
-        let mut buf = [0u8; 2];
-        B::write_i16(&mut buf, v);
-        self.writer.write_all(&buf).map_err(SerializeError::Write)
+        self.write_value(v)
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
-        let mut buf = [0u8; 4];
-        B::write_i32(&mut buf, v);
-        self.writer.write_all(&buf).map_err(SerializeError::Write)
+        self.write_value(v)
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        let mut buf = [0u8; 8];
-        B::write_i64(&mut buf, v);
-        self.writer.write_all(&buf).map_err(SerializeError::Write)
+        self.write_value(v)
     }
 
-    serde_if_integer128! {
-        fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
-            let mut buf = [0u8; 16];
-            B::write_i128(&mut buf, v);
-            self.writer.write_all(&buf).map_err(SerializeError::Write)
-        }
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.write_value(v)
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
-        self.writer.write(v).map_err(SerializeError::Write)
+        self.write_byte(v)
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
-        let mut buf = [0u8; 2];
-        B::write_u16(&mut buf, v);
-        self.writer.write_all(&buf).map_err(SerializeError::Write)
+        self.write_value(v)
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
-        let mut buf = [0u8; 4];
-        B::write_u32(&mut buf, v);
-        self.writer.write_all(&buf).map_err(SerializeError::Write)
+        self.write_value(v)
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        let mut buf = [0u8; 8];
-        B::write_u64(&mut buf, v);
-        self.writer.write_all(&buf).map_err(SerializeError::Write)
+        self.write_value(v)
     }
 
-    serde_if_integer128! {
-        fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
-            let mut buf = [0u8; 16];
-            B::write_u128(&mut buf, v);
-            self.writer.write_all(&buf).map_err(SerializeError::Write)
-        }
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.write_value(v)
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        let mut buf = [0u8; 4];
-        B::write_f32(&mut buf, v);
-        self.writer.write_all(&buf).map_err(SerializeError::Write)
+        if self.config.canonical_nan && v.is_nan() {
+            return self.write_value(f32::NAN);
+        }
+        self.write_value(v)
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        let mut buf = [0u8; 8];
-        B::write_f64(&mut buf, v);
-        self.writer.write_all(&buf).map_err(SerializeError::Write)
+        if self.config.canonical_nan && v.is_nan() {
+            return self.write_value(f64::NAN);
+        }
+        self.write_value(v)
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
-        self.writer
-            .write_all(encode_utf8(v).as_slice())
-            .map_err(SerializeError::Write)
+        if self.config.fixed_char {
+            return self.write_value(v as u32);
+        }
+        self.write_bytes(encode_utf8(v).as_slice())
     }
 
-    fn serialize_str(mut self, v: &str) -> Result<Self::Ok, Self::Error> {
-        serialize_str_len(&mut self, v.len())?;
-        self.writer
-            .write_all(v.as_bytes())
-            .map_err(SerializeError::Write)
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        if self.config.cstr_mode {
+            if v.as_bytes().contains(&0) {
+                return Err(SerializeError::InteriorNul);
+            }
+            self.write_bytes(v.as_bytes())?;
+            return self.write_byte(0);
+        }
+        serialize_str_len(self, v.len())?;
+        self.write_bytes(v.as_bytes())
     }
 
-    fn serialize_bytes(mut self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        serialize_slice_len(&mut self, v.len())?;
-        self.writer.write_all(v).map_err(SerializeError::Write)
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        serialize_slice_len(self, v.len())?;
+        self.write_bytes(v)
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
-        self.writer.write(0).map_err(SerializeError::Write)
+        self.write_tag(false)
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
-    where
-        T: Serialize,
-    {
-        self.writer.write(1).map_err(SerializeError::Write)?;
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.write_tag(true)?;
         value.serialize(self)
     }
 
@@ -243,41 +686,41 @@ impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> serde::Serializer
     }
 
     fn serialize_unit_variant(
-        mut self,
+        self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        serialize_unit_variant(&mut self, variant_index)
+        serialize_unit_variant(self, variant_index, variant)
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
-    ) -> Result<Self::Ok, Self::Error>
-    where
-        T: Serialize,
-    {
+    ) -> Result<Self::Ok, Self::Error> {
+        if name == SINGLE_VARIANT_NAME && self.config.omit_single_variant_tag && is_variant(value) {
+            self.omit_variant = true;
+            let result = value.serialize(&mut *self);
+            self.omit_variant = false;
+            return result;
+        }
         value.serialize(self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
-        mut self,
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         value: &T,
-    ) -> Result<Self::Ok, Self::Error>
-    where
-        T: Serialize,
-    {
-        serialize_enum_variant_index(&mut self, variant_index)?;
+    ) -> Result<Self::Ok, Self::Error> {
+        serialize_enum_variant_index(self, variant_index, variant)?;
         value.serialize(self)
     }
 
-    fn serialize_seq(mut self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        serialize_seq_len(&mut self, len)?;
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        serialize_seq_len(self, len)?;
         Ok(Compound {
             ser: self,
             pd: PhantomData,
@@ -303,57 +746,79 @@ impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> serde::Serializer
     }
 
     fn serialize_tuple_variant(
-        mut self,
+        self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        serialize_enum_variant_index(&mut self, variant_index)?;
+        serialize_enum_variant_index(self, variant_index, variant)?;
         Ok(Compound {
             ser: self,
             pd: PhantomData,
         })
     }
 
-    fn serialize_map(mut self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        serialize_map_len(&mut self, len)?;
-        Ok(Compound {
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        serialize_map_len(self, len)?;
+        #[cfg(feature = "alloc")]
+        let sorted = if self.config.sort_map_keys {
+            if self.config.bit_pack_tags {
+                return Err(SerializeError::NotSupported(
+                    "sort_map_keys with bit_pack_tags",
+                ));
+            }
+            Some(alloc::vec::Vec::new())
+        } else {
+            None
+        };
+        Ok(MapCompound {
             ser: self,
-            pd: PhantomData,
+            #[cfg(feature = "alloc")]
+            sorted,
         })
     }
 
     fn serialize_struct(
         self,
-        _name: &'static str,
-        _len: usize,
+        name: &'static str,
+        len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        Ok(Compound {
+        if self.config.struct_field_guard {
+            let len: u8 = narrow(len)?;
+            self.write_value(len)?;
+        }
+        Ok(StructCompound {
             ser: self,
-            pd: PhantomData,
+            name,
+            len,
+            index: 0,
         })
     }
 
     fn serialize_struct_variant(
-        mut self,
+        self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
-        _len: usize,
+        variant: &'static str,
+        len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        serialize_struct_variant(&mut self, variant_index)?;
-        Ok(Compound {
+        serialize_struct_variant(self, variant_index, variant)?;
+        Ok(StructCompound {
             ser: self,
-            pd: PhantomData,
+            name: variant,
+            len,
+            index: 0,
         })
     }
 
-    fn collect_str<T: ?Sized>(self, _value: &T) -> Result<Self::Ok, Self::Error>
-    where
-        T: core::fmt::Display,
-    {
-        panic!("Unimplemented: Serialize::collect_str")
+    /// The length of the string is needed before it is written, and there is no allocator to
+    /// format it into, so this results in [SerializeError::NotSupported].
+    fn collect_str<T: ?Sized + core::fmt::Display>(
+        self,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(SerializeError::NotSupported("collect_str"))
     }
 
     fn is_human_readable(&self) -> bool {
@@ -372,10 +837,7 @@ impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> SerializeSeq for Compo
     type Error = SerializeError<W>;
 
     #[inline]
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
-    where
-        T: serde::ser::Serialize,
-    {
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
         value.serialize(&mut *self.ser)
     }
 
@@ -390,10 +852,7 @@ impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> SerializeTuple for Com
     type Error = SerializeError<W>;
 
     #[inline]
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
-    where
-        T: serde::ser::Serialize,
-    {
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
         value.serialize(&mut *self.ser)
     }
 
@@ -410,10 +869,7 @@ impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> SerializeTupleStruct
     type Error = SerializeError<W>;
 
     #[inline]
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
-    where
-        T: serde::ser::Serialize,
-    {
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
         value.serialize(&mut *self.ser)
     }
 
@@ -430,10 +886,7 @@ impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> SerializeTupleVariant
     type Error = SerializeError<W>;
 
     #[inline]
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
-    where
-        T: serde::ser::Serialize,
-    {
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
         value.serialize(&mut *self.ser)
     }
 
@@ -443,46 +896,144 @@ impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> SerializeTupleVariant
     }
 }
 
-impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> SerializeMap for Compound<'a, W, B> {
+/// The `SerializeMap` implementation of [Serializer].
+///
+/// With [Config::sort_map_keys], the entries are serialized to separate buffers and written
+/// sorted by their key when the map ends.
+pub struct MapCompound<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> {
+    ser: &'a mut Serializer<W, B>,
+    /// The serialized keys and values, if the entries are sorted.
+    #[cfg(feature = "alloc")]
+    sorted: Option<alloc::vec::Vec<(alloc::vec::Vec<u8>, alloc::vec::Vec<u8>)>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> MapCompound<'a, W, B> {
+    /// Serialize `value` to a new buffer with the same config as the map.
+    fn buffer<T: ?Sized + Serialize>(
+        &self,
+        value: &T,
+    ) -> Result<alloc::vec::Vec<u8>, SerializeError<W>> {
+        let mut serializer = Serializer::<VecWriter, B>::new(VecWriter::default(), self.ser.config);
+        value
+            .serialize(&mut serializer)
+            .and_then(|()| serializer.finish())
+            .map(|writer| writer.0)
+            .map_err(|e| e.map_write(|never| match never {}))
+    }
+}
+
+/// A writer that collects the bytes of a map entry, see [Config::sort_map_keys].
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+struct VecWriter(alloc::vec::Vec<u8>);
+
+#[cfg(feature = "alloc")]
+impl CoreWrite for VecWriter {
+    type Error = core::convert::Infallible;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.0.push(val);
+        Ok(())
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        self.0.extend_from_slice(val);
+        Ok(())
+    }
+}
+
+impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> SerializeMap for MapCompound<'a, W, B> {
     type Ok = ();
     type Error = SerializeError<W>;
 
     #[inline]
-    fn serialize_key<K: ?Sized>(&mut self, value: &K) -> Result<(), Self::Error>
-    where
-        K: serde::ser::Serialize,
-    {
+    fn serialize_key<K: ?Sized + Serialize>(&mut self, value: &K) -> Result<(), Self::Error> {
+        #[cfg(feature = "alloc")]
+        if self.sorted.is_some() {
+            let key = self.buffer(value)?;
+            if let Some(sorted) = &mut self.sorted {
+                sorted.push((key, alloc::vec::Vec::new()));
+            }
+            return Ok(());
+        }
         value.serialize(&mut *self.ser)
     }
 
     #[inline]
-    fn serialize_value<V: ?Sized>(&mut self, value: &V) -> Result<(), Self::Error>
-    where
-        V: serde::ser::Serialize,
-    {
+    fn serialize_value<V: ?Sized + Serialize>(&mut self, value: &V) -> Result<(), Self::Error> {
+        #[cfg(feature = "alloc")]
+        if self.sorted.is_some() {
+            let value = self.buffer(value)?;
+            if let Some((_, entry)) = self.sorted.as_mut().and_then(|sorted| sorted.last_mut()) {
+                *entry = value;
+            }
+            return Ok(());
+        }
         value.serialize(&mut *self.ser)
     }
 
     #[inline]
     fn end(self) -> Result<(), Self::Error> {
+        #[cfg(feature = "alloc")]
+        if let Some(mut sorted) = self.sorted {
+            sorted.sort_by(|a, b| a.0.cmp(&b.0));
+            for (key, value) in sorted {
+                self.ser.write_bytes(&key)?;
+                self.ser.write_bytes(&value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The `SerializeStruct` and `SerializeStructVariant` implementation of [Serializer].
+///
+/// With [Config::field_mask], this writes a presence mask before every group of 8 fields, and
+/// skips the fields that [Config::field_filter] leaves out.
+pub struct StructCompound<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> {
+    ser: &'a mut Serializer<W, B>,
+    /// The name of the struct or variant, which is passed to the field filter.
+    name: &'static str,
+    len: usize,
+    index: usize,
+}
+
+impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> StructCompound<'a, W, B> {
+    fn field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerializeError<W>> {
+        let index = self.index;
+        self.index += 1;
+        if !self.ser.config.field_mask {
+            return value.serialize(&mut *self.ser);
+        }
+        let (name, filter) = (self.name, self.ser.config.field_filter);
+        let present = |index| filter.map_or(true, |filter| filter(name, index));
+        if index % 8 == 0 {
+            let mask = (index..self.len.min(index + 8))
+                .filter(|i| present(*i))
+                .fold(0u8, |mask, i| mask | 1 << (i % 8));
+            self.ser.write_value(mask)?;
+        }
+        if present(index) {
+            value.serialize(&mut *self.ser)?;
+        }
         Ok(())
     }
 }
 
-impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> SerializeStruct for Compound<'a, W, B> {
+impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> SerializeStruct
+    for StructCompound<'a, W, B>
+{
     type Ok = ();
     type Error = SerializeError<W>;
 
     #[inline]
-    fn serialize_field<T: ?Sized>(
+    fn serialize_field<T: ?Sized + Serialize>(
         &mut self,
         _key: &'static str,
         value: &T,
-    ) -> Result<(), Self::Error>
-    where
-        T: serde::ser::Serialize,
-    {
-        value.serialize(&mut *self.ser)
+    ) -> Result<(), Self::Error> {
+        self.field(value)
     }
 
     #[inline]
@@ -492,7 +1043,7 @@ impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> SerializeStruct for Co
 }
 
 impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> SerializeStructVariant
-    for Compound<'a, W, B>
+    for StructCompound<'a, W, B>
 {
     type Ok = ();
     type Error = SerializeError<W>;
@@ -502,7 +1053,7 @@ impl<'a, W: CoreWrite, B: byteorder::ByteOrder + 'static> SerializeStructVariant
         _key: &'static str,
         value: &T,
     ) -> Result<(), Self::Error> {
-        value.serialize(&mut *self.ser)
+        self.field(value)
     }
 
     fn end(self) -> Result<(), Self::Error> {
@@ -0,0 +1,217 @@
+use core::fmt;
+use core::marker::PhantomData;
+use serde::de::{Deserialize, Deserializer, Visitor};
+use serde::ser::{Impossible, Serialize, Serializer};
+
+/// The name that [SingleVariant] passes to `serialize_newtype_struct` and
+/// `deserialize_newtype_struct`. The [Serializer](crate::Serializer) and
+/// [Deserializer](crate::Deserializer) of this crate recognize this name, and skip the tag of the
+/// enum that is wrapped.
+pub(crate) const SINGLE_VARIANT_NAME: &str = "$bincode_embedded::SingleVariant";
+
+/// An enum that has exactly one variant, see [Config::omit_single_variant_tag](crate::Config::omit_single_variant_tag).
+///
+/// serde does not pass the amount of variants of an enum to the serializer, so the serializer can
+/// not tell that an enum has a single variant. Wrapping the enum in this type tells both the
+/// serializer and the deserializer to skip the tag. Only the tag of the wrapped enum itself is
+/// skipped, enums that are nested in it keep their tag.
+///
+/// Without [Config::omit_single_variant_tag](crate::Config::omit_single_variant_tag) this is
+/// encoded the same as `T`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct SingleVariant<T>(pub T);
+
+impl<T: Serialize> Serialize for SingleVariant<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(SINGLE_VARIANT_NAME, &self.0)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SingleVariant<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SingleVariantVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for SingleVariantVisitor<T> {
+            type Value = SingleVariant<T>;
+
+            fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                fmt.write_str("an enum with a single variant")
+            }
+
+            fn visit_newtype_struct<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<Self::Value, D::Error> {
+                T::deserialize(deserializer).map(SingleVariant)
+            }
+        }
+
+        deserializer
+            .deserialize_newtype_struct(SINGLE_VARIANT_NAME, SingleVariantVisitor(PhantomData))
+    }
+}
+
+/// Returns true if `value` serializes as an enum variant, looking through newtype structs. The
+/// [Serializer](crate::Serializer) only skips the tag of a [SingleVariant] that directly wraps an
+/// enum, so the tags of enums that are nested deeper are kept.
+pub(crate) fn is_variant<T: ?Sized + Serialize>(value: &T) -> bool {
+    matches!(value.serialize(VariantProbe), Err(Probe::Variant))
+}
+
+/// What [VariantProbe] found. Both outcomes are errors, so that the value stops serializing as
+/// soon as it is known.
+#[derive(Debug)]
+enum Probe {
+    Variant,
+    Other,
+}
+
+impl fmt::Display for Probe {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, fmt)
+    }
+}
+
+impl serde::ser::StdError for Probe {}
+
+impl serde::ser::Error for Probe {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        Probe::Other
+    }
+}
+
+/// A serializer that writes nothing, and only reports whether the value is an enum variant.
+struct VariantProbe;
+
+macro_rules! other {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method(self, _v: $ty) -> Result<(), Probe> {
+                Err(Probe::Other)
+            }
+        )*
+    };
+}
+
+impl Serializer for VariantProbe {
+    type Ok = ();
+    type Error = Probe;
+    type SerializeSeq = Impossible<(), Probe>;
+    type SerializeTuple = Impossible<(), Probe>;
+    type SerializeTupleStruct = Impossible<(), Probe>;
+    type SerializeTupleVariant = Impossible<(), Probe>;
+    type SerializeMap = Impossible<(), Probe>;
+    type SerializeStruct = Impossible<(), Probe>;
+    type SerializeStructVariant = Impossible<(), Probe>;
+
+    other! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_struct(&'static str),
+    }
+
+    fn serialize_none(self) -> Result<(), Probe> {
+        Err(Probe::Other)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<(), Probe> {
+        Err(Probe::Other)
+    }
+
+    fn serialize_unit(self) -> Result<(), Probe> {
+        Err(Probe::Other)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Probe> {
+        Err(Probe::Variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Probe> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Probe> {
+        Err(Probe::Variant)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Probe> {
+        Err(Probe::Other)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Probe> {
+        Err(Probe::Other)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Probe> {
+        Err(Probe::Other)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Probe> {
+        Err(Probe::Variant)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Probe> {
+        Err(Probe::Other)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Probe> {
+        Err(Probe::Other)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Probe> {
+        Err(Probe::Variant)
+    }
+
+    fn collect_str<T: ?Sized + fmt::Display>(self, _value: &T) -> Result<(), Probe> {
+        Err(Probe::Other)
+    }
+}
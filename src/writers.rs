@@ -0,0 +1,1165 @@
+use super::*;
+
+const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+
+/// A [CoreWrite] adapter that writes every byte as two ASCII hex characters to an inner writer.
+///
+/// This is useful during bring-up, to dump serialized values to a text console:
+///
+/// ```
+/// # use bincode_embedded::{serialize, BufferWriter, HexWriter};
+/// let mut buffer = [0u8; 16];
+/// let mut writer = BufferWriter::new(&mut buffer);
+/// serialize::<_, _, byteorder::NetworkEndian>(&0x1234u16, HexWriter::with_spaces(&mut writer))
+///     .unwrap();
+/// assert_eq!(b"12 34", writer.written_buffer());
+/// ```
+pub struct HexWriter<W: CoreWrite> {
+    inner: W,
+    spaces: bool,
+    first: bool,
+}
+
+impl<W: CoreWrite> HexWriter<W> {
+    /// Create a new hex writer that writes the hex characters without any separators.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            spaces: false,
+            first: true,
+        }
+    }
+
+    /// Create a new hex writer that writes a space between every byte.
+    pub fn with_spaces(inner: W) -> Self {
+        Self {
+            inner,
+            spaces: true,
+            first: true,
+        }
+    }
+
+    /// Get the inner writer back.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: CoreWrite> CoreWrite for HexWriter<W> {
+    type Error = W::Error;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        if self.spaces && !self.first {
+            self.inner.write(b' ')?;
+        }
+        self.first = false;
+        self.inner.write_all(&[
+            HEX_CHARS[(val >> 4) as usize],
+            HEX_CHARS[(val & 0x0F) as usize],
+        ])
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+/// A [CoreWrite] implementation that discards every byte.
+///
+/// This can be used to check that a value serializes without errors, or to benchmark the
+/// serializer without the overhead of a real writer.
+///
+/// ```
+/// # use bincode_embedded::{serialize, NullWriter};
+/// serialize::<_, _, byteorder::NetworkEndian>(&(1u8, "abc"), NullWriter).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NullWriter;
+
+impl CoreWrite for NullWriter {
+    type Error = core::convert::Infallible;
+
+    fn write(&mut self, _val: u8) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn write_all(&mut self, _val: &[u8]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// A [CoreWrite] implementation that only counts the bytes that are written to it.
+///
+/// See [serialized_size] to get the size of a value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CountWriter {
+    count: usize,
+}
+
+impl CountWriter {
+    /// Create a new writer with a count of `0`.
+    pub const fn new() -> Self {
+        Self { count: 0 }
+    }
+
+    /// The amount of bytes that are written to this writer.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl CoreWrite for CountWriter {
+    type Error = core::convert::Infallible;
+
+    fn write(&mut self, _val: u8) -> Result<(), Self::Error> {
+        self.count += 1;
+        Ok(())
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        self.count += val.len();
+        Ok(())
+    }
+}
+
+impl CoreWrite for &'_ mut CountWriter {
+    type Error = core::convert::Infallible;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (**self).write(val)
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(val)
+    }
+}
+
+/// A [CoreWrite] adapter that limits the amount of bytes that are written to an inner writer.
+///
+/// This limits the size of a frame independently of the storage, e.g. for a radio with a fixed
+/// MTU. Bytes are written to the inner writer until the limit is reached, after that every write
+/// returns [LimitWriterError::Exceeded].
+///
+/// ```
+/// # use bincode_embedded::{serialize, BufferWriter, LimitWriter, LimitWriterError, SerializeError};
+/// let mut buffer = [0u8; 64];
+/// let mut writer = BufferWriter::new(&mut buffer);
+/// let result =
+///     serialize::<_, _, byteorder::NetworkEndian>(&[0u32; 4], LimitWriter::new(&mut writer, 10));
+/// assert!(matches!(result, Err(SerializeError::Write(LimitWriterError::Exceeded))));
+/// assert_eq!(10, writer.written_len());
+/// ```
+pub struct LimitWriter<W: CoreWrite> {
+    inner: W,
+    max: usize,
+    written: usize,
+}
+
+impl<W: CoreWrite> LimitWriter<W> {
+    /// Create a new writer that writes at most `max` bytes to `inner`.
+    pub fn new(inner: W, max: usize) -> Self {
+        Self {
+            inner,
+            max,
+            written: 0,
+        }
+    }
+
+    /// The amount of bytes that are written to the inner writer.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Get the inner writer back.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Errors that can be returned from a [LimitWriter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitWriterError<E> {
+    /// More bytes were written than the limit of the [LimitWriter] allows.
+    Exceeded,
+
+    /// The inner writer returned an error.
+    Write(E),
+}
+
+impl<W: CoreWrite> CoreWrite for LimitWriter<W> {
+    type Error = LimitWriterError<W::Error>;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.write_all(&[val])
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush().map_err(LimitWriterError::Write)
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        // Write the bytes that still fit, so the inner writer ends up exactly at the limit
+        let available = self.max - self.written;
+        let len = val.len().min(available);
+        self.inner
+            .write_all(&val[..len])
+            .map_err(LimitWriterError::Write)?;
+        self.written += len;
+        if len < val.len() {
+            return Err(LimitWriterError::Exceeded);
+        }
+        Ok(())
+    }
+}
+
+impl<W: CoreWrite> CoreWrite for &'_ mut LimitWriter<W> {
+    type Error = LimitWriterError<W::Error>;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (**self).write(val)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (**self).flush()
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(val)
+    }
+}
+
+/// A [CoreWrite] adapter that counts the bytes that are written to an inner writer and computes
+/// their CRC-32 at the same time.
+///
+/// This builds a frame with a length and checksum trailer in a single pass, instead of running
+/// [serialized_size] and a separate checksum over the output. The checksum is the common CRC-32
+/// (IEEE 802.3, as used by e.g. zlib and Ethernet). Bytes are only counted once the inner writer
+/// accepts them.
+///
+/// ```
+/// # use bincode_embedded::{serialize, BufferWriter, MeteredWriter};
+/// let mut buffer = [0u8; 16];
+/// let mut writer = BufferWriter::new(&mut buffer);
+/// let mut metered = MeteredWriter::new(&mut writer);
+/// // Arrays have no length prefix
+/// serialize::<_, _, byteorder::NetworkEndian>(b"123456789", &mut metered).unwrap();
+/// let (_, len, crc) = metered.into_parts();
+/// assert_eq!(9, len);
+/// assert_eq!(0xCBF4_3926, crc);
+/// ```
+pub struct MeteredWriter<W: CoreWrite> {
+    inner: W,
+    written: usize,
+    crc: u32,
+}
+
+impl<W: CoreWrite> MeteredWriter<W> {
+    /// Create a new writer that forwards every byte to `inner`.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            written: 0,
+            crc: !0,
+        }
+    }
+
+    /// The amount of bytes that are written to the inner writer.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// The CRC-32 of the bytes that are written to the inner writer.
+    pub fn crc(&self) -> u32 {
+        !self.crc
+    }
+
+    /// Get the inner writer back, together with the amount of bytes that are written and their
+    /// CRC-32.
+    pub fn into_parts(self) -> (W, usize, u32) {
+        let crc = self.crc();
+        (self.inner, self.written, crc)
+    }
+}
+
+/// Lookup table for the reflected CRC-32 polynomial `0xEDB8_8320`.
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+impl<W: CoreWrite> CoreWrite for MeteredWriter<W> {
+    type Error = W::Error;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.write_all(&[val])
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        self.inner.write_all(val)?;
+        self.written += val.len();
+        for &byte in val {
+            self.crc = CRC32_TABLE[((self.crc ^ byte as u32) & 0xFF) as usize] ^ (self.crc >> 8);
+        }
+        Ok(())
+    }
+}
+
+impl<W: CoreWrite> CoreWrite for &'_ mut MeteredWriter<W> {
+    type Error = W::Error;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (**self).write(val)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (**self).flush()
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(val)
+    }
+}
+
+/// A [CoreWrite] adapter that collects small writes in a buffer of `N` bytes, and hands them to an
+/// inner writer in a single [CoreWrite::write_all] call when the buffer is full.
+///
+/// The serializer writes every value with its own call, e.g. a struct of 16 `u16` fields results
+/// in 16 calls. This adapter reduces that to a single call, which helps writers with a high
+/// overhead per call, like a UART behind a lock. The bytes are the same either way. Values are not
+/// split: the buffer is written when the next value does not fit, and values of `N` bytes or more
+/// are handed to the inner writer directly.
+///
+/// The bytes that are left in the buffer are written by [CoreWrite::flush] or
+/// [CoalescingWriter::finish], so a writer that is dropped without calling either may not write
+/// everything.
+///
+/// ```
+/// # use bincode_embedded::{serialize, BufferWriter, CoalescingWriter};
+/// let mut buffer = [0u8; 16];
+/// let mut writer = BufferWriter::new(&mut buffer);
+/// let mut coalescing = CoalescingWriter::<_, 8>::new(&mut writer);
+/// serialize::<_, _, byteorder::NetworkEndian>(&(1u8, 2u16, 3u32), &mut coalescing).unwrap();
+/// coalescing.finish().unwrap();
+/// assert_eq!(&[1, 0, 2, 0, 0, 0, 3], writer.written_buffer());
+/// ```
+pub struct CoalescingWriter<W: CoreWrite, const N: usize = 32> {
+    inner: W,
+    buffer: [u8; N],
+    len: usize,
+}
+
+impl<W: CoreWrite, const N: usize> CoalescingWriter<W, N> {
+    /// Create a new writer that collects writes to `inner`.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buffer: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Write the collected bytes and return the inner writer.
+    pub fn finish(mut self) -> Result<W, W::Error> {
+        self.write_buffer()?;
+        Ok(self.inner)
+    }
+
+    /// Write the collected bytes to the inner writer, if there are any.
+    fn write_buffer(&mut self) -> Result<(), W::Error> {
+        if self.len == 0 {
+            return Ok(());
+        }
+        let len = self.len;
+        self.len = 0;
+        self.inner.write_all(&self.buffer[..len])
+    }
+}
+
+impl<W: CoreWrite, const N: usize> CoreWrite for CoalescingWriter<W, N> {
+    type Error = W::Error;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.write_all(&[val])
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.write_buffer()?;
+        self.inner.flush()
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        if self.len + val.len() > N {
+            self.write_buffer()?;
+        }
+        if val.len() >= N {
+            return self.inner.write_all(val);
+        }
+        self.buffer[self.len..self.len + val.len()].copy_from_slice(val);
+        self.len += val.len();
+        Ok(())
+    }
+}
+
+impl<W: CoreWrite, const N: usize> CoreWrite for &'_ mut CoalescingWriter<W, N> {
+    type Error = W::Error;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (**self).write(val)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (**self).flush()
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(val)
+    }
+}
+
+/// A [CoreWrite] implementation that writes to a backing `&mut [u8]` at a position that can be
+/// moved, like `std::io::Cursor`.
+///
+/// Writes start at [Cursor::position] and move it forward. Moving the position back with
+/// [Cursor::seek] allows overwriting bytes that are already written, e.g. a header that depends on
+/// the body. [BufferWriter] is a [Cursor] that only writes forward.
+///
+/// ```
+/// # use bincode_embedded::{serialize, Cursor};
+/// let mut buffer = [0u8; 16];
+/// let mut cursor = Cursor::new(&mut buffer);
+/// serialize::<_, _, byteorder::NetworkEndian>(&(0u8, "body"), &mut cursor).unwrap();
+/// let end = cursor.position();
+/// cursor.seek(0).unwrap();
+/// serialize::<_, _, byteorder::NetworkEndian>(&(end as u8 - 1), &mut cursor).unwrap();
+/// cursor.seek(end).unwrap();
+///
+/// assert_eq!(&[6, 0, 4, b'b', b'o', b'd', b'y'], cursor.written_buffer());
+/// ```
+pub struct Cursor<'a> {
+    buffer: &'a mut [u8],
+    position: usize,
+    len: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// Create a new cursor at the start of `buffer`.
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            buffer,
+            position: 0,
+            len: 0,
+        }
+    }
+
+    /// The offset in the buffer where the next byte is written.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Move the position to `position`. The position can be moved anywhere within
+    /// [Cursor::written_len], otherwise this returns [BufferWriterError::OutOfBounds].
+    pub fn seek(&mut self, position: usize) -> Result<(), BufferWriterError> {
+        if position > self.len {
+            return Err(BufferWriterError::OutOfBounds);
+        }
+        self.position = position;
+        Ok(())
+    }
+
+    /// The amount of bytes that are written to the buffer. This is the end of the furthest write,
+    /// which does not change when bytes are overwritten after a [Cursor::seek].
+    pub fn written_len(&self) -> usize {
+        self.len
+    }
+
+    /// The bytes that are written to the buffer, see [Cursor::written_len].
+    pub fn written_buffer(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    /// The whole backing buffer, including the bytes that are not written yet.
+    pub fn get_ref(&self) -> &[u8] {
+        self.buffer
+    }
+
+    /// The whole backing buffer, including the bytes that are not written yet.
+    ///
+    /// Changing the bytes does not change [Cursor::position] or [Cursor::written_len].
+    pub fn get_mut(&mut self) -> &mut [u8] {
+        self.buffer
+    }
+
+    /// Get the backing buffer back.
+    pub fn into_inner(self) -> &'a mut [u8] {
+        self.buffer
+    }
+
+    /// Move the position `n` bytes forward, over bytes that are written to the buffer directly.
+    pub(crate) fn advance(&mut self, n: usize) -> Result<(), BufferWriterError> {
+        let end = self
+            .position
+            .checked_add(n)
+            .filter(|end| *end <= self.buffer.len())
+            .ok_or(BufferWriterError::BufferTooSmall)?;
+        self.position = end;
+        self.len = self.len.max(end);
+        Ok(())
+    }
+}
+
+impl CoreWrite for Cursor<'_> {
+    type Error = BufferWriterError;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.write_all(&[val])
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        let start = self.position;
+        let end = start
+            .checked_add(val.len())
+            .filter(|end| *end <= self.buffer.len())
+            .ok_or(BufferWriterError::BufferTooSmall)?;
+        self.buffer[start..end].copy_from_slice(val);
+        self.position = end;
+        self.len = self.len.max(end);
+        Ok(())
+    }
+}
+
+impl CoreWrite for &'_ mut Cursor<'_> {
+    type Error = BufferWriterError;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (**self).write(val)
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(val)
+    }
+}
+
+/// A [CoreWrite] implementation that owns a fixed-size buffer.
+///
+/// Unlike [BufferWriter], this does not borrow its buffer, so it can be created, stored and
+/// returned without tracking a lifetime. This suits stack-local serialization:
+///
+/// ```
+/// # use bincode_embedded::{serialize, ArrayWriter};
+/// let mut writer = ArrayWriter::<16>::new();
+/// serialize::<_, _, byteorder::NetworkEndian>(&(1u8, 2u16), &mut writer).unwrap();
+/// assert_eq!(&[1, 0, 2], writer.as_slice());
+/// ```
+///
+/// Writes that do not fit in the `N` bytes result in [BufferWriterError::BufferTooSmall].
+#[derive(Debug, Clone)]
+pub struct ArrayWriter<const N: usize> {
+    buffer: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> ArrayWriter<N> {
+    /// Create a new, empty writer.
+    pub const fn new() -> Self {
+        Self {
+            buffer: [0; N],
+            len: 0,
+        }
+    }
+
+    /// The bytes that are written.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    /// The amount of bytes that are written.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if nothing is written.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove the written bytes, so the writer can be reused for the next value.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for ArrayWriter<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> CoreWrite for ArrayWriter<N> {
+    type Error = BufferWriterError;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.write_all(&[val])
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        let end = self
+            .len
+            .checked_add(val.len())
+            .filter(|end| *end <= N)
+            .ok_or(BufferWriterError::BufferTooSmall)?;
+        self.buffer[self.len..end].copy_from_slice(val);
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl<const N: usize> CoreWrite for &'_ mut ArrayWriter<N> {
+    type Error = BufferWriterError;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (**self).write(val)
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(val)
+    }
+}
+
+/// A buffer that is written to as a [CoreWrite], and then read back as a [CoreRead].
+///
+/// This allows serializing a value and deserializing it again with a single buffer, e.g. for
+/// self-tests on a device:
+///
+/// ```
+/// # use bincode_embedded::{deserialize, serialize, Loopback};
+/// let mut buffer = [0u8; 16];
+/// let mut loopback = Loopback::new(&mut buffer);
+/// serialize::<_, _, byteorder::NetworkEndian>(&(7u8, "hi"), &mut loopback).unwrap();
+/// loopback.reset_to_read();
+/// let value: (u8, &str) = deserialize::<_, _, byteorder::NetworkEndian>(&mut loopback).unwrap();
+/// assert_eq!((7, "hi"), value);
+/// ```
+///
+/// Borrowed values like `&str` point into the buffer, so it can not be written again after
+/// [Loopback::reset_to_read]. Writes after that result in [BufferWriterError::BufferTooSmall],
+/// and reads before that find no bytes.
+pub struct Loopback<'a> {
+    buffer: &'a mut [u8],
+    written: usize,
+    reader: &'a [u8],
+}
+
+impl<'a> Loopback<'a> {
+    /// Create a new loopback that writes to `buffer`.
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            buffer,
+            written: 0,
+            reader: &[],
+        }
+    }
+
+    /// The amount of bytes that are written.
+    pub fn written_len(&self) -> usize {
+        self.written
+    }
+
+    /// Stop writing, and read the bytes that are written from the start. Calling this again has no
+    /// effect.
+    pub fn reset_to_read(&mut self) {
+        let buffer: &'a mut [u8] = core::mem::take(&mut self.buffer);
+        let buffer: &'a [u8] = buffer;
+        if let Some(written) = buffer.get(..self.written) {
+            self.reader = written;
+        }
+    }
+}
+
+impl CoreWrite for Loopback<'_> {
+    type Error = BufferWriterError;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.write_all(&[val])
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        let target = self
+            .buffer
+            .get_mut(self.written..self.written + val.len())
+            .ok_or(BufferWriterError::BufferTooSmall)?;
+        target.copy_from_slice(val);
+        self.written += val.len();
+        Ok(())
+    }
+}
+
+impl CoreWrite for &'_ mut Loopback<'_> {
+    type Error = BufferWriterError;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (**self).write(val)
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(val)
+    }
+}
+
+impl RemainingBytes for Loopback<'_> {
+    fn remaining(&self) -> usize {
+        self.reader.len()
+    }
+}
+
+impl<'a> CoreRead<'a> for Loopback<'a> {
+    type Error = ();
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        self.reader.read()
+    }
+
+    fn read_range(&mut self, len: usize) -> Result<&'a [u8], Self::Error> {
+        self.reader.read_range(len)
+    }
+
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.reader.read_bytes_into(buf)
+    }
+
+    fn read_until(&mut self, delimiter: u8) -> Result<Option<&'a [u8]>, Self::Error> {
+        self.reader.read_until(delimiter)
+    }
+}
+
+// Values that are read through `&mut Loopback` borrow the loopback instead of the buffer, so the
+// loopback can be used again once they are dropped
+impl<'de, 'a: 'de> CoreRead<'de> for &'_ mut Loopback<'a> {
+    type Error = ();
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        (**self).read()
+    }
+
+    fn read_range(&mut self, len: usize) -> Result<&'de [u8], Self::Error> {
+        (**self).read_range(len)
+    }
+
+    fn read_bytes_into(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        (**self).read_bytes_into(buf)
+    }
+
+    fn read_until(&mut self, delimiter: u8) -> Result<Option<&'de [u8]>, Self::Error> {
+        (**self).read_until(delimiter)
+    }
+}
+
+/// A [CoreWrite] implementation that writes across multiple backing buffers, e.g. the segments of
+/// a DMA scatter-gather list.
+///
+/// The buffers are filled in order. When a buffer is full, writing continues in the next one.
+/// [BufferWriterError::BufferTooSmall] is only returned when all buffers are full.
+///
+/// The amount of buffers is often only known at runtime, and there is no allocator to keep the
+/// written length of every buffer in. These lengths are kept in `segments` instead, which the
+/// caller provides next to the buffers. Only as many buffers are used as `segments` has room for.
+///
+/// ```
+/// # use bincode_embedded::{serialize, ScatterWriter};
+/// let mut first = [0u8; 3];
+/// let mut second = [0u8; 8];
+/// let mut buffers = [&mut first[..], &mut second[..]];
+/// let mut segments = [0; 2];
+/// let mut writer = ScatterWriter::new(&mut buffers, &mut segments);
+/// serialize::<_, _, byteorder::NetworkEndian>(&"hello", &mut writer).unwrap();
+/// assert_eq!(&[3, 4], writer.segments());
+/// assert_eq!(&[0, 5, b'h'], &first);
+/// assert_eq!(b"ello", &second[..4]);
+/// ```
+pub struct ScatterWriter<'a> {
+    buffers: &'a mut [&'a mut [u8]],
+    segments: &'a mut [usize],
+    current: usize,
+}
+
+impl<'a> ScatterWriter<'a> {
+    /// Create a new writer that fills `buffers` in order, and keeps the amount of bytes that are
+    /// written to every buffer in `segments`.
+    pub fn new(buffers: &'a mut [&'a mut [u8]], segments: &'a mut [usize]) -> Self {
+        let len = buffers.len().min(segments.len());
+        let (buffers, _) = buffers.split_at_mut(len);
+        let (segments, _) = segments.split_at_mut(len);
+        for segment in segments.iter_mut() {
+            *segment = 0;
+        }
+        Self {
+            buffers,
+            segments,
+            current: 0,
+        }
+    }
+
+    /// The amount of bytes that are written to every buffer.
+    pub fn segments(&self) -> &[usize] {
+        self.segments
+    }
+
+    /// The total amount of bytes that are written.
+    pub fn written_len(&self) -> usize {
+        self.segments.iter().sum()
+    }
+}
+
+impl CoreWrite for ScatterWriter<'_> {
+    type Error = BufferWriterError;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.write_all(&[val])
+    }
+
+    fn write_all(&mut self, mut val: &[u8]) -> Result<(), Self::Error> {
+        while !val.is_empty() {
+            let buffer = self
+                .buffers
+                .get_mut(self.current)
+                .ok_or(BufferWriterError::BufferTooSmall)?;
+            let written = self.segments[self.current];
+            let len = val.len().min(buffer.len() - written);
+            buffer[written..written + len].copy_from_slice(&val[..len]);
+            self.segments[self.current] += len;
+            val = &val[len..];
+            if self.segments[self.current] == buffer.len() {
+                self.current += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CoreWrite for &'_ mut ScatterWriter<'_> {
+    type Error = BufferWriterError;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (**self).write(val)
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(val)
+    }
+}
+
+/// A [CoreWrite] implementation that writes to a backing `&mut [u8]`, and moves to a larger buffer
+/// when it is full.
+///
+/// When a write does not fit, `grow` is called with the amount of bytes that are needed. It can
+/// return a buffer of at least that size, e.g. a larger block from a memory pool, and the bytes
+/// that are written so far are copied over. When `grow` returns `None` or a buffer that is too
+/// small, the write results in [BufferWriterError::BufferTooSmall].
+///
+/// ```
+/// # use bincode_embedded::{serialize, GrowableWriter};
+/// let mut small = [0u8; 2];
+/// let mut large = [0u8; 16];
+/// let mut spare = Some(&mut large[..]);
+/// let mut writer = GrowableWriter::new(&mut small, |_needed| spare.take());
+/// serialize::<_, _, byteorder::NetworkEndian>(&"hello", &mut writer).unwrap();
+/// assert_eq!(&[0, 5, b'h', b'e', b'l', b'l', b'o'], writer.written_buffer());
+/// ```
+pub struct GrowableWriter<'a, F: FnMut(usize) -> Option<&'a mut [u8]>> {
+    buffer: &'a mut [u8],
+    len: usize,
+    grow: F,
+}
+
+impl<'a, F: FnMut(usize) -> Option<&'a mut [u8]>> GrowableWriter<'a, F> {
+    /// Create a new writer that starts in `buffer`, and calls `grow` when it is full.
+    pub fn new(buffer: &'a mut [u8], grow: F) -> Self {
+        Self {
+            buffer,
+            len: 0,
+            grow,
+        }
+    }
+
+    /// The amount of bytes that are written.
+    pub fn written_len(&self) -> usize {
+        self.len
+    }
+
+    /// The bytes that are written, in the current buffer.
+    pub fn written_buffer(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    /// Get the current buffer back.
+    pub fn into_inner(self) -> &'a mut [u8] {
+        self.buffer
+    }
+}
+
+impl<'a, F: FnMut(usize) -> Option<&'a mut [u8]>> CoreWrite for GrowableWriter<'a, F> {
+    type Error = BufferWriterError;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.write_all(&[val])
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        let end = self
+            .len
+            .checked_add(val.len())
+            .ok_or(BufferWriterError::BufferTooSmall)?;
+        if end > self.buffer.len() {
+            let buffer = (self.grow)(end)
+                .filter(|buffer| buffer.len() >= end)
+                .ok_or(BufferWriterError::BufferTooSmall)?;
+            buffer[..self.len].copy_from_slice(&self.buffer[..self.len]);
+            self.buffer = buffer;
+        }
+        self.buffer[self.len..end].copy_from_slice(val);
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl<'a, F: FnMut(usize) -> Option<&'a mut [u8]>> CoreWrite for &'_ mut GrowableWriter<'a, F> {
+    type Error = BufferWriterError;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (**self).write(val)
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(val)
+    }
+}
+
+/// A [CoreWrite] adapter that XORs every byte with a repeating key before it is passed to the
+/// inner writer. See [XorReader] for the read side, which must use the same key.
+///
+/// **This is obfuscation, not encryption.** A repeating XOR key is trivially recovered from a few
+/// known bytes, so it must not be used to protect secrets or to authenticate frames.
+///
+/// ```
+/// # use bincode_embedded::{serialize, BufferWriter, XorWriter};
+/// let mut buffer = [0u8; 8];
+/// let mut writer = BufferWriter::new(&mut buffer);
+/// serialize::<_, _, byteorder::NetworkEndian>(&"hi", XorWriter::new(&mut writer, [0x5A, 0xA5]))
+///     .unwrap();
+/// assert_eq!(&[0x5A, 0xA7, 0x32, 0xCC], writer.written_buffer());
+/// ```
+pub struct XorWriter<W: CoreWrite, const N: usize> {
+    inner: W,
+    key: [u8; N],
+    position: usize,
+}
+
+impl<W: CoreWrite, const N: usize> XorWriter<W, N> {
+    /// Create a new writer that XORs every byte with `key` before it is written to `inner`.
+    pub fn new(inner: W, key: [u8; N]) -> Self {
+        Self {
+            inner,
+            key,
+            position: 0,
+        }
+    }
+
+    /// Get the inner writer back.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// XOR `val` with the key, and advance the position in the key.
+    fn apply(&mut self, val: u8) -> u8 {
+        if N == 0 {
+            return val;
+        }
+        let key = self.key[self.position % N];
+        self.position = self.position.wrapping_add(1);
+        val ^ key
+    }
+}
+
+impl<W: CoreWrite, const N: usize> CoreWrite for XorWriter<W, N> {
+    type Error = W::Error;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        let val = self.apply(val);
+        self.inner.write(val)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        // Encrypt in chunks, so the inner writer still gets a single call for most values
+        let mut chunk = [0u8; 32];
+        for part in val.chunks(chunk.len()) {
+            for (out, byte) in chunk.iter_mut().zip(part) {
+                *out = self.apply(*byte);
+            }
+            self.inner.write_all(&chunk[..part.len()])?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: CoreWrite, const N: usize> CoreWrite for &'_ mut XorWriter<W, N> {
+    type Error = W::Error;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (**self).write(val)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (**self).flush()
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_all(val)
+    }
+}
+
+/// A [CoreWrite] adapter that reports every write to a closure, for debugging how a frame is
+/// built.
+///
+/// The closure is called with the name of the [CoreWrite] method and the amount of bytes that is
+/// written, before the write is forwarded to the inner writer. A `flush` is reported with a length
+/// of `0`. See [TracingReader] for the read side.
+///
+/// ```
+/// # use bincode_embedded::{serialize, NullWriter, TracingWriter};
+/// let mut trace = Vec::new();
+/// let writer = TracingWriter::new(NullWriter, |op: &str, len| trace.push((op.to_owned(), len)));
+/// serialize::<_, _, byteorder::NetworkEndian>(&(5u16, 1u8), writer).unwrap();
+/// assert_eq!(vec![("write_all".to_owned(), 2), ("write_all".to_owned(), 1)], trace);
+/// ```
+pub struct TracingWriter<W: CoreWrite, F: FnMut(&str, usize)> {
+    inner: W,
+    f: F,
+}
+
+impl<W: CoreWrite, F: FnMut(&str, usize)> TracingWriter<W, F> {
+    /// Create a new writer that reports every write to `inner` to `f`.
+    pub fn new(inner: W, f: F) -> Self {
+        Self { inner, f }
+    }
+
+    /// Get the inner writer back.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: CoreWrite, F: FnMut(&str, usize)> CoreWrite for TracingWriter<W, F> {
+    type Error = W::Error;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (self.f)("write", 1);
+        self.inner.write(val)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (self.f)("flush", 0);
+        self.inner.flush()
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (self.f)("write_all", val.len());
+        self.inner.write_all(val)
+    }
+}
+
+/// A [CoreWrite] adapter that passes every written chunk of bytes to a closure, see
+/// [serialize_with].
+pub struct FnWriter<F, E> {
+    f: F,
+    pd: core::marker::PhantomData<E>,
+}
+
+impl<F: FnMut(&[u8]) -> Result<(), E>, E: core::fmt::Debug> FnWriter<F, E> {
+    /// Create a new writer that calls `f` with every written chunk of bytes.
+    pub fn new(f: F) -> Self {
+        Self {
+            f,
+            pd: core::marker::PhantomData,
+        }
+    }
+
+    /// Get the closure back.
+    pub fn into_inner(self) -> F {
+        self.f
+    }
+}
+
+impl<F: FnMut(&[u8]) -> Result<(), E>, E: core::fmt::Debug> CoreWrite for FnWriter<F, E> {
+    type Error = E;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        (self.f)(&[val])
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        (self.f)(val)
+    }
+}
+
+/// A [CoreWrite] adapter that writes every byte as escaped ASCII text to a [core::fmt::Write], e.g.
+/// a debug console that only accepts UTF-8.
+///
+/// Printable ASCII characters are written as-is, other bytes are escaped with
+/// [core::ascii::escape_default]. See [Base64Writer] for a more compact encoding.
+///
+/// ```
+/// # use bincode_embedded::{serialize, FmtWriter};
+/// let mut text = String::new();
+/// serialize::<_, _, byteorder::NetworkEndian>(&"hi", FmtWriter::new(&mut text)).unwrap();
+/// assert_eq!("\\x00\\x02hi", text);
+/// ```
+pub struct FmtWriter<W: core::fmt::Write> {
+    inner: W,
+}
+
+impl<W: core::fmt::Write> FmtWriter<W> {
+    /// Create a new writer that writes escaped text to `inner`.
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    /// Get the inner writer back.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: core::fmt::Write> CoreWrite for FmtWriter<W> {
+    type Error = core::fmt::Error;
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        for c in core::ascii::escape_default(val) {
+            self.inner.write_char(c as char)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use bincode_embedded::*;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+
+/// Serialize `value`, check that it takes `N * element_size` bytes without a length prefix, and
+/// deserialize it again.
+fn roundtrip<T, const N: usize>(value: [T; N], element_size: usize)
+where
+    [T; N]: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let mut buffer = [0u8; 512];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(N * element_size, writer.written_len());
+
+    // Append a byte to check that exactly `N` elements are read
+    let len = writer.written_len();
+    buffer[len] = 0xAA;
+    let (deserialized, reader): ([T; N], _) =
+        deserialize_and_return::<_, _, byteorder::NetworkEndian>(&buffer[..=len]).unwrap();
+    assert_eq!(value, deserialized);
+    assert_eq!(&[0xAA], reader);
+}
+
+#[test]
+fn empty() {
+    roundtrip::<u32, 0>([], 4);
+    roundtrip::<u8, 0>([], 1);
+
+    // An empty array reads nothing, not even from an empty reader
+    let (value, reader): ([u32; 0], _) =
+        deserialize_and_return::<_, _, byteorder::NetworkEndian>(&[][..]).unwrap();
+    assert_eq!([0u32; 0], value);
+    assert_eq!(0, reader.remaining());
+}
+
+#[test]
+fn integers() {
+    roundtrip([7u8], 1);
+    roundtrip([1u16, 2, 3, 4], 2);
+    roundtrip([-1i32; 7], 4);
+    roundtrip([u64::MAX; 16], 8);
+    roundtrip([i128::MIN; 3], 16);
+}
+
+#[test]
+fn largest() {
+    // serde implements arrays up to 32 elements
+    let mut value = [0u32; 32];
+    for (i, v) in value.iter_mut().enumerate() {
+        *v = i as u32 * 1_000_000;
+    }
+    roundtrip(value, 4);
+    roundtrip([0.5f64; 32], 8);
+    roundtrip([true; 32], 1);
+}
+
+#[test]
+fn nested() {
+    roundtrip([[1u16, 2], [3, 4], [5, 6]], 4);
+    roundtrip([[0u8; 0]; 5], 0);
+    roundtrip([(1u8, 'a'), (2, 'b')], 2);
+}
+
+#[test]
+fn truncated() {
+    let buffer = [0u8, 1, 0, 2, 0];
+    let result = deserialize::<[u16; 3], _, byteorder::NetworkEndian>(&buffer[..]);
+    assert!(matches!(result, Err(DeserializeError::Read(()))));
+}
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize, PartialEq, Debug)]
+struct Image {
+    version: u16,
+    #[serde(with = "bincode_embedded::byte_array")]
+    data: [u8; 1024],
+    crc: u32,
+}
+
+#[test]
+fn byte_array() {
+    let mut image = Image {
+        version: 3,
+        data: [0; 1024],
+        crc: 0xDEAD_BEEF,
+    };
+    for (i, byte) in image.data.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    let mut buffer = [0u8; 1100];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&image, &mut writer).unwrap();
+    assert_eq!(2 + 1024 + 4, writer.written_len());
+    let bytes = writer.written_buffer();
+    assert_eq!(&image.data[..], &bytes[2..1026]);
+
+    // The array is read at once, and matches reading every byte on its own
+    let mut trace = Vec::new();
+    let reader = TracingReader::new(bytes, |op: &str, len| trace.push((op.to_owned(), len)));
+    let deserialized: Image = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+    assert_eq!(image, deserialized);
+    assert_eq!(("read_transient".to_owned(), 1024), trace[1]);
+    assert_eq!(3, trace.len());
+
+    let mut deserializer = Deserializer::<_, byteorder::NetworkEndian>::new(bytes, Config::new());
+    let _version = <u16 as serde::Deserialize>::deserialize(&mut deserializer).unwrap();
+    let mut data = [0u8; 1024];
+    for byte in data.iter_mut() {
+        *byte = serde::Deserialize::deserialize(&mut deserializer).unwrap();
+    }
+    assert_eq!(image.data, data);
+}
+
+#[test]
+fn byte_array_truncated() {
+    #[derive(serde_derive::Deserialize, Debug)]
+    struct Small {
+        #[serde(with = "bincode_embedded::byte_array")]
+        _data: [u8; 4],
+    }
+
+    let result = deserialize::<Small, _, byteorder::NetworkEndian>(&[1, 2, 3][..]);
+    assert!(matches!(result, Err(DeserializeError::Read(()))));
+}
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize, PartialEq, Debug)]
+struct Key<'a> {
+    id: u8,
+    #[serde(borrow)]
+    key: Bytes<'a, 16>,
+    flags: u8,
+}
+
+#[test]
+fn borrowed_bytes() {
+    let key: [u8; 16] = *b"0123456789abcdef";
+    let value = Key {
+        id: 1,
+        key: Bytes::new(&key),
+        flags: 0x80,
+    };
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(18, writer.written_len());
+    let len = writer.written_len();
+
+    let deserialized: Key = from_bytes::<_, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+    assert_eq!(value, deserialized);
+    // The key points into the buffer instead of being copied
+    assert_eq!(buffer[1..].as_ptr(), deserialized.key.as_ptr());
+
+    // A truncated key is a read error
+    let result = from_bytes::<Key, byteorder::NetworkEndian>(&buffer[..10]);
+    assert!(matches!(result, Err(DeserializeError::Read(()))));
+}
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize, PartialEq, Debug)]
+struct Point {
+    x: i16,
+    y: i16,
+}
+
+#[test]
+fn slice_of_structs() {
+    let points = [Point { x: 1, y: -1 }, Point { x: 2, y: -2 }];
+    let slice: &[Point] = &points;
+    let mut buffer = [0u8; 16];
+    let len = to_bytes::<_, byteorder::NetworkEndian>(&slice, &mut buffer).unwrap();
+    assert_eq!(&[0, 2, 0, 1, 0xFF, 0xFF, 0, 2, 0xFF, 0xFE], &buffer[..len]);
+
+    // A slice has a length prefix, which an array does not
+    let (count, deserialized): (u16, [Point; 2]) =
+        from_bytes::<_, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+    assert_eq!(2, count);
+    assert_eq!(points, deserialized);
+    let mut array_buffer = [0u8; 16];
+    let array_len = to_bytes::<_, byteorder::NetworkEndian>(&points, &mut array_buffer).unwrap();
+    assert_eq!(&buffer[2..len], &array_buffer[..array_len]);
+
+    let deserialized: Vec<Point> =
+        from_bytes::<_, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+    assert_eq!(&points[..], &deserialized[..]);
+
+    let empty: &[Point] = &[];
+    let len = to_bytes::<_, byteorder::NetworkEndian>(&empty, &mut buffer).unwrap();
+    assert_eq!(&[0, 0], &buffer[..len]);
+}
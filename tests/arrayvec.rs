@@ -0,0 +1,43 @@
+#![cfg(feature = "arrayvec")]
+
+#[macro_use]
+extern crate serde_derive;
+
+use arrayvec::ArrayVec;
+use bincode_embedded::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Frame<'a> {
+    id: u16,
+    name: &'a str,
+    data: [u8; 4],
+}
+
+#[test]
+fn arrayvec_roundtrip() {
+    let frame = Frame {
+        id: 7,
+        name: "arrayvec",
+        data: [1, 2, 3, 4],
+    };
+
+    let mut vec = ArrayVec::<u8, 64>::new();
+    serialize::<_, _, byteorder::NetworkEndian>(&frame, &mut vec).unwrap();
+    assert_eq!(2 + 2 + 8 + 4, vec.len());
+
+    let deserialized: Frame = deserialize::<_, _, byteorder::NetworkEndian>(&vec[..]).unwrap();
+    assert_eq!(frame, deserialized);
+}
+
+#[test]
+fn arrayvec_full() {
+    let frame = Frame {
+        id: 7,
+        name: "arrayvec",
+        data: [1, 2, 3, 4],
+    };
+
+    let mut vec = ArrayVec::<u8, 8>::new();
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&frame, &mut vec);
+    assert!(matches!(result, Err(SerializeError::Write(_))));
+}
@@ -0,0 +1,89 @@
+#[macro_use]
+extern crate serde_derive;
+
+use base64::Engine;
+use bincode_embedded::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Frame<'a> {
+    id: u16,
+    name: &'a str,
+}
+
+fn serialized(value: &Frame) -> Vec<u8> {
+    let mut buffer = [0u8; 64];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(value, &mut writer).unwrap();
+    writer.written_buffer().to_vec()
+}
+
+fn encoded(value: &Frame) -> Vec<u8> {
+    let mut buffer = [0u8; 128];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let mut base64 = Base64Writer::new(&mut writer);
+    serialize::<_, _, byteorder::NetworkEndian>(value, &mut base64).unwrap();
+    base64.finish().unwrap();
+    writer.written_buffer().to_vec()
+}
+
+#[test]
+fn base64_writer() {
+    // Serialized lengths of 6, 7 and 8 bytes cover every amount of padding
+    for name in ["ab", "abc", "abcd"] {
+        let value = Frame { id: 0x0102, name };
+        let expected = base64::engine::general_purpose::STANDARD.encode(serialized(&value));
+        assert_eq!(expected.as_bytes(), encoded(&value).as_slice());
+    }
+}
+
+#[test]
+fn fmt_writer() {
+    let mut text = String::new();
+    let value = Frame {
+        id: 0x4142,
+        name: "a\n",
+    };
+    serialize::<_, _, byteorder::NetworkEndian>(&value, FmtWriter::new(&mut text)).unwrap();
+    assert_eq!("AB\\x00\\x02a\\n", text);
+}
+
+#[test]
+fn base64_reader() {
+    for name in ["ab", "abc", "abcd"] {
+        let value = Frame { id: 0x0102, name };
+        let input = encoded(&value);
+
+        let mut scratch = [0u8; 64];
+        let reader = Base64Reader::new(&input, &mut scratch).unwrap();
+        let deserialized: Frame = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+        assert_eq!(value, deserialized);
+    }
+}
+
+#[test]
+fn base64_reader_errors() {
+    let mut scratch = [0u8; 8];
+    assert!(matches!(
+        Base64Reader::new(b"AAJo!Q==", &mut scratch),
+        Err(Base64Error::InvalidCharacter(b'!'))
+    ));
+    assert!(matches!(
+        Base64Reader::new(b"AA==AAJo", &mut scratch),
+        Err(Base64Error::InvalidCharacter(b'='))
+    ));
+    assert!(matches!(
+        Base64Reader::new(b"AAJoa", &mut scratch),
+        Err(Base64Error::InvalidLength)
+    ));
+    assert!(matches!(
+        Base64Reader::new(b"AAJoaQ==", &mut scratch[..3]),
+        Err(Base64Error::BufferTooSmall)
+    ));
+
+    let reader = Base64Reader::new(b"AAJoaQ==", &mut scratch).unwrap();
+    assert_eq!(&[0, 2, b'h', b'i'], reader.remaining_buffer());
+    match deserialize::<(u16, u32), _, byteorder::NetworkEndian>(reader) {
+        Err(DeserializeError::Read(Base64Error::UnexpectedEnd)) => {}
+        other => panic!("Expected UnexpectedEnd, got {:?}", other),
+    }
+}
@@ -0,0 +1,202 @@
+//! Compare the output of this crate with upstream bincode, configured with fixed-size integers and
+//! big-endian byte order.
+
+#[macro_use]
+extern crate serde_derive;
+
+use bincode::Options;
+use bincode_embedded::*;
+
+fn bincode_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_big_endian()
+}
+
+fn embedded_bytes<T: serde::Serialize>(value: &T) -> Vec<u8> {
+    let mut buffer = [0u8; 256];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(value, &mut writer).unwrap();
+    writer.written_buffer().to_vec()
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Unit;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Newtype(u16);
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Telemetry {
+    a: u8,
+    b: u16,
+    c: u32,
+    d: u64,
+    e: u128,
+    f: i8,
+    g: i16,
+    h: i32,
+    i: i64,
+    j: i128,
+    k: f32,
+    l: f64,
+    m: bool,
+    n: Option<u16>,
+    o: Option<Newtype>,
+    p: [u8; 4],
+    q: (char, char, char),
+    r: Unit,
+}
+
+const TELEMETRY: Telemetry = Telemetry {
+    a: 0xAB,
+    b: 0x0102,
+    c: 0x0304_0506,
+    d: u64::MAX - 1,
+    e: 1 << 100,
+    f: -1,
+    g: -300,
+    h: i32::MIN,
+    i: -5,
+    j: i128::MIN + 1,
+    k: 1.5,
+    l: -0.25,
+    m: true,
+    n: None,
+    o: Some(Newtype(7)),
+    p: [1, 2, 3, 4],
+    q: ('a', 'é', '😀'),
+    r: Unit,
+};
+
+#[test]
+fn serialize_matches_bincode() {
+    let expected = bincode_options().serialize(&TELEMETRY).unwrap();
+    assert_eq!(expected, embedded_bytes(&TELEMETRY));
+}
+
+#[test]
+fn deserialize_bincode_output() {
+    let bytes = bincode_options().serialize(&TELEMETRY).unwrap();
+    let deserialized: Telemetry =
+        deserialize::<_, _, byteorder::NetworkEndian>(&bytes[..]).unwrap();
+    assert_eq!(TELEMETRY, deserialized);
+
+    let bytes = embedded_bytes(&TELEMETRY);
+    let deserialized: Telemetry = bincode_options().deserialize(&bytes).unwrap();
+    assert_eq!(TELEMETRY, deserialized);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum Command {
+    Stop,
+    Move(i16, i16),
+    Set { id: u8, value: u32 },
+}
+
+/// These are documented as incompatibilities in the crate documentation.
+#[test]
+fn known_incompatibilities() {
+    // Strings, slices and sequences have a `u16` length instead of a `u64`
+    assert_eq!(vec![0, 3, b'a', b'b', b'c'], embedded_bytes(&"abc"));
+    assert_eq!(
+        vec![0, 0, 0, 0, 0, 0, 0, 3, b'a', b'b', b'c'],
+        bincode_options().serialize(&"abc").unwrap()
+    );
+
+    // Enum variants are a `u8` instead of a `u32`
+    assert_eq!(vec![0], embedded_bytes(&Command::Stop));
+    assert_eq!(
+        vec![0, 0, 0, 0],
+        bincode_options().serialize(&Command::Stop).unwrap()
+    );
+}
+
+#[test]
+fn variant_width_u32() {
+    let config = Config::new().variant_width(VariantWidth::U32);
+    for command in [
+        Command::Stop,
+        Command::Move(-1, 2),
+        Command::Set { id: 3, value: 4 },
+    ] {
+        let mut buffer = [0u8; 16];
+        let mut writer = BufferWriter::new(&mut buffer);
+        serialize_with_config::<_, _, byteorder::NetworkEndian>(&command, &mut writer, config)
+            .unwrap();
+        let expected = bincode_options().serialize(&command).unwrap();
+        assert_eq!(&expected[..], writer.written_buffer());
+
+        let deserialized: Command =
+            deserialize_with_config::<_, _, byteorder::NetworkEndian>(&expected[..], config)
+                .unwrap();
+        assert_eq!(command, deserialized);
+    }
+}
+
+#[test]
+fn length_width_u64() {
+    let config = Config::new().length_width(LengthWidth::U64);
+    let values = vec![1u32, 0x0203_0405];
+    let name = String::from("node");
+
+    // Produced by `bincode_options().serialize(...)`
+    let values_fixture = [0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 1, 2, 3, 4, 5];
+    let name_fixture = [0, 0, 0, 0, 0, 0, 0, 4, b'n', b'o', b'd', b'e'];
+    assert_eq!(
+        &values_fixture[..],
+        &bincode_options().serialize(&values).unwrap()[..]
+    );
+    assert_eq!(
+        &name_fixture[..],
+        &bincode_options().serialize(&name).unwrap()[..]
+    );
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&values, &mut writer, config).unwrap();
+    assert_eq!(&values_fixture[..], writer.written_buffer());
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&name, &mut writer, config).unwrap();
+    assert_eq!(&name_fixture[..], writer.written_buffer());
+
+    let deserialized: Vec<u32> =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&values_fixture[..], config)
+            .unwrap();
+    assert_eq!(values, deserialized);
+    let deserialized: String =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&name_fixture[..], config)
+            .unwrap();
+    assert_eq!(name, deserialized);
+}
+
+#[test]
+fn length_width_u64_collections() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Log<'a> {
+        source: &'a str,
+        #[serde(with = "serde_bytes")]
+        payload: &'a [u8],
+        entries: Vec<(u8, Command)>,
+        tags: std::collections::BTreeMap<u8, bool>,
+    }
+
+    let config = Config::new()
+        .length_width(LengthWidth::U64)
+        .variant_width(VariantWidth::U32);
+    let log = Log {
+        source: "uart",
+        payload: &[1, 2, 3],
+        entries: vec![(1, Command::Stop), (2, Command::Set { id: 3, value: 4 })],
+        tags: [(1, true), (2, false)].iter().copied().collect(),
+    };
+    let expected = bincode_options().serialize(&log).unwrap();
+    let mut buffer = [0u8; 128];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&log, &mut writer, config).unwrap();
+    assert_eq!(&expected[..], writer.written_buffer());
+
+    let deserialized: Log =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&expected[..], config).unwrap();
+    assert_eq!(log, deserialized);
+}
@@ -0,0 +1,110 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Named<'a> {
+    id: u8,
+    #[serde(borrow)]
+    name: BoundedStr<'a, 8>,
+}
+
+#[test]
+fn bounded_str() {
+    let value = Named {
+        id: 3,
+        name: BoundedStr::new("sensor").unwrap(),
+    };
+
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    // Encoded the same as a `&str`
+    assert_eq!(b"\x03\x00\x06sensor", writer.written_buffer());
+    let len = writer.written_len();
+
+    let deserialized: Named =
+        deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+    assert_eq!(value, deserialized);
+    assert_eq!("sensor", &*deserialized.name);
+
+    assert!(BoundedStr::<8>::new("too long!").is_none());
+}
+
+/// A reader that fails the test if a string is read from it.
+struct NoRangeReads<'a>(&'a [u8]);
+
+impl<'a> CoreRead<'a> for NoRangeReads<'a> {
+    type Error = ();
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        let (first, rest) = self.0.split_first().ok_or(())?;
+        self.0 = rest;
+        Ok(*first)
+    }
+
+    fn read_range(&mut self, _len: usize) -> Result<&'a [u8], Self::Error> {
+        panic!("The payload should not be read")
+    }
+}
+
+#[test]
+fn bounded_str_too_long() {
+    let buffer = [3, 0xFF, 0xFF, b'a', b'b'];
+    let result = deserialize::<Named, _, byteorder::NetworkEndian>(NoRangeReads(&buffer));
+    match result {
+        Err(DeserializeError::StringTooLong {
+            len: 0xFFFF,
+            max: 8,
+        }) => {}
+        other => panic!("Expected StringTooLong, got {:?}", other),
+    }
+}
+
+#[test]
+fn bounded_chars() {
+    fn read<const MAX: usize>(
+        buffer: &[u8],
+    ) -> Result<BoundedChars<'_, MAX>, DeserializeError<'_, &[u8]>> {
+        deserialize::<_, _, byteorder::NetworkEndian>(buffer)
+    }
+
+    // ASCII has a byte per character
+    assert_eq!("abcd", read::<4>(b"\x00\x04abcd").unwrap().as_str());
+    assert!(matches!(
+        read::<3>(b"\x00\x04abcd"),
+        Err(DeserializeError::TooManyChars { count: 4, max: 3 })
+    ));
+
+    // 4 characters in 10 bytes, which is more than the limit in bytes
+    let text = "añ€😀";
+    let mut buffer = [0u8; 16];
+    let len = to_bytes::<_, byteorder::NetworkEndian>(&text, &mut buffer).unwrap();
+    assert_eq!(12, len);
+    assert_eq!(text, read::<4>(&buffer[..len]).unwrap().as_str());
+    assert!(matches!(
+        read::<3>(&buffer[..len]),
+        Err(DeserializeError::TooManyChars { count: 4, max: 3 })
+    ));
+
+    // Encoded the same as a `&str`
+    let value = BoundedChars::<4>::new(text).unwrap();
+    let mut other = [0u8; 16];
+    let other_len = to_bytes::<_, byteorder::NetworkEndian>(&value, &mut other).unwrap();
+    assert_eq!(&buffer[..len], &other[..other_len]);
+    assert!(BoundedChars::<3>::new(text).is_none());
+
+    // Strings with a terminator are counted as well
+    let config = Config::new().cstr_mode();
+    let buffer = "€€\0".as_bytes();
+    let value: BoundedChars<2> =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(buffer, config).unwrap();
+    assert_eq!("€€", value.as_str());
+    let result =
+        deserialize_with_config::<BoundedChars<1>, _, byteorder::NetworkEndian>(buffer, config);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::TooManyChars { count: 2, max: 1 })
+    ));
+}
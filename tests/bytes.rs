@@ -0,0 +1,136 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct PlainSlice<'a> {
+    before: u8,
+    data: &'a [u8],
+    after: u8,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct AnnotatedSlice<'a> {
+    before: u8,
+    #[serde(with = "serde_bytes")]
+    data: &'a [u8],
+    after: u8,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct OwnedBytes {
+    before: u8,
+    data: Vec<u8>,
+    after: u8,
+}
+
+const EXPECTED: [u8; 7] = [1, 0, 3, 10, 11, 12, 2];
+
+fn to_bytes<T: serde::Serialize>(value: &T) -> Vec<u8> {
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(value, &mut writer).unwrap();
+    writer.written_buffer().to_vec()
+}
+
+#[test]
+fn slice_in_struct() {
+    // serde serializes this as a sequence, but deserializes it as bytes
+    let value = PlainSlice {
+        before: 1,
+        data: &[10, 11, 12],
+        after: 2,
+    };
+    assert_eq!(&EXPECTED[..], &to_bytes(&value)[..]);
+
+    let deserialized: PlainSlice =
+        deserialize::<_, _, byteorder::NetworkEndian>(&EXPECTED[..]).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[test]
+fn serde_bytes_slice() {
+    let value = AnnotatedSlice {
+        before: 1,
+        data: &[10, 11, 12],
+        after: 2,
+    };
+    assert_eq!(&EXPECTED[..], &to_bytes(&value)[..]);
+
+    let deserialized: AnnotatedSlice =
+        deserialize::<_, _, byteorder::NetworkEndian>(&EXPECTED[..]).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[test]
+fn owned_bytes() {
+    let value = OwnedBytes {
+        before: 1,
+        data: vec![10, 11, 12],
+        after: 2,
+    };
+    assert_eq!(&EXPECTED[..], &to_bytes(&value)[..]);
+
+    let deserialized: OwnedBytes =
+        deserialize::<_, _, byteorder::NetworkEndian>(&EXPECTED[..]).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[test]
+fn mixed_sides() {
+    // A plain slice can be read by an annotated slice and the other way around
+    let plain = PlainSlice {
+        before: 1,
+        data: &[10, 11, 12],
+        after: 2,
+    };
+    let bytes = to_bytes(&plain);
+    let annotated: AnnotatedSlice =
+        deserialize::<_, _, byteorder::NetworkEndian>(&bytes[..]).unwrap();
+    assert_eq!(plain.data, annotated.data);
+
+    let bytes = to_bytes(&annotated);
+    let owned: OwnedBytes = deserialize::<_, _, byteorder::NetworkEndian>(&bytes[..]).unwrap();
+    assert_eq!(plain.data, &owned.data[..]);
+}
+
+#[test]
+fn i8_slice_layout() {
+    // serde has no byte path for i8, but the layout is the same as a byte slice
+    let signed: &[i8] = &[10, 11, -1];
+    let unsigned: &[u8] = &[10, 11, 0xFF];
+    let bytes = to_bytes(&signed);
+    assert_eq!(vec![0, 3, 10, 11, 0xFF], bytes);
+    assert_eq!(bytes, to_bytes(&unsigned));
+
+    let deserialized: Vec<i8> = deserialize::<_, _, byteorder::NetworkEndian>(&bytes[..]).unwrap();
+    assert_eq!(signed, &deserialized[..]);
+    let deserialized: &[u8] = deserialize::<_, _, byteorder::NetworkEndian>(&bytes[..]).unwrap();
+    assert_eq!(unsigned, deserialized);
+}
+
+#[test]
+fn only_u8_slices_are_borrowed() {
+    let bytes = [0, 3, 10, 11, 12];
+    let mut trace = Vec::new();
+    let reader = TracingReader::new(&bytes[..], |op: &str, len| trace.push((op.to_owned(), len)));
+    let _: &[u8] = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+    assert_eq!(
+        vec![("read_array".to_owned(), 2), ("read_range".to_owned(), 3)],
+        trace
+    );
+
+    let mut trace = Vec::new();
+    let reader = TracingReader::new(&bytes[..], |op: &str, len| trace.push((op.to_owned(), len)));
+    let _: Vec<i8> = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+    assert_eq!(
+        vec![
+            ("read_array".to_owned(), 2),
+            ("read".to_owned(), 1),
+            ("read".to_owned(), 1),
+            ("read".to_owned(), 1)
+        ],
+        trace
+    );
+}
@@ -0,0 +1,663 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Mixed<'a> {
+    value: u32,
+    name: &'a str,
+}
+
+#[test]
+fn mixed_endian() {
+    let value = Mixed {
+        value: 0x0102_0304,
+        name: "abc",
+    };
+    let config = Config::new()
+        .length_endian(RuntimeEndian::Big)
+        .value_endian(RuntimeEndian::Little);
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(
+        &[4, 3, 2, 1, 0, 3, b'a', b'b', b'c'],
+        writer.written_buffer()
+    );
+    let len = writer.written_len();
+
+    let deserialized: Mixed =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..len], config).unwrap();
+    assert_eq!(value, deserialized);
+
+    // Without the config, the value is read big-endian
+    let deserialized: Mixed =
+        deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+    assert_eq!(0x0403_0201, deserialized.value);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Lengths<'a> {
+    name: &'a str,
+    #[serde(with = "serde_bytes")]
+    data: &'a [u8],
+    values: Vec<u16>,
+    map: std::collections::BTreeMap<u8, u16>,
+}
+
+#[test]
+fn length_endian_only() {
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(1, 0x0102);
+    let value = Lengths {
+        name: "ab",
+        data: &[9],
+        values: vec![0x0304],
+        map,
+    };
+    // Only the lengths are big-endian, the values use the little-endian byte order of `B`
+    let config = Config::new().length_endian(RuntimeEndian::Big);
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::LittleEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(
+        &[0, 2, b'a', b'b', 0, 1, 9, 0, 1, 4, 3, 1, 1, 2, 1],
+        writer.written_buffer()
+    );
+    let len = writer.written_len();
+
+    let deserialized: Lengths =
+        deserialize_with_config::<_, _, byteorder::LittleEndian>(&buffer[..len], config).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[test]
+fn limit() {
+    let value = Mixed {
+        value: 5,
+        name: "abc",
+    };
+    let config = Config::new().limit(9);
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(9, writer.written_len());
+
+    let deserialized: Mixed =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..9], config).unwrap();
+    assert_eq!(value, deserialized);
+
+    // Corrupt the length of the string
+    buffer[4] = 0xFF;
+    buffer[5] = 0xFF;
+    let result =
+        deserialize_with_config::<Mixed, _, byteorder::NetworkEndian>(&buffer[..9], config);
+    match result {
+        Err(DeserializeError::LimitExceeded) => {}
+        other => panic!("Expected LimitExceeded, got {:?}", other),
+    }
+}
+
+#[test]
+fn read_error_offset() {
+    let value = (
+        Mixed {
+            value: 5,
+            name: "abc",
+        },
+        0x0102_0304u32,
+    );
+    let config = Config::new().read_error_offset();
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(13, writer.written_len());
+
+    // Cut the frame off before the trailing u32 and in the middle of the string
+    for (truncated, offset) in [(9, 9), (12, 9), (7, 6)].iter().copied() {
+        let result = deserialize_with_config::<(Mixed, u32), _, byteorder::NetworkEndian>(
+            &buffer[..truncated],
+            config,
+        );
+        match result {
+            Err(DeserializeError::ReadAt {
+                source: (),
+                offset: o,
+            }) if o == offset => {}
+            other => panic!("Expected ReadAt {{ offset: {} }}, got {:?}", offset, other),
+        }
+    }
+
+    // The offset is only reported when the option is enabled
+    let result = deserialize::<(Mixed, u32), _, byteorder::NetworkEndian>(&buffer[..9]);
+    assert!(matches!(result, Err(DeserializeError::Read(()))));
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Framed<'a> {
+    id: u8,
+    #[serde(borrow)]
+    body: Mixed<'a>,
+    crc: u16,
+}
+
+#[test]
+fn read_error_field() {
+    let value = Framed {
+        id: 1,
+        body: Mixed {
+            value: 5,
+            name: "abc",
+        },
+        crc: 0x0102,
+    };
+    let config = Config::new().read_error_field();
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(12, writer.written_len());
+
+    // Cut the frame off between and inside fields, nested fields report the innermost name
+    let cases = [
+        (0, "id", 0),
+        (3, "value", 1),
+        (5, "name", 5),
+        (9, "name", 7),
+        (10, "crc", 10),
+    ];
+    for (truncated, field, offset) in cases.iter().copied() {
+        let result = deserialize_with_config::<Framed, _, byteorder::NetworkEndian>(
+            &buffer[..truncated],
+            config,
+        );
+        match result {
+            Err(DeserializeError::ReadInField {
+                source: (),
+                field: f,
+                offset: o,
+            }) if f == field && o == offset => {}
+            other => panic!(
+                "Expected ReadInField {{ field: {}, offset: {} }}, got {:?}",
+                field, offset, other
+            ),
+        }
+    }
+
+    // Values outside of a struct are not named
+    let result = deserialize_with_config::<(Mixed, u32), _, byteorder::NetworkEndian>(
+        &buffer[1..11],
+        config,
+    );
+    assert!(matches!(result, Err(DeserializeError::Read(()))));
+
+    // The field is only reported when the option is enabled
+    let result = deserialize::<Framed, _, byteorder::NetworkEndian>(&buffer[..5]);
+    assert!(matches!(result, Err(DeserializeError::Read(()))));
+}
+
+#[test]
+fn runtime_endian() {
+    let value = (0x0102u16, -2i128, "abc");
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_endian(&value, &mut writer, RuntimeEndian::Little).unwrap();
+    let len = writer.written_len();
+    assert_eq!(&[0x02, 0x01], &buffer[..2]);
+    assert_eq!(&[3, 0], &buffer[18..20]);
+
+    // The same as a little-endian type
+    let mut typed_buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut typed_buffer);
+    serialize::<_, _, byteorder::LittleEndian>(&value, &mut writer).unwrap();
+    assert_eq!(&buffer[..len], writer.written_buffer());
+
+    let deserialized: (u16, i128, &str) =
+        deserialize_with_endian(&buffer[..len], RuntimeEndian::Little).unwrap();
+    assert_eq!(value, deserialized);
+
+    // Reading with the wrong byte order gives different values
+    let wrong: (u16, i128) = deserialize_with_endian(&buffer[..18], RuntimeEndian::Big).unwrap();
+    assert_eq!(0x0201, wrong.0);
+    assert_ne!(value.1, wrong.1);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Flags {
+    a: Option<()>,
+    b: Option<()>,
+    c: Option<()>,
+    d: Option<()>,
+    e: Option<()>,
+}
+
+#[test]
+fn bit_pack_tags() {
+    let value = Flags {
+        a: Some(()),
+        b: None,
+        c: Some(()),
+        d: Some(()),
+        e: None,
+    };
+    let config = Config::new().bit_pack_tags();
+
+    let mut buffer = [0u8; 8];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(&[0b01101], writer.written_buffer());
+
+    let deserialized: Flags =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..1], config).unwrap();
+    assert_eq!(value, deserialized);
+
+    // Without packing every tag takes a full byte
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(&[1, 0, 1, 1, 0], writer.written_buffer());
+}
+
+#[test]
+fn bit_pack_tags_mixed() {
+    let value = (
+        true,
+        false,
+        Some(0x0102u16),
+        [true; 9],
+        None::<u8>,
+        'a',
+        false,
+    );
+    let config = Config::new().bit_pack_tags();
+
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(&[0b101, 1, 2, 0xFF, 0b01, b'a', 0], writer.written_buffer());
+    let len = writer.written_len();
+
+    let deserialized =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..len], config).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[test]
+fn cstr_mode() {
+    let value = ("abc", 7u8, "", &b"de"[..]);
+    let config = Config::new().cstr_mode();
+
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    // Byte slices still have a length prefix
+    assert_eq!(b"abc\0\x07\0\0\x02de", writer.written_buffer());
+    let len = writer.written_len();
+
+    let deserialized: (&str, u8, &str, &[u8]) =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..len], config).unwrap();
+    assert_eq!(value, deserialized);
+
+    let result =
+        deserialize_with_config::<BoundedStr<2>, _, byteorder::NetworkEndian>(&buffer[..], config);
+    match result {
+        Err(DeserializeError::StringTooLong { len: 3, max: 2 }) => {}
+        other => panic!("Expected StringTooLong, got {:?}", other),
+    }
+}
+
+#[test]
+fn cstr_mode_errors() {
+    let config = Config::new().cstr_mode();
+
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let result =
+        serialize_with_config::<_, _, byteorder::NetworkEndian>(&"a\0b", &mut writer, config);
+    match result {
+        Err(SerializeError::InteriorNul) => {}
+        other => panic!("Expected InteriorNul, got {:?}", other),
+    }
+
+    let result = deserialize_with_config::<&str, _, byteorder::NetworkEndian>(&b"abc"[..], config);
+    match result {
+        Err(DeserializeError::DelimiterNotFound) => {}
+        other => panic!("Expected DelimiterNotFound, got {:?}", other),
+    }
+}
+
+#[test]
+fn fixed_char() {
+    let config = Config::new().fixed_char();
+    let cases = [
+        ('a', [0, 0, 0, 0x61]),
+        ('€', [0, 0, 0x20, 0xAC]),
+        ('😀', [0, 0x01, 0xF6, 0x00]),
+    ];
+    for (c, expected) in cases.iter().copied() {
+        let mut buffer = [0u8; 8];
+        let mut writer = BufferWriter::new(&mut buffer);
+        serialize_with_config::<_, _, byteorder::NetworkEndian>(&c, &mut writer, config).unwrap();
+        assert_eq!(&expected, writer.written_buffer());
+
+        let deserialized: char =
+            deserialize_with_config::<_, _, byteorder::NetworkEndian>(&expected[..], config)
+                .unwrap();
+        assert_eq!(c, deserialized);
+    }
+
+    // The code point uses the byte order of values
+    let mut buffer = [0u8; 8];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::LittleEndian>(&'€', &mut writer, config).unwrap();
+    assert_eq!(&[0xAC, 0x20, 0, 0], writer.written_buffer());
+
+    // Surrogates and values above `char::MAX` are not valid code points
+    for invalid in [[0, 0, 0xD8, 0], [0, 0x11, 0, 0]].iter() {
+        let result =
+            deserialize_with_config::<char, _, byteorder::NetworkEndian>(&invalid[..], config);
+        assert!(matches!(result, Err(DeserializeError::InvalidCharEncoding)));
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct FixedField {
+    a: u32,
+    b: Le<u32>,
+    c: Be<i16>,
+    d: Le<f64>,
+}
+
+#[test]
+fn fixed_endian_fields() {
+    let value = FixedField {
+        a: 0x0102_0304,
+        b: Le(0x0506_0708),
+        c: Be(-2),
+        d: Le(1.0),
+    };
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::BigEndian>(&value, &mut writer).unwrap();
+    assert_eq!(
+        &[1, 2, 3, 4, 8, 7, 6, 5, 0xFF, 0xFE, 0, 0, 0, 0, 0, 0, 0xF0, 0x3F],
+        writer.written_buffer()
+    );
+    let len = writer.written_len();
+    let deserialized: FixedField =
+        deserialize::<_, _, byteorder::BigEndian>(&buffer[..len]).unwrap();
+    assert_eq!(value, deserialized);
+
+    // The fixed fields do not change with the byte order of the serializer
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::LittleEndian>(&value, &mut writer).unwrap();
+    assert_eq!(
+        &[4, 3, 2, 1, 8, 7, 6, 5, 0xFF, 0xFE],
+        &writer.written_buffer()[..10]
+    );
+}
+
+#[test]
+fn canonical_nan() {
+    let values = [
+        (
+            f32::from_bits(0x7FC0_0000),
+            f64::from_bits(0x7FF8_0000_0000_0000),
+        ),
+        (
+            f32::from_bits(0xFFC0_1234),
+            f64::from_bits(0xFFF0_0000_0000_0001),
+        ),
+    ];
+    let config = Config::new().canonical_nan();
+
+    let mut outputs = [[0u8; 12]; 2];
+    for (value, output) in values.iter().zip(outputs.iter_mut()) {
+        let mut writer = BufferWriter::new(output);
+        serialize_with_config::<_, _, byteorder::NetworkEndian>(value, &mut writer, config)
+            .unwrap();
+    }
+    assert_eq!(outputs[0], outputs[1]);
+    assert_eq!([0x7F, 0xC0, 0, 0, 0x7F, 0xF8, 0, 0, 0, 0, 0, 0], outputs[0]);
+
+    // Without the option, the NaN bits are kept
+    let mut buffer = [0u8; 12];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&values[1], &mut writer).unwrap();
+    assert_ne!(outputs[1], buffer);
+}
+
+#[test]
+fn bool_width() {
+    let value = (true, 5u8, false);
+
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let config = Config::new().bool_width(BoolWidth::One);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(&[1, 5, 0], writer.written_buffer());
+
+    let config = Config::new().bool_width(BoolWidth::Four);
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::LittleEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(&[1, 0, 0, 0, 5, 0, 0, 0, 0], writer.written_buffer());
+    let deserialized: (bool, u8, bool) =
+        deserialize_with_config::<_, _, byteorder::LittleEndian>(&buffer[..9], config).unwrap();
+    assert_eq!(value, deserialized);
+
+    // Only the values 0 and 1 are accepted, a non-zero high byte is rejected
+    let buffer = [1, 0, 0, 1];
+    let result = deserialize_with_config::<bool, _, byteorder::LittleEndian>(&buffer[..], config);
+    match result {
+        Err(DeserializeError::InvalidWideBoolValue(0x0100_0001)) => {}
+        other => panic!("Expected InvalidWideBoolValue, got {:?}", other),
+    }
+}
+
+#[test]
+fn struct_field_guard() {
+    let value = Mixed {
+        value: 5,
+        name: "abc",
+    };
+
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let config = Config::new().struct_field_guard();
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(
+        &[2, 0, 0, 0, 5, 0, 3, b'a', b'b', b'c'],
+        writer.written_buffer()
+    );
+    let len = writer.written_len();
+    let deserialized: Mixed =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..len], config).unwrap();
+    assert_eq!(value, deserialized);
+
+    // A struct with a different field count is rejected before any field is read
+    buffer[0] = 3;
+    let result =
+        deserialize_with_config::<Mixed, _, byteorder::NetworkEndian>(&buffer[..len], config);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::FieldCountMismatch {
+            expected: 2,
+            found: 3
+        })
+    ));
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Telemetry<'a> {
+    id: u16,
+    temperature: i16,
+    label: &'a str,
+    alarm: bool,
+}
+
+#[test]
+fn field_mask() {
+    let value = Telemetry {
+        id: 7,
+        temperature: -2,
+        label: "hall",
+        alarm: true,
+    };
+
+    // Only the temperature and label are written
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let config = Config::new().field_filter(|_, index| index == 1 || index == 2);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(
+        &[0b0110, 0xFF, 0xFE, 0, 4, b'h', b'a', b'l', b'l'],
+        writer.written_buffer()
+    );
+    let len = writer.written_len();
+
+    // Absent fields are read as zero
+    let config = Config::new().field_mask();
+    let deserialized: Telemetry =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..len], config).unwrap();
+    let expected = Telemetry {
+        id: 0,
+        temperature: -2,
+        label: "hall",
+        alarm: false,
+    };
+    assert_eq!(expected, deserialized);
+
+    // Without a filter every field is present
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(
+        &[0b1111, 0, 7, 0xFF, 0xFE, 0, 4, b'h', b'a', b'l', b'l', 1],
+        writer.written_buffer()
+    );
+    let len = writer.written_len();
+    let deserialized: Telemetry =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..len], config).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Position {
+    x: i16,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum Mode {
+    Idle,
+    Active(u8),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Update {
+    id: u8,
+    position: Position,
+    mode: Mode,
+}
+
+#[test]
+fn field_mask_with_framing() {
+    let value = Update {
+        id: 3,
+        position: Position { x: 5 },
+        mode: Mode::Active(1),
+    };
+    let expected = Update {
+        id: 3,
+        position: Position { x: 0 },
+        mode: Mode::Idle,
+    };
+    let mask = Config::new().field_filter(|name, index| name != "Update" || index == 0);
+    // Absent fields get the same defaults when their framing can not be read from zero bytes
+    for config in [mask.struct_field_guard(), mask.string_enum_tags()].iter() {
+        let mut buffer = [0u8; 16];
+        let mut writer = BufferWriter::new(&mut buffer);
+        serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, *config)
+            .unwrap();
+        let len = writer.written_len();
+        let deserialized: Update =
+            deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..len], *config)
+                .unwrap();
+        assert_eq!(expected, deserialized);
+    }
+}
+
+#[test]
+fn lenient_bool() {
+    let buffer = [2u8, 0xFF, 0];
+
+    // Strict is the default
+    let result = deserialize::<bool, _, byteorder::NetworkEndian>(&buffer[..]);
+    assert!(matches!(result, Err(DeserializeError::InvalidBoolValue(2))));
+    let config = Config::new().lenient_bool().strict_bool();
+    let result = deserialize_with_config::<bool, _, byteorder::NetworkEndian>(&buffer[..], config);
+    assert!(matches!(result, Err(DeserializeError::InvalidBoolValue(2))));
+
+    let config = Config::new().lenient_bool();
+    let value: (bool, bool, bool) =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..], config).unwrap();
+    assert_eq!((true, true, false), value);
+
+    // Wide bools accept any non-zero value as well
+    let config = Config::new().lenient_bool().bool_width(BoolWidth::Four);
+    let buffer = [1, 0, 0, 1, 0, 0, 0, 0];
+    let value: (bool, bool) =
+        deserialize_with_config::<_, _, byteorder::LittleEndian>(&buffer[..], config).unwrap();
+    assert_eq!((true, false), value);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum Expr {
+    Value(u8),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+}
+
+#[test]
+fn max_depth() {
+    // -(-(1 + 2))
+    let value = Expr::Neg(Box::new(Expr::Neg(Box::new(Expr::Add(
+        Box::new(Expr::Value(1)),
+        Box::new(Expr::Value(2)),
+    )))));
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    let len = writer.written_len();
+
+    // The `Add` variant is a tuple inside an enum, which makes 5 levels with the values inside it
+    let config = Config::new().max_depth(5);
+    let deserialized: Expr =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..len], config).unwrap();
+    assert_eq!(value, deserialized);
+
+    let config = Config::new().max_depth(4);
+    let result =
+        deserialize_with_config::<Expr, _, byteorder::NetworkEndian>(&buffer[..len], config);
+    assert!(matches!(result, Err(DeserializeError::DepthExceeded(4))));
+
+    // Crafted input that nests `Neg` far deeper than any real value
+    let buffer = [1u8; 10_000];
+    let config = Config::new().max_depth(32);
+    let result = deserialize_with_config::<Expr, _, byteorder::NetworkEndian>(&buffer[..], config);
+    assert!(matches!(result, Err(DeserializeError::DepthExceeded(32))));
+
+    let config = Config::new().max_depth(1);
+    let value: Vec<u8> =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&[0, 1, 5][..], config).unwrap();
+    assert_eq!(vec![5], value);
+    let result = deserialize_with_config::<Option<Vec<u8>>, _, byteorder::NetworkEndian>(
+        &[1, 0, 0][..],
+        config,
+    );
+    assert!(matches!(result, Err(DeserializeError::DepthExceeded(1))));
+}
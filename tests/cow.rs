@@ -0,0 +1,55 @@
+#![cfg(feature = "alloc")]
+
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+use std::borrow::Cow;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Named<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow, with = "serde_bytes")]
+    data: Cow<'a, [u8]>,
+}
+
+fn encode(buffer: &mut [u8]) -> usize {
+    let value = Named {
+        name: Cow::Borrowed("abc"),
+        data: Cow::Borrowed(&[1, 2]),
+    };
+    let mut writer = BufferWriter::new(buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    writer.written_len()
+}
+
+#[test]
+fn cow_borrows() {
+    let mut buffer = [0u8; 16];
+    let len = encode(&mut buffer);
+    let input = &buffer[..len];
+
+    let deserialized: Named = deserialize::<_, _, byteorder::NetworkEndian>(input).unwrap();
+    assert_eq!("abc", deserialized.name);
+    assert_eq!(&[1, 2], &*deserialized.data);
+    match (&deserialized.name, &deserialized.data) {
+        (Cow::Borrowed(name), Cow::Borrowed(data)) => {
+            assert_eq!(input[2..].as_ptr(), name.as_ptr());
+            assert_eq!(input[7..].as_ptr(), data.as_ptr());
+        }
+        other => panic!("Expected borrowed values, got {:?}", other),
+    }
+}
+
+#[test]
+fn cow_copy_strings() {
+    let mut buffer = [0u8; 16];
+    let len = encode(&mut buffer);
+
+    let config = Config::new().copy_strings();
+    let deserialized: Named =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..len], config).unwrap();
+    assert!(matches!(deserialized.name, Cow::Owned(_)));
+    assert_eq!("abc", deserialized.name);
+}
@@ -0,0 +1,411 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum Command<'a> {
+    Reboot,
+    SetConfig { id: u8, value: &'a str },
+    SetLed(Led),
+    Move(i16, i16),
+    Message(&'a str),
+    Payload(#[serde(with = "serde_bytes")] &'a [u8]),
+    Packet(&'a [u8], u16),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Led {
+    index: u8,
+    color: Color,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum Color {
+    Off,
+    Rgb(u8, u8, u8),
+}
+
+fn roundtrip(command: &Command, expected: &[u8]) {
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(command, &mut writer).unwrap();
+    assert_eq!(expected, writer.written_buffer());
+    let len = writer.written_len();
+
+    let deserialized: Command =
+        deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+    assert_eq!(command, &deserialized);
+}
+
+#[test]
+fn unit_variant() {
+    roundtrip(&Command::Reboot, &[0]);
+}
+
+#[test]
+fn struct_variant() {
+    // The variant index is written before the fields
+    roundtrip(
+        &Command::SetConfig { id: 4, value: "on" },
+        &[1, 4, 0, 2, b'o', b'n'],
+    );
+}
+
+#[test]
+fn nested_enums() {
+    roundtrip(
+        &Command::SetLed(Led {
+            index: 2,
+            color: Color::Rgb(10, 20, 30),
+        }),
+        &[2, 2, 1, 10, 20, 30],
+    );
+    roundtrip(
+        &Command::SetLed(Led {
+            index: 3,
+            color: Color::Off,
+        }),
+        &[2, 3, 0],
+    );
+}
+
+#[test]
+fn tuple_variant() {
+    roundtrip(&Command::Move(-1, 2), &[3, 0xFF, 0xFF, 0, 2]);
+}
+
+#[test]
+fn every_variant_shape_in_one_frame() {
+    // The tag of every variant is followed by its content, so consecutive values stay aligned
+    let commands = (
+        Command::Reboot,
+        Command::Message("up"),
+        Command::Move(-1, 2),
+        Command::SetConfig { id: 4, value: "on" },
+        Command::Reboot,
+    );
+    #[rustfmt::skip]
+    let expected = [
+        0,
+        4, 0, 2, b'u', b'p',
+        3, 0xFF, 0xFF, 0, 2,
+        1, 4, 0, 2, b'o', b'n',
+        0,
+    ];
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&commands, &mut writer).unwrap();
+    assert_eq!(&expected[..], writer.written_buffer());
+
+    let deserialized: (Command, Command, Command, Command, Command) =
+        deserialize_exact::<_, _, byteorder::NetworkEndian>(&expected[..]).unwrap();
+    assert_eq!(commands, deserialized);
+
+    // Cutting the frame off after a tag fails while reading the content
+    let result = deserialize::<Command, _, byteorder::NetworkEndian>(&expected[6..7]);
+    assert!(matches!(result, Err(DeserializeError::Read(()))));
+}
+
+#[test]
+fn unknown_variant_index() {
+    // The index is checked before the content of the variant is read
+    for tag in [7u8, 8, 0xFF].iter() {
+        let buffer = [*tag];
+        let result = deserialize::<Command, _, byteorder::NetworkEndian>(&buffer[..]);
+        match result {
+            Err(DeserializeError::UnknownVariantIndex(index)) if index == *tag as u32 => {}
+            other => panic!("Expected UnknownVariantIndex({}), got {:?}", tag, other),
+        }
+    }
+    let result = deserialize::<Color, _, byteorder::NetworkEndian>(&[2u8][..]);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::UnknownVariantIndex(2))
+    ));
+}
+
+#[test]
+fn sequence_of_enums() {
+    let value = [Color::Off, Color::Rgb(1, 2, 3), Color::Off];
+
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(&[0, 1, 1, 2, 3, 0], writer.written_buffer());
+
+    let deserialized: [Color; 3] =
+        deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..6]).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[test]
+fn newtype_variant_borrowed() {
+    roundtrip(&Command::Message("hi"), &[4, 0, 2, b'h', b'i']);
+    roundtrip(&Command::Payload(&[1, 2, 3]), &[5, 0, 3, 1, 2, 3]);
+
+    // The borrowed values point into the input buffer
+    let buffer = [4, 0, 2, b'h', b'i'];
+    let command: Command = deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+    match command {
+        Command::Message(message) => {
+            assert_eq!("hi", message);
+            assert_eq!(buffer[3..].as_ptr(), message.as_ptr());
+        }
+        other => panic!("Expected Message, got {:?}", other),
+    }
+
+    let buffer = [5, 0, 3, 1, 2, 3];
+    let command: Command = deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+    match command {
+        Command::Payload(payload) => {
+            assert_eq!(&[1, 2, 3], payload);
+            assert_eq!(buffer[3..].as_ptr(), payload.as_ptr());
+        }
+        other => panic!("Expected Payload, got {:?}", other),
+    }
+}
+
+#[test]
+fn tuple_variant_borrowed() {
+    // The variant index, the length of the slice, the slice and the trailing field
+    let expected = [6, 0, 4, 0xDE, 0xAD, 0xBE, 0xEF, 0x12, 0x34];
+    roundtrip(
+        &Command::Packet(&[0xDE, 0xAD, 0xBE, 0xEF], 0x1234),
+        &expected,
+    );
+
+    let command: Command = deserialize::<_, _, byteorder::NetworkEndian>(&expected[..]).unwrap();
+    match command {
+        Command::Packet(payload, crc) => {
+            assert_eq!(&[0xDE, 0xAD, 0xBE, 0xEF], payload);
+            assert_eq!(expected[3..].as_ptr(), payload.as_ptr());
+            assert_eq!(0x1234, crc);
+        }
+        other => panic!("Expected Packet, got {:?}", other),
+    }
+
+    // An empty slice is followed by the trailing field
+    roundtrip(&Command::Packet(&[], 7), &[6, 0, 0, 0, 7]);
+}
+
+macro_rules! large_enum {
+    ($($variant:ident)*) => {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        enum Large {
+            $($variant,)*
+            Last(u8),
+        }
+
+        const LARGE_VARIANTS: &[Large] = &[$(Large::$variant,)*];
+    };
+}
+
+// 300 unit variants, followed by `Last`
+large_enum! {
+    V000 V001 V002 V003 V004 V005 V006 V007 V008 V009 V010 V011
+    V012 V013 V014 V015 V016 V017 V018 V019 V020 V021 V022 V023
+    V024 V025 V026 V027 V028 V029 V030 V031 V032 V033 V034 V035
+    V036 V037 V038 V039 V040 V041 V042 V043 V044 V045 V046 V047
+    V048 V049 V050 V051 V052 V053 V054 V055 V056 V057 V058 V059
+    V060 V061 V062 V063 V064 V065 V066 V067 V068 V069 V070 V071
+    V072 V073 V074 V075 V076 V077 V078 V079 V080 V081 V082 V083
+    V084 V085 V086 V087 V088 V089 V090 V091 V092 V093 V094 V095
+    V096 V097 V098 V099 V100 V101 V102 V103 V104 V105 V106 V107
+    V108 V109 V110 V111 V112 V113 V114 V115 V116 V117 V118 V119
+    V120 V121 V122 V123 V124 V125 V126 V127 V128 V129 V130 V131
+    V132 V133 V134 V135 V136 V137 V138 V139 V140 V141 V142 V143
+    V144 V145 V146 V147 V148 V149 V150 V151 V152 V153 V154 V155
+    V156 V157 V158 V159 V160 V161 V162 V163 V164 V165 V166 V167
+    V168 V169 V170 V171 V172 V173 V174 V175 V176 V177 V178 V179
+    V180 V181 V182 V183 V184 V185 V186 V187 V188 V189 V190 V191
+    V192 V193 V194 V195 V196 V197 V198 V199 V200 V201 V202 V203
+    V204 V205 V206 V207 V208 V209 V210 V211 V212 V213 V214 V215
+    V216 V217 V218 V219 V220 V221 V222 V223 V224 V225 V226 V227
+    V228 V229 V230 V231 V232 V233 V234 V235 V236 V237 V238 V239
+    V240 V241 V242 V243 V244 V245 V246 V247 V248 V249 V250 V251
+    V252 V253 V254 V255 V256 V257 V258 V259 V260 V261 V262 V263
+    V264 V265 V266 V267 V268 V269 V270 V271 V272 V273 V274 V275
+    V276 V277 V278 V279 V280 V281 V282 V283 V284 V285 V286 V287
+    V288 V289 V290 V291 V292 V293 V294 V295 V296 V297 V298 V299
+}
+
+#[test]
+fn variant_width_u16() {
+    let config = Config::new().variant_width(VariantWidth::U16);
+    let mut buffer = [0u8; 8];
+    for (index, variant) in LARGE_VARIANTS.iter().enumerate() {
+        let mut writer = BufferWriter::new(&mut buffer);
+        serialize_with_config::<_, _, byteorder::NetworkEndian>(variant, &mut writer, config)
+            .unwrap();
+        assert_eq!(&(index as u16).to_be_bytes(), writer.written_buffer());
+        let deserialized: Large =
+            deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..2], config)
+                .unwrap();
+        assert_eq!(variant, &deserialized);
+    }
+
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&Large::Last(7), &mut writer, config)
+        .unwrap();
+    assert_eq!(&[0x01, 0x2C, 7], writer.written_buffer());
+    let deserialized: Large =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..3], config).unwrap();
+    assert_eq!(Large::Last(7), deserialized);
+
+    // The default width can not encode these variants
+    let mut writer = BufferWriter::new(&mut buffer);
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&Large::V256, &mut writer);
+    assert!(matches!(result, Err(SerializeError::LengthOverflow)));
+}
+
+#[test]
+fn unit_variant_overflow() {
+    let mut buffer = [0u8; 8];
+    for (index, variant) in LARGE_VARIANTS.iter().enumerate() {
+        let mut writer = BufferWriter::new(&mut buffer);
+        let result = serialize::<_, _, byteorder::NetworkEndian>(variant, &mut writer);
+        if index < 256 {
+            result.unwrap();
+            assert_eq!(&[index as u8], writer.written_buffer());
+        } else {
+            // The index is checked before anything is written
+            assert!(matches!(result, Err(SerializeError::LengthOverflow)));
+            assert!(writer.written_buffer().is_empty());
+        }
+    }
+}
+
+#[test]
+fn string_enum_tags() {
+    let config = Config::new().string_enum_tags();
+    let cases: [(Command, &[u8]); 3] = [
+        (Command::Reboot, b"\x00\x06Reboot"),
+        (Command::Move(1, -1), b"\x00\x04Move\x00\x01\xFF\xFF"),
+        (
+            Command::SetLed(Led {
+                index: 2,
+                color: Color::Rgb(10, 20, 30),
+            }),
+            b"\x00\x06SetLed\x02\x00\x03Rgb\x0A\x14\x1E",
+        ),
+    ];
+    for (command, expected) in cases.iter() {
+        let mut buffer = [0u8; 32];
+        let mut writer = BufferWriter::new(&mut buffer);
+        serialize_with_config::<_, _, byteorder::NetworkEndian>(command, &mut writer, config)
+            .unwrap();
+        assert_eq!(*expected, writer.written_buffer());
+
+        let deserialized: Command =
+            deserialize_with_config::<_, _, byteorder::NetworkEndian>(*expected, config).unwrap();
+        assert_eq!(command, &deserialized);
+    }
+}
+
+#[test]
+fn string_enum_tags_unknown_name() {
+    let config = Config::new().string_enum_tags();
+    for input in [&b"\x00\x05Sleep"[..], b"\x00\x05reboo", b"\x00\x00"]
+        .iter()
+        .copied()
+    {
+        let result = deserialize_with_config::<Command, _, byteorder::NetworkEndian>(input, config);
+        assert!(matches!(result, Err(DeserializeError::UnknownVariant)));
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum Heartbeat {
+    Alive { uptime: u16 },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Status {
+    heartbeat: SingleVariant<Heartbeat>,
+    color: Color,
+}
+
+#[test]
+fn omit_single_variant_tag() {
+    let config = Config::new().omit_single_variant_tag();
+    let value = Status {
+        heartbeat: SingleVariant(Heartbeat::Alive { uptime: 0x0102 }),
+        color: Color::Rgb(1, 2, 3),
+    };
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    // No tag for the heartbeat, enums with more variants keep their tag
+    assert_eq!(&[1, 2, 1, 1, 2, 3], writer.written_buffer());
+
+    let deserialized: Status =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..6], config).unwrap();
+    assert_eq!(value, deserialized);
+
+    // The wrapper is transparent without the option
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(&[0, 1, 2, 1, 1, 2, 3], writer.written_buffer());
+    let deserialized: Status = deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..7]).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Lamp {
+    color: Color,
+}
+
+#[test]
+fn omit_single_variant_tag_nested_enum() {
+    let config = Config::new().omit_single_variant_tag();
+    // The wrapper does not hold an enum, so the tag of the enum in the struct is kept
+    let value = SingleVariant(Lamp {
+        color: Color::Rgb(1, 2, 3),
+    });
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(&[1, 1, 2, 3], writer.written_buffer());
+    let deserialized: SingleVariant<Lamp> =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..4], config).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum One {
+    Only(u8),
+}
+
+#[test]
+fn omit_single_variant_tag_unwrapped() {
+    let config = Config::new().omit_single_variant_tag();
+    // Enums that are not wrapped keep their tag, also when they have a single variant
+    let value = (One::Only(7), 9u8);
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(&[0, 7, 9], writer.written_buffer());
+    let deserialized: (One, u8) =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..3], config).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[test]
+fn omit_single_variant_tag_multiple_variants() {
+    let config = Config::new().omit_single_variant_tag();
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let result = serialize_with_config::<_, _, byteorder::NetworkEndian>(
+        &SingleVariant(Color::Rgb(1, 2, 3)),
+        &mut writer,
+        config,
+    );
+    assert!(matches!(result, Err(SerializeError::NotSupported(_))));
+    let result = deserialize_with_config::<SingleVariant<Color>, _, byteorder::NetworkEndian>(
+        &[1u8, 2, 3][..],
+        config,
+    );
+    assert!(matches!(result, Err(DeserializeError::NotSupported(_))));
+}
@@ -0,0 +1,229 @@
+use bincode_embedded::*;
+
+type DeError = DeserializeError<'static, &'static [u8]>;
+type SerError = SerializeError<&'static mut BufferWriter<'static>>;
+
+#[test]
+fn deserialize_error_display() {
+    let invalid = vec![0xFF];
+    let utf8 = std::str::from_utf8(&invalid).unwrap_err();
+    let cases: Vec<(DeError, &str)> = vec![
+        (DeserializeError::Read(()), "read error: ()"),
+        (
+            DeserializeError::ReadAt {
+                source: (),
+                offset: 5,
+            },
+            "read error at offset 5: ()",
+        ),
+        (
+            DeserializeError::ReadInField {
+                source: (),
+                field: "crc",
+                offset: 5,
+            },
+            "read error in field `crc` at offset 5: ()",
+        ),
+        (
+            DeserializeError::InvalidBoolValue(2),
+            "invalid bool value 2, expected 0 or 1",
+        ),
+        (
+            DeserializeError::InvalidWideBoolValue(0x100),
+            "invalid bool value 0x100, expected 0 or 1",
+        ),
+        (
+            DeserializeError::InvalidCharEncoding,
+            "invalid character encoding",
+        ),
+        (
+            DeserializeError::Utf8(utf8),
+            "string is not valid UTF-8: invalid utf-8 sequence of 1 bytes from index 0",
+        ),
+        (
+            DeserializeError::InvalidOptionValue(3),
+            "invalid Option tag 3, expected 0 or 1",
+        ),
+        (
+            DeserializeError::InvalidNonZero,
+            "expected a non-zero integer, got 0",
+        ),
+        (
+            DeserializeError::LimitExceeded,
+            "deserialize limit exceeded",
+        ),
+        (
+            DeserializeError::ScratchTooSmall { len: 8, scratch: 4 },
+            "scratch buffer is too small, got 4 bytes, expected at least 8",
+        ),
+        (
+            DeserializeError::UnconsumedElements(2),
+            "sequence has 2 elements that were not read",
+        ),
+        (
+            DeserializeError::TrailingBytes(3),
+            "3 trailing bytes after the value",
+        ),
+        (
+            DeserializeError::ByteOrderRequired,
+            "value requires a byte order",
+        ),
+        (DeserializeError::DelimiterNotFound, "delimiter not found"),
+        (
+            DeserializeError::StringTooLong { len: 10, max: 4 },
+            "string is too long, got 10 bytes, expected at most 4",
+        ),
+        (
+            DeserializeError::TooManyChars { count: 5, max: 4 },
+            "string has too many characters, got 5, expected at most 4",
+        ),
+        (
+            DeserializeError::StringCapacityExceeded {
+                len: 10,
+                capacity: 4,
+            },
+            "string does not fit, got 10 bytes, capacity is 4",
+        ),
+        (
+            DeserializeError::VecCapacityExceeded {
+                len: 10,
+                capacity: 4,
+            },
+            "sequence does not fit, got 10 elements, capacity is 4",
+        ),
+        (
+            DeserializeError::UnknownVariant,
+            "unknown enum variant name",
+        ),
+        (
+            DeserializeError::UnknownVariantIndex(7),
+            "unknown enum variant index 7",
+        ),
+        (
+            DeserializeError::DepthExceeded(16),
+            "values are nested deeper than 16 levels",
+        ),
+        (
+            DeserializeError::NotSupported("flatten"),
+            "flatten is not supported",
+        ),
+        (
+            DeserializeError::FieldCountMismatch {
+                expected: 2,
+                found: 3,
+            },
+            "struct has 3 fields, expected 2",
+        ),
+        (DeserializeError::BadMagic, "magic number does not match"),
+        (
+            DeserializeError::Custom,
+            "custom error of a Deserialize implementation",
+        ),
+    ];
+    for (error, expected) in cases {
+        assert_eq!(expected, error.to_string());
+    }
+}
+
+#[test]
+fn serialize_error_display() {
+    let cases: Vec<(SerError, &str)> = vec![
+        (
+            SerializeError::Write(BufferWriterError::BufferTooSmall),
+            "write error: BufferTooSmall",
+        ),
+        (
+            SerializeError::SequenceMustHaveLength,
+            "sequence must have a known length",
+        ),
+        (
+            SerializeError::LengthOverflow,
+            "length does not fit the length type",
+        ),
+        (
+            SerializeError::ByteOrderRequired,
+            "value requires a byte order",
+        ),
+        (SerializeError::InteriorNul, "string contains a NUL byte"),
+        (
+            SerializeError::NotSupported("flatten"),
+            "flatten is not supported",
+        ),
+        (
+            SerializeError::Custom,
+            "custom error of a Serialize implementation",
+        ),
+    ];
+    for (error, expected) in cases {
+        assert_eq!(expected, error.to_string());
+    }
+}
+
+#[test]
+fn debug_shows_the_variant() {
+    let error: DeError = DeserializeError::StringTooLong { len: 10, max: 4 };
+    assert_eq!("StringTooLong { len: 10, max: 4 }", format!("{:?}", error));
+    let error: DeError = DeserializeError::Read(());
+    assert_eq!("Read(())", format!("{:?}", error));
+    let error: SerError = SerializeError::Write(BufferWriterError::BufferTooSmall);
+    assert_eq!("Write(BufferTooSmall)", format!("{:?}", error));
+}
+
+/// Fails to serialize and deserialize with a custom error.
+struct Rejected;
+
+impl serde::Serialize for Rejected {
+    fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("rejected"))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Rejected {
+    fn deserialize<D: serde::Deserializer<'de>>(_deserializer: D) -> Result<Self, D::Error> {
+        Err(serde::de::Error::custom("rejected"))
+    }
+}
+
+/// Serialized with `Serializer::collect_str`.
+struct Collected;
+
+impl serde::Serialize for Collected {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&5)
+    }
+}
+
+#[test]
+fn errors_instead_of_panics() {
+    let mut buffer = [0u8; 4];
+
+    let result =
+        serialize::<_, _, byteorder::NetworkEndian>(&Rejected, BufferWriter::new(&mut buffer));
+    assert!(matches!(result, Err(SerializeError::Custom)));
+    let result = deserialize::<Rejected, _, byteorder::NetworkEndian>(&buffer[..]);
+    assert!(matches!(result, Err(DeserializeError::Custom)));
+
+    // A value that serde rejects, the nanoseconds overflow the seconds
+    let result = deserialize::<std::time::Duration, _, byteorder::NetworkEndian>(&[0xFF; 12][..]);
+    assert!(matches!(result, Err(DeserializeError::Custom)));
+
+    let result =
+        serialize::<_, _, byteorder::NetworkEndian>(&Collected, BufferWriter::new(&mut buffer));
+    assert!(matches!(
+        result,
+        Err(SerializeError::NotSupported("collect_str"))
+    ));
+
+    let result = deserialize::<serde::de::IgnoredAny, _, byteorder::NetworkEndian>(&buffer[..]);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::NotSupported("deserialize_ignored_any"))
+    ));
+
+    // The owned `BufferWriter` returns an error when it is full
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&0u64, BufferWriter::new(&mut buffer));
+    assert!(matches!(
+        result,
+        Err(SerializeError::Write(BufferWriterError::BufferTooSmall))
+    ));
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "embedded-storage")]
+
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+use embedded_storage::nor_flash::{check_read, ErrorType, NorFlashErrorKind, ReadNorFlash};
+
+/// A flash of 256 bytes that counts the reads.
+struct MockFlash {
+    data: [u8; 256],
+    reads: usize,
+}
+
+impl MockFlash {
+    /// A flash that contains `index as u8` at every index.
+    fn new() -> Self {
+        let mut data = [0u8; 256];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        Self { data, reads: 0 }
+    }
+}
+
+impl ErrorType for MockFlash {
+    type Error = NorFlashErrorKind;
+}
+
+impl ReadNorFlash for MockFlash {
+    const READ_SIZE: usize = 4;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        check_read(self, offset, bytes.len())?;
+        self.reads += 1;
+        let offset = offset as usize;
+        bytes.copy_from_slice(&self.data[offset..offset + bytes.len()]);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.data.len()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Settings<'a> {
+    id: u8,
+    interval: u16,
+    name: &'a str,
+}
+
+#[test]
+fn flash_pattern() {
+    let mut flash = MockFlash::new();
+    let mut scratch = [0u8; 16];
+    let reader = FlashReader::new(&mut flash, 0x20, &mut scratch);
+    let value: (u8, u16, [u8; 4]) = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+    assert_eq!((0x20, 0x2122, [0x23, 0x24, 0x25, 0x26]), value);
+
+    // The whole region is read at once
+    assert_eq!(1, flash.reads);
+    assert_eq!(0x2F, scratch[15]);
+}
+
+#[test]
+fn flash_settings() {
+    let settings = Settings {
+        id: 7,
+        interval: 1000,
+        name: "node",
+    };
+    let mut flash = MockFlash::new();
+    let mut writer = BufferWriter::new(&mut flash.data[0x40..0x60]);
+    serialize::<_, _, byteorder::NetworkEndian>(&settings, &mut writer).unwrap();
+
+    let mut scratch = [0u8; 32];
+    let reader = FlashReader::new(&mut flash, 0x40, &mut scratch);
+    let deserialized: Settings = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+    assert_eq!(settings, deserialized);
+}
+
+#[test]
+fn flash_errors() {
+    // The region is not aligned to the read size of the flash
+    let mut flash = MockFlash::new();
+    let mut scratch = [0u8; 8];
+    let reader = FlashReader::new(&mut flash, 0x21, &mut scratch);
+    let result = deserialize::<u8, _, byteorder::NetworkEndian>(reader);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::Read(FlashReaderError::Flash(
+            NorFlashErrorKind::NotAligned
+        )))
+    ));
+
+    // The region is past the end of the flash
+    let reader = FlashReader::new(&mut flash, 0xFC, &mut scratch);
+    let result = deserialize::<u8, _, byteorder::NetworkEndian>(reader);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::Read(FlashReaderError::Flash(
+            NorFlashErrorKind::OutOfBounds
+        )))
+    ));
+
+    // The value is larger than the region
+    let reader = FlashReader::new(&mut flash, 0, &mut scratch);
+    let result = deserialize::<(u32, u32, u8), _, byteorder::NetworkEndian>(reader);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::Read(FlashReaderError::UnexpectedEnd))
+    ));
+    assert_eq!(1, flash.reads);
+}
@@ -0,0 +1,162 @@
+#![cfg(feature = "heapless")]
+
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Device {
+    id: u8,
+    #[serde(with = "heapless_string")]
+    name: heapless::String<16>,
+}
+
+fn device(name: &str) -> Device {
+    let mut device = Device {
+        id: 3,
+        name: heapless::String::new(),
+    };
+    device.name.push_str(name).unwrap();
+    device
+}
+
+#[test]
+fn string() {
+    let value = device("temperature");
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(&b"\x03\x00\x0btemperature"[..], writer.written_buffer());
+    let len = writer.written_len();
+
+    let deserialized: Device =
+        deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+    assert_eq!(value, deserialized);
+
+    // The string is copied, so it can be read from a reader that can not lend its data
+    let mut scratch = [0u8; 16];
+    let deserialized: Device = deserialize_with_scratch::<_, _, byteorder::NetworkEndian>(
+        &buffer[..len],
+        Config::new(),
+        &mut scratch,
+    )
+    .unwrap();
+    assert_eq!(value, deserialized);
+
+    let value = device("");
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    let len = writer.written_len();
+    let deserialized: Device =
+        deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[test]
+fn string_capacity_exceeded() {
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&(3u8, "a string of 17 ch"), &mut writer).unwrap();
+    let len = writer.written_len();
+
+    let result = deserialize::<Device, _, byteorder::NetworkEndian>(&buffer[..len]);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::StringCapacityExceeded {
+            len: 17,
+            capacity: 16
+        })
+    ));
+
+    // The length is checked before the string is read
+    let result = deserialize::<Device, _, byteorder::NetworkEndian>(&[3, 0xFF, 0xFF][..]);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::StringCapacityExceeded {
+            len: 0xFFFF,
+            capacity: 16
+        })
+    ));
+}
+
+#[test]
+fn string_cstr_mode() {
+    let config = Config::new().cstr_mode();
+    let value = device("abc");
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(&b"\x03abc\x00"[..], writer.written_buffer());
+    let len = writer.written_len();
+    let deserialized: Device =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(&buffer[..len], config).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Samples {
+    #[serde(with = "heapless_vec")]
+    values: heapless::Vec<u32, 8>,
+    #[serde(with = "heapless_vec")]
+    devices: heapless::Vec<Device, 2>,
+}
+
+#[test]
+fn vec() {
+    let mut value = Samples {
+        values: heapless::Vec::new(),
+        devices: heapless::Vec::new(),
+    };
+    for i in 0..8 {
+        value.values.push(i * 1000).unwrap();
+    }
+    value.devices.push(device("a")).unwrap();
+
+    let mut buffer = [0u8; 64];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(2 + 8 * 4 + 2 + 4, writer.written_len());
+    let len = writer.written_len();
+
+    // A heapless::Vec is encoded the same as a slice
+    let mut expected = [0u8; 64];
+    let mut writer = BufferWriter::new(&mut expected);
+    serialize::<_, _, byteorder::NetworkEndian>(
+        &(&value.values[..], &[(3u8, "a")][..]),
+        &mut writer,
+    )
+    .unwrap();
+    assert_eq!(&buffer[..len], writer.written_buffer());
+
+    let deserialized: Samples =
+        deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[test]
+fn vec_capacity_exceeded() {
+    let mut buffer = [0u8; 64];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&vec![0u32; 9], &mut writer).unwrap();
+    let len = writer.written_len();
+
+    let result = deserialize::<Samples, _, byteorder::NetworkEndian>(&buffer[..len]);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::VecCapacityExceeded {
+            len: 9,
+            capacity: 8
+        })
+    ));
+
+    // The length is checked before any element is read
+    let result = deserialize::<Samples, _, byteorder::NetworkEndian>(&[0xFF, 0xFF][..]);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::VecCapacityExceeded {
+            len: 0xFFFF,
+            capacity: 8
+        })
+    ));
+}
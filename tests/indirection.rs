@@ -0,0 +1,63 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Point {
+    x: i16,
+    y: i16,
+}
+
+#[derive(Serialize)]
+struct Borrowing<'a> {
+    point: &'a Point,
+    points: &'a [&'a Point; 2],
+}
+
+#[test]
+fn references() {
+    let point = Point { x: 1, y: -1 };
+    let value = Borrowing {
+        point: &point,
+        points: &[&point, &point],
+    };
+
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(
+        &[0, 1, 0xFF, 0xFF, 0, 1, 0xFF, 0xFF, 0, 1, 0xFF, 0xFF],
+        writer.written_buffer()
+    );
+
+    // The same bytes as the owned value
+    let deserialized: [Point; 3] =
+        deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..12]).unwrap();
+    assert!(deserialized.iter().all(|p| *p == point));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn boxed() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Boxed {
+        point: Box<Point>,
+        name: Box<str>,
+    }
+
+    let value = Boxed {
+        point: Box::new(Point { x: 3, y: 4 }),
+        name: "abc".into(),
+    };
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(
+        &[0, 3, 0, 4, 0, 3, b'a', b'b', b'c'],
+        writer.written_buffer()
+    );
+
+    let deserialized: Boxed = deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+    assert_eq!(value, deserialized);
+}
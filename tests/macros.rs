@@ -0,0 +1,79 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Status<'a> {
+    id: u16,
+    name: &'a str,
+}
+
+/// A fake serial port that records every call.
+#[derive(Default)]
+struct Serial {
+    sent: Vec<u8>,
+    flushes: usize,
+}
+
+impl CoreWrite for Serial {
+    type Error = ();
+
+    fn write(&mut self, val: u8) -> Result<(), Self::Error> {
+        self.sent.push(val);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flushes += 1;
+        Ok(())
+    }
+}
+
+struct Uart {
+    serial: Serial,
+}
+
+struct Driver {
+    uart: Uart,
+}
+
+impl_core_write!(Uart, serial: Serial);
+impl_core_write!(Driver, uart.serial: Serial);
+
+struct Frame<'a> {
+    body: &'a [u8],
+}
+
+impl_core_read!('a, Frame<'a>, body: &'a [u8]);
+
+#[test]
+fn write_through_wrapper() {
+    let value = Status { id: 7, name: "ok" };
+
+    let mut uart = Uart {
+        serial: Serial::default(),
+    };
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut uart).unwrap();
+    uart.flush().unwrap();
+    assert_eq!(&[0, 7, 0, 2, b'o', b'k'], &uart.serial.sent[..]);
+    assert_eq!(1, uart.serial.flushes);
+
+    let mut driver = Driver { uart };
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut driver).unwrap();
+    assert_eq!(12, driver.uart.serial.sent.len());
+}
+
+#[test]
+fn read_through_wrapper() {
+    let buffer = [0, 7, 0, 2, b'o', b'k'];
+    let frame = Frame { body: &buffer };
+    let (value, frame): (Status, _) =
+        deserialize_and_return::<_, _, byteorder::NetworkEndian>(frame).unwrap();
+    assert_eq!(Status { id: 7, name: "ok" }, value);
+    assert!(frame.body.is_empty());
+
+    let frame = Frame { body: &buffer[..3] };
+    let result = deserialize::<Status, _, byteorder::NetworkEndian>(frame);
+    assert!(matches!(result, Err(DeserializeError::Read(()))));
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "alloc")]
+
+use bincode_embedded::*;
+use std::collections::{BTreeMap, HashMap};
+
+fn to_bytes<T: serde::Serialize>(value: &T, config: Config) -> Vec<u8> {
+    let mut buffer = [0u8; 256];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(value, &mut writer, config).unwrap();
+    writer.written_buffer().to_vec()
+}
+
+#[test]
+fn sort_map_keys() {
+    let config = Config::new().sort_map_keys();
+    let entries = [(300u16, "c"), (1, "a"), (20, "b"), (4000, "d"), (5, "")];
+
+    // Every HashMap has its own random iteration order
+    let first: HashMap<u16, &str> = entries.iter().copied().collect();
+    let second: HashMap<u16, &str> = entries.iter().rev().copied().collect();
+    let bytes = to_bytes(&first, config);
+    assert_eq!(bytes, to_bytes(&second, config));
+
+    // Big-endian keys sort the same as the numbers, which matches the order of a BTreeMap
+    let sorted: BTreeMap<u16, &str> = entries.iter().copied().collect();
+    assert_eq!(to_bytes(&sorted, Config::new()), bytes);
+
+    let deserialized: HashMap<u16, &str> =
+        deserialize::<_, _, byteorder::NetworkEndian>(&bytes[..]).unwrap();
+    assert_eq!(first, deserialized);
+}
+
+#[test]
+fn sort_map_keys_nested() {
+    let config = Config::new().sort_map_keys();
+    let mut inner = HashMap::new();
+    inner.insert('b', vec![Some(2u8)]);
+    inner.insert('a', vec![None, Some(1)]);
+    let mut outer = HashMap::new();
+    outer.insert(2u8, inner.clone());
+    outer.insert(1u8, inner);
+
+    let bytes = to_bytes(&outer, config);
+    assert_eq!(
+        vec![
+            2, 1, 2, b'a', 0, 2, 0, 1, 1, b'b', 0, 1, 1, 2, 2, 2, b'a', 0, 2, 0, 1, 1, b'b', 0, 1,
+            1, 2
+        ],
+        bytes
+    );
+    let deserialized: HashMap<u8, HashMap<char, Vec<Option<u8>>>> =
+        deserialize::<_, _, byteorder::NetworkEndian>(&bytes[..]).unwrap();
+    assert_eq!(outer, deserialized);
+}
+
+#[test]
+fn sort_map_keys_bit_pack_tags() {
+    let config = Config::new().sort_map_keys().bit_pack_tags();
+    let map: HashMap<bool, bool> = [(true, false)].iter().copied().collect();
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let result = serialize_with_config::<_, _, byteorder::NetworkEndian>(&map, &mut writer, config);
+    assert!(matches!(result, Err(SerializeError::NotSupported(_))));
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "nb")]
+
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Reading {
+    sensor: u8,
+    value: i32,
+    valid: bool,
+}
+
+const READING: Reading = Reading {
+    sensor: 3,
+    value: -1000,
+    valid: true,
+};
+
+#[test]
+fn writer_retries_would_block() {
+    let mut output = Vec::new();
+    let mut calls = 0;
+    let writer = NbWriter::new(|byte| -> nb::Result<(), ()> {
+        calls += 1;
+        // Every byte is rejected twice before the driver accepts it
+        if calls % 3 != 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+        output.push(byte);
+        Ok(())
+    });
+    serialize::<_, _, byteorder::NetworkEndian>(&READING, writer).unwrap();
+    assert_eq!(18, calls);
+    assert_eq!(&[3, 0xFF, 0xFF, 0xFC, 0x18, 1], &output[..]);
+}
+
+#[test]
+fn writer_error() {
+    let writer = NbWriter::new(|_| -> nb::Result<(), &str> { Err(nb::Error::Other("busy")) });
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&READING, writer);
+    assert!(matches!(result, Err(SerializeError::Write("busy"))));
+}
+
+#[test]
+fn reader_retries_would_block() {
+    let mut input = [3u8, 0xFF, 0xFF, 0xFC, 0x18, 1].iter().copied();
+    let mut calls = 0;
+    let reader = NbReader::new(|| -> nb::Result<u8, ()> {
+        calls += 1;
+        if calls % 3 != 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+        input.next().ok_or(nb::Error::Other(()))
+    });
+    let value: Reading = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+    assert_eq!(READING, value);
+    assert_eq!(18, calls);
+}
+
+#[test]
+fn reader_borrowed() {
+    let mut input = [0u8, 1, b'a'].iter().copied();
+    let reader =
+        NbReader::new(|| -> nb::Result<u8, ()> { input.next().ok_or(nb::Error::Other(())) });
+    let result = deserialize::<&str, _, byteorder::NetworkEndian>(reader);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::Read(NbReaderError::NotPersistent))
+    ));
+
+    // A scratch buffer can be used to copy the string instead
+    let mut input = [0u8, 1, b'a'].iter().copied();
+    let reader =
+        NbReader::new(|| -> nb::Result<u8, ()> { input.next().ok_or(nb::Error::Other(())) });
+    let mut scratch = [0u8; 4];
+    let value: String = deserialize_with_scratch::<_, _, byteorder::NetworkEndian>(
+        reader,
+        Config::new(),
+        &mut scratch,
+    )
+    .unwrap();
+    assert_eq!("a", value);
+}
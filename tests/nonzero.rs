@@ -0,0 +1,72 @@
+use bincode_embedded::*;
+use core::num::{NonZeroI32, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+use serde::de::{DeserializeOwned, Deserializer, Error, Unexpected};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// Serialize `value`, check that it is encoded as its inner integer, and deserialize it again.
+fn roundtrip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: T, expected: &[u8]) {
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(expected, writer.written_buffer());
+    let deserialized: T = deserialize::<_, _, byteorder::NetworkEndian>(expected).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+/// Deserialize a zero value of `size` bytes, which must be rejected.
+fn assert_rejects_zero<T: DeserializeOwned + Debug>(size: usize) {
+    let buffer = [0u8; 8];
+    match deserialize::<T, _, byteorder::NetworkEndian>(&buffer[..size]) {
+        Err(DeserializeError::InvalidNonZero) => {}
+        other => panic!("Expected InvalidNonZero, got {:?}", other),
+    }
+}
+
+#[test]
+fn nonzero_roundtrip() {
+    roundtrip(NonZeroU8::new(7).unwrap(), &[7]);
+    roundtrip(NonZeroU16::new(0x0102).unwrap(), &[1, 2]);
+    roundtrip(NonZeroU32::new(0x0102_0304).unwrap(), &[1, 2, 3, 4]);
+    roundtrip(
+        NonZeroU64::new(u64::MAX).unwrap(),
+        &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+    );
+    roundtrip(NonZeroI32::new(-1).unwrap(), &[0xFF, 0xFF, 0xFF, 0xFF]);
+    roundtrip(Some(NonZeroU16::new(1).unwrap()), &[1, 0, 1]);
+}
+
+#[test]
+fn nonzero_rejects_zero() {
+    assert_rejects_zero::<NonZeroU8>(1);
+    assert_rejects_zero::<NonZeroU16>(2);
+    assert_rejects_zero::<NonZeroU32>(4);
+    assert_rejects_zero::<NonZeroU64>(8);
+    assert_rejects_zero::<NonZeroI32>(4);
+}
+
+/// A value that rejects zero itself, without being a `NonZero*` type.
+#[derive(Debug)]
+struct Percentage(u8);
+
+impl<'de> Deserialize<'de> for Percentage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u8::deserialize(deserializer)?;
+        if value == 0 || value > 100 {
+            let unexp = Unexpected::Unsigned(value as u64);
+            return Err(D::Error::invalid_value(
+                unexp,
+                &"a percentage from 1 to 100",
+            ));
+        }
+        Ok(Percentage(value))
+    }
+}
+
+#[test]
+fn custom_zero_is_not_nonzero() {
+    let result = deserialize::<Percentage, _, byteorder::NetworkEndian>(&[0u8][..]);
+    assert!(matches!(result, Err(DeserializeError::Custom)));
+    let result = deserialize::<Percentage, _, byteorder::NetworkEndian>(&[50u8][..]);
+    assert!(matches!(result, Ok(Percentage(50))));
+}
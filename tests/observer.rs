@@ -0,0 +1,78 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+#[derive(Deserialize, PartialEq, Debug)]
+struct Packet<'a> {
+    id: u8,
+    flags: (bool, i16),
+    name: &'a str,
+    payload: &'a [u8],
+    ratio: f32,
+    checksum: Option<u32>,
+}
+
+#[test]
+fn observe_struct() {
+    use serde::Deserialize;
+
+    let buffer = [
+        7, // id
+        1, 0xFF, 0xFE, // flags
+        0, 2, b'h', b'i', // name
+        0, 3, 1, 2, 3, // payload
+        0x3F, 0x80, 0, 0, // ratio
+        1, 0, 0, 0x12, 0x34, // checksum
+    ];
+    let mut observed = Vec::new();
+    let mut observer = |value: Observed| observed.push(format!("{:?}", value));
+    let mut deserializer =
+        Deserializer::<_, byteorder::NetworkEndian>::new(&buffer[..], Config::new())
+            .with_observer(&mut observer);
+    let packet = Packet::deserialize(&mut deserializer).unwrap();
+    assert_eq!(
+        Packet {
+            id: 7,
+            flags: (true, -2),
+            name: "hi",
+            payload: &[1, 2, 3],
+            ratio: 1.0,
+            checksum: Some(0x1234),
+        },
+        packet
+    );
+    assert_eq!(
+        vec![
+            "U8(7)",
+            "Bool(true)",
+            "I16(-2)",
+            "Str(\"hi\")",
+            "Bytes([1, 2, 3])",
+            "F32(1.0)",
+            "U32(4660)",
+        ],
+        observed
+    );
+}
+
+#[test]
+fn observe_copied_strings() {
+    use serde::Deserialize;
+
+    let buffer = [0, 2, b'h', b'i', 0, 1, b'!'];
+    let mut scratch = [0u8; 4];
+    let mut observed = Vec::new();
+    let mut observer = |value: Observed| {
+        if let Observed::Str(s) = value {
+            observed.push(s.to_string());
+        }
+    };
+    let mut deserializer =
+        Deserializer::<_, byteorder::NetworkEndian>::new(&buffer[..], Config::new())
+            .with_scratch(&mut scratch)
+            .with_observer(&mut observer);
+    let value = <(String, String)>::deserialize(&mut deserializer).unwrap();
+    assert_eq!(("hi".to_string(), "!".to_string()), value);
+    assert_eq!(vec!["hi", "!"], observed);
+}
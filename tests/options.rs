@@ -0,0 +1,77 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+struct Marker;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Flags {
+    a: Option<Marker>,
+    b: Option<()>,
+    c: Option<Option<u8>>,
+}
+
+fn roundtrip<T: Serialize + DeserializeOwned + PartialEq + Debug>(
+    value: T,
+    config: Config,
+    expected: &[u8],
+) {
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(expected, writer.written_buffer());
+
+    let mut deserializer = Deserializer::<_, byteorder::NetworkEndian>::new(expected, config);
+    let deserialized = T::deserialize(&mut deserializer).unwrap();
+    assert_eq!(value, deserialized);
+    assert!(deserializer.into_reader().is_empty());
+}
+
+#[test]
+fn zero_sized() {
+    roundtrip(None::<()>, Config::new(), &[0]);
+    roundtrip(Some(()), Config::new(), &[1]);
+    roundtrip(None::<Marker>, Config::new(), &[0]);
+    roundtrip(Some(Marker), Config::new(), &[1]);
+    roundtrip(Some([Marker; 4]), Config::new(), &[1]);
+}
+
+#[test]
+fn nested() {
+    roundtrip(None::<Option<u8>>, Config::new(), &[0]);
+    roundtrip(Some(None::<u8>), Config::new(), &[1, 0]);
+    roundtrip(Some(Some(5u8)), Config::new(), &[1, 1, 5]);
+    roundtrip(Some(Some(Some(()))), Config::new(), &[1, 1, 1]);
+    roundtrip(Some(Some(None::<()>)), Config::new(), &[1, 1, 0]);
+}
+
+#[test]
+fn in_struct() {
+    let value = Flags {
+        a: Some(Marker),
+        b: None,
+        c: Some(None),
+    };
+    roundtrip(value, Config::new(), &[1, 0, 1, 0]);
+
+    // Every presence byte of the nested option is packed separately
+    let value = Flags {
+        a: Some(Marker),
+        b: None,
+        c: Some(Some(7)),
+    };
+    roundtrip(value, Config::new().bit_pack_tags(), &[0b1101, 7]);
+}
+
+#[test]
+fn invalid_presence_byte() {
+    let result = deserialize::<Option<Option<u8>>, _, byteorder::NetworkEndian>(&[1, 2][..]);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::InvalidOptionValue(2))
+    ));
+}
@@ -0,0 +1,90 @@
+use bincode_embedded::*;
+use serde::ser::{Serialize, SerializeStructVariant, SerializeTupleVariant, Serializer};
+use std::collections::BTreeMap;
+
+/// Serialize the value and return the written length, or `None` if a length overflowed.
+fn serialize_len<T: Serialize>(value: &T) -> Option<usize> {
+    let mut buffer = vec![0u8; 70_000];
+    let mut writer = BufferWriter::new(&mut buffer);
+    match serialize::<_, _, byteorder::NetworkEndian>(value, &mut writer) {
+        Ok(()) => Some(writer.written_len()),
+        Err(SerializeError::LengthOverflow) => None,
+        Err(e) => panic!("Unexpected error {:?}", e),
+    }
+}
+
+fn assert_overflow<T: Serialize>(value: &T) {
+    assert_eq!(None, serialize_len(value));
+}
+
+#[test]
+fn str_len() {
+    let max = "a".repeat(u16::MAX as usize);
+    assert_eq!(Some(2 + max.len()), serialize_len(&max.as_str()));
+    assert_overflow(&format!("{}a", max).as_str());
+}
+
+#[test]
+fn slice_len() {
+    let max = vec![0u8; u16::MAX as usize];
+    assert!(serialize_len(&serde_bytes::Bytes::new(&max)).is_some());
+    let too_long = vec![0u8; u16::MAX as usize + 1];
+    assert_overflow(&serde_bytes::Bytes::new(&too_long));
+}
+
+#[test]
+fn seq_len() {
+    assert!(serialize_len(&vec![0u8; u16::MAX as usize]).is_some());
+    assert_overflow(&vec![0u8; u16::MAX as usize + 1]);
+}
+
+#[test]
+fn map_len() {
+    let mut map: BTreeMap<u16, u8> = (0..255).map(|k| (k, 0)).collect();
+    assert!(serialize_len(&map).is_some());
+    map.insert(255, 0);
+    assert_overflow(&map);
+}
+
+/// An enum with a variant at the given index, which can not be written with `#[derive]` without
+/// declaring all the variants before it.
+enum Variant {
+    Unit(u32),
+    Newtype(u32),
+    Tuple(u32),
+    Struct(u32),
+}
+
+impl Serialize for Variant {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Variant::Unit(index) => serializer.serialize_unit_variant("Variant", index, "V"),
+            Variant::Newtype(index) => {
+                serializer.serialize_newtype_variant("Variant", index, "V", &1u8)
+            }
+            Variant::Tuple(index) => {
+                let mut tuple = serializer.serialize_tuple_variant("Variant", index, "V", 1)?;
+                tuple.serialize_field(&1u8)?;
+                tuple.end()
+            }
+            Variant::Struct(index) => {
+                let mut s = serializer.serialize_struct_variant("Variant", index, "V", 1)?;
+                s.serialize_field("a", &1u8)?;
+                s.end()
+            }
+        }
+    }
+}
+
+#[test]
+fn variant_index() {
+    for variant in [
+        Variant::Unit,
+        Variant::Newtype,
+        Variant::Tuple,
+        Variant::Struct,
+    ] {
+        assert!(serialize_len(&variant(255)).is_some());
+        assert_overflow(&variant(256));
+    }
+}
@@ -0,0 +1,312 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Frame<'a> {
+    id: u16,
+    name: &'a str,
+    data: &'a [u8],
+}
+
+#[test]
+fn stack_reader() {
+    let value = Frame {
+        id: 0x0102,
+        name: "sensor",
+        data: &[1, 2, 3],
+    };
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+
+    let reader = StackReader::<128>::from_slice(writer.written_buffer()).unwrap();
+    let deserialized: Frame = deserialize::<_, _, byteorder::NetworkEndian>(&reader).unwrap();
+    assert_eq!(value, deserialized);
+    assert_eq!(0, reader.remaining());
+
+    // The borrowed fields point into the reader itself
+    let start = &reader as *const _ as usize;
+    let end = start + std::mem::size_of_val(&reader);
+    for ptr in [deserialized.name.as_ptr(), deserialized.data.as_ptr()] {
+        assert!((start..end).contains(&(ptr as usize)));
+    }
+}
+
+#[test]
+fn stack_reader_errors() {
+    assert!(matches!(
+        StackReader::<2>::from_slice(&[1, 2, 3]),
+        Err(StackReaderError::BufferTooSmall)
+    ));
+
+    let reader = StackReader::new([0, 5, b'a', 0], 3);
+    match deserialize::<&str, _, byteorder::NetworkEndian>(&reader) {
+        Err(DeserializeError::Read(StackReaderError::UnexpectedEnd)) => {}
+        other => panic!("Expected UnexpectedEnd, got {:?}", other),
+    }
+}
+
+#[test]
+fn remaining_bytes() {
+    let value = Frame {
+        id: 1,
+        name: "ab",
+        data: &[3],
+    };
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(9, writer.written_len());
+
+    // The rest of the buffer is padding
+    let (deserialized, reader) =
+        deserialize_and_return::<Frame, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+    assert_eq!(value, deserialized);
+    assert_eq!(7, reader.remaining());
+
+    let reader = StackReader::<16>::from_slice(&buffer[..10]).unwrap();
+    let _: Frame = deserialize::<_, _, byteorder::NetworkEndian>(&reader).unwrap();
+    assert_eq!(1, reader.remaining());
+}
+
+#[test]
+fn deserialize_exact() {
+    let buffer = [0, 1, 0, 2, b'a', b'b', 0, 1, 3, 0, 0];
+
+    let value: Frame =
+        bincode_embedded::deserialize_exact::<_, _, byteorder::NetworkEndian>(&buffer[..9])
+            .unwrap();
+    assert_eq!("ab", value.name);
+
+    let result =
+        bincode_embedded::deserialize_exact::<Frame, _, byteorder::NetworkEndian>(&buffer[..]);
+    match result {
+        Err(DeserializeError::TrailingBytes(2)) => {}
+        other => panic!("Expected TrailingBytes, got {:?}", other),
+    }
+}
+
+#[test]
+fn frame_reader() {
+    let mut buffer = [0u8; 64];
+    let mut writer = BufferWriter::new(&mut buffer);
+    for value in [1u32, 2, 3] {
+        let mut frame = [0u8; 8];
+        let mut frame_writer = BufferWriter::new(&mut frame);
+        serialize::<_, _, byteorder::LittleEndian>(&(value, "ab"), &mut frame_writer).unwrap();
+        let frame = serde_bytes::Bytes::new(frame_writer.written_buffer());
+        serialize::<_, _, byteorder::LittleEndian>(&frame, &mut writer).unwrap();
+    }
+    // A frame that claims 9 bytes, but only has 2
+    serialize::<_, _, byteorder::LittleEndian>(&9u16, &mut writer).unwrap();
+    serialize::<_, _, byteorder::LittleEndian>(&[0u8; 2], &mut writer).unwrap();
+    let len = writer.written_len();
+
+    let mut frames = FrameReader::<byteorder::LittleEndian>::new(&buffer[..len]);
+    for expected in [1u32, 2, 3] {
+        let frame = frames.next().unwrap().unwrap();
+        let value: (u32, &str) =
+            bincode_embedded::deserialize_exact::<_, _, byteorder::LittleEndian>(frame).unwrap();
+        assert_eq!((expected, "ab"), value);
+    }
+    assert_eq!(4, frames.remaining_buffer().len());
+    assert_eq!(
+        Some(Err(FrameError::TruncatedFrame {
+            len: 9,
+            remaining: 2
+        })),
+        frames.next()
+    );
+    assert_eq!(None, frames.next());
+
+    // The length prefix itself can be cut off as well
+    let mut frames = FrameReader::<byteorder::LittleEndian>::new(&[1, 0, 5, 1]);
+    assert_eq!(Some(Ok(&[5][..])), frames.next());
+    assert_eq!(Some(Err(FrameError::TruncatedLength)), frames.next());
+    assert_eq!(None, frames.next());
+}
+
+#[test]
+fn frame_reader_length_endian() {
+    let config = Config::new().length_endian(RuntimeEndian::Big);
+    let frames: Vec<_> =
+        FrameReader::<byteorder::LittleEndian>::with_config(&[0, 1, 5, 0, 0, 0, 2, 6, 7], config)
+            .collect();
+    assert_eq!(vec![Ok(&[5][..]), Ok(&[][..]), Ok(&[6, 7][..])], frames);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Traced<'a> {
+    kind: u8,
+    id: u32,
+    name: &'a str,
+    value: Option<i16>,
+}
+
+#[test]
+fn tracing_reader_and_writer() {
+    let value = Traced {
+        kind: 1,
+        id: 2,
+        name: "abc",
+        value: Some(-3),
+    };
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let mut writes = Vec::new();
+    let tracing = TracingWriter::new(&mut writer, |op: &str, len| {
+        writes.push((op.to_owned(), len))
+    });
+    serialize::<_, _, byteorder::NetworkEndian>(&value, tracing).unwrap();
+    let len = writer.written_len();
+    let writes: Vec<_> = writes.iter().map(|(op, len)| (op.as_str(), *len)).collect();
+    assert_eq!(
+        vec![
+            ("write_all", 1),
+            ("write_all", 4),
+            ("write_all", 2),
+            ("write_all", 3),
+            ("write_all", 1),
+            ("write_all", 2)
+        ],
+        writes
+    );
+
+    let mut reads = Vec::new();
+    let tracing = TracingReader::new(&buffer[..len], |op: &str, len| {
+        reads.push((op.to_owned(), len))
+    });
+    let deserialized: Traced = deserialize::<_, _, byteorder::NetworkEndian>(tracing).unwrap();
+    assert_eq!(value, deserialized);
+    let reads: Vec<_> = reads.iter().map(|(op, len)| (op.as_str(), *len)).collect();
+    assert_eq!(
+        vec![
+            ("read", 1),
+            ("read_array", 4),
+            ("read_array", 2),
+            ("read_range", 3),
+            ("read", 1),
+            ("read_array", 2)
+        ],
+        reads
+    );
+}
+
+#[test]
+fn chain_reader() {
+    let value = Frame {
+        id: 0x0102,
+        name: "sensor",
+        data: &[1, 2, 3],
+    };
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    let bytes = writer.written_buffer();
+
+    // The header ends after the length prefix of `name`, the id is split over both readers
+    for split in [1, 4] {
+        let (header, body) = bytes.split_at(split);
+        let reader = ChainReader::new(header, body);
+        let (deserialized, reader) =
+            deserialize_and_return::<Frame, _, byteorder::NetworkEndian>(reader).unwrap();
+        assert_eq!(value, deserialized);
+        assert_eq!(0, reader.remaining());
+
+        // Borrowed values point into the buffers
+        let name = deserialized.name.as_ptr() as usize;
+        assert_eq!(body.as_ptr() as usize + 4 - split, name);
+    }
+}
+
+#[test]
+fn chain_reader_spanning_range() {
+    let (header, body) = (&[0, 3, b'a'][..], &[b'b', b'c', 0, 1, b'd'][..]);
+
+    // The string spans both readers, which needs a scratch buffer
+    let reader = ChainReader::new(header, body);
+    let result = deserialize::<&str, _, byteorder::NetworkEndian>(reader);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::Read(ChainReaderError::ScratchTooSmall {
+            len: 3,
+            scratch: 0
+        }))
+    ));
+
+    // Every spanning read takes up part of the scratch buffer
+    let mut scratch = [0u8; 4];
+    let reader = ChainReader::new(header, body).with_scratch(&mut scratch);
+    let value: (&str, &str) = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+    assert_eq!(("abc", "d"), value);
+    assert_eq!(b"abc", &scratch[..3]);
+
+    let mut scratch = [0u8; 2];
+    let reader = ChainReader::new(header, body).with_scratch(&mut scratch);
+    let result = deserialize::<&str, _, byteorder::NetworkEndian>(reader);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::Read(ChainReaderError::ScratchTooSmall {
+            len: 3,
+            scratch: 2
+        }))
+    ));
+}
+
+#[test]
+fn xor_roundtrip() {
+    let value = Frame {
+        id: 0x0102,
+        name: "sensor",
+        data: &[1, 2, 3],
+    };
+    let key = [0x13, 0x37, 0xC0];
+
+    let mut plain = [0u8; 32];
+    let len = to_bytes::<_, byteorder::NetworkEndian>(&value, &mut plain).unwrap();
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, XorWriter::new(&mut writer, key)).unwrap();
+    assert_eq!(len, writer.written_len());
+    assert_ne!(&plain[..len], writer.written_buffer());
+
+    // The borrowed name and data are decrypted into the scratch buffer
+    let mut scratch = [0u8; 9];
+    let reader = XorReader::new(&buffer[..len], key, &mut scratch);
+    let deserialized: Frame = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+    assert_eq!(value, deserialized);
+
+    let mut scratch = [0u8; 8];
+    let reader = XorReader::new(&buffer[..len], key, &mut scratch);
+    let result = deserialize::<Frame, _, byteorder::NetworkEndian>(reader);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::Read(XorReaderError::ScratchTooSmall {
+            len: 3,
+            scratch: 2
+        }))
+    ));
+
+    let mut scratch = [0u8; 9];
+    let reader = XorReader::new(&buffer[..len - 1], key, &mut scratch);
+    let result = deserialize::<Frame, _, byteorder::NetworkEndian>(reader);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::Read(XorReaderError::UnexpectedEnd))
+    ));
+
+    // NUL-terminated strings are found in the decrypted input
+    let config = Config::new().cstr_mode();
+    let mut writer = BufferWriter::new(&mut buffer);
+    let writer = XorWriter::new(&mut writer, key);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&("ab", "c"), writer, config).unwrap();
+    let mut scratch = [0u8; 3];
+    let reader = XorReader::new(&buffer[..5], key, &mut scratch);
+    let strings: (&str, &str) =
+        deserialize_with_config::<_, _, byteorder::NetworkEndian>(reader, config).unwrap();
+    assert_eq!(("ab", "c"), strings);
+}
@@ -0,0 +1,63 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+#[derive(Deserialize)]
+struct Reading {
+    id: u8,
+    value: u16,
+}
+
+#[derive(Deserialize)]
+struct Swapped {
+    value: u16,
+    id: u8,
+}
+
+#[derive(Deserialize)]
+struct Renamed {
+    sensor: u8,
+    temperature: u16,
+}
+
+#[derive(Deserialize)]
+struct Wider {
+    id: u8,
+    value: u32,
+}
+
+#[derive(Deserialize)]
+struct Message<'a> {
+    name: &'a str,
+    readings: Option<[Reading; 2]>,
+}
+
+#[derive(Deserialize)]
+struct Node {
+    value: u8,
+    next: Option<Box<Node>>,
+}
+
+#[test]
+fn schema_hash_layout() {
+    let hash = schema_hash::<Reading>();
+    assert_eq!(hash, schema_hash::<Reading>());
+    assert_eq!(hash, schema_hash::<Renamed>());
+    assert_eq!(hash, schema_hash::<(u8, u16)>());
+
+    assert_ne!(hash, schema_hash::<Swapped>());
+    assert_ne!(hash, schema_hash::<Wider>());
+    assert_ne!(hash, schema_hash::<(u8, u16, u8)>());
+    assert_ne!(schema_hash::<u16>(), schema_hash::<i16>());
+    assert_ne!(schema_hash::<Option<u8>>(), schema_hash::<u8>());
+    assert_ne!(
+        schema_hash::<Message>(),
+        schema_hash::<(&str, [Reading; 2])>()
+    );
+}
+
+#[test]
+fn schema_hash_recursive() {
+    assert_ne!(schema_hash::<Node>(), schema_hash::<(u8, Option<u8>)>());
+}
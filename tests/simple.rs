@@ -67,3 +67,684 @@ fn simple_tuple() {
         deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
     assert_eq!(s, deserialized);
 }
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct EmptyFieldStruct<'a> {
+    before: u8,
+    name: &'a str,
+    after: u8,
+}
+
+#[test]
+fn empty_str_and_slice() {
+    let s = ("", &b""[..], 7u8);
+
+    let mut buffer = [0u8; 100];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&s, &mut writer).unwrap();
+
+    // type         size
+    // &str         2 (len) + 0 (str content)
+    // &[u8]        2 (len) + 0 (byte content)
+    // u8           1
+    assert_eq!(&[0, 0, 0, 0, 7], writer.written_buffer());
+
+    // The trailing u8 is only read correctly if the empty values did not advance the reader
+    let deserialized: (&str, &[u8], u8) =
+        deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..5]).unwrap();
+    assert_eq!(s, deserialized);
+}
+
+#[test]
+fn empty_str_field() {
+    let s = EmptyFieldStruct {
+        before: 1,
+        name: "",
+        after: 2,
+    };
+
+    let mut buffer = [0u8; 100];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&s, &mut writer).unwrap();
+    assert_eq!(&[1, 0, 0, 2], writer.written_buffer());
+
+    let deserialized: EmptyFieldStruct =
+        deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..4]).unwrap();
+    assert_eq!(s, deserialized);
+}
+
+/// A reader that refuses zero-length reads, like some hand-written readers do.
+struct NoEmptyReads<'a>(&'a [u8]);
+
+impl<'a> CoreRead<'a> for NoEmptyReads<'a> {
+    type Error = ();
+
+    fn read_range(&mut self, len: usize) -> Result<&'a [u8], Self::Error> {
+        if len == 0 {
+            return Err(());
+        }
+        self.0.read_range(len)
+    }
+}
+
+#[test]
+fn empty_str_does_not_read() {
+    let buffer = [0u8, 0, 0, 0];
+    let deserialized: (&str, &[u8]) =
+        deserialize::<_, _, byteorder::NetworkEndian>(NoEmptyReads(&buffer[..])).unwrap();
+    assert_eq!(("", &b""[..]), deserialized);
+}
+
+#[test]
+fn simple_map() {
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(1u8, 10u16);
+    map.insert(2u8, 20u16);
+
+    let mut buffer = [0u8; 100];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&map, &mut writer).unwrap();
+
+    // type         size
+    // length       1
+    // entries      2 * (1 + 2)
+    assert_eq!(&[2, 1, 0, 10, 2, 0, 20], writer.written_buffer());
+
+    let deserialized: std::collections::BTreeMap<u8, u16> =
+        deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+    assert_eq!(map, deserialized);
+}
+
+#[test]
+fn deserialize_multiple_frames() {
+    let first = (1u8, "first");
+    let second = (2u8, "second");
+
+    let mut buffer = [0u8; 100];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&first, &mut writer).unwrap();
+    serialize::<_, _, byteorder::NetworkEndian>(&second, &mut writer).unwrap();
+    let len = writer.written_len();
+
+    let (deserialized, reader): ((u8, &str), _) =
+        deserialize_and_return::<_, _, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+    assert_eq!(first, deserialized);
+    assert_eq!(1 + 2 + 6, reader.len());
+
+    let (deserialized, reader): ((u8, &str), _) =
+        deserialize_and_return::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+    assert_eq!(second, deserialized);
+    assert!(reader.is_empty());
+}
+
+#[test]
+fn char_widths() {
+    for c in ['a', 'é', '€', '😀'] {
+        let mut buffer = [0u8; 4];
+        let mut writer = BufferWriter::new(&mut buffer);
+        serialize::<_, _, byteorder::NetworkEndian>(&c, &mut writer).unwrap();
+        assert_eq!(c.len_utf8(), writer.written_len());
+        let len = writer.written_len();
+
+        let deserialized: char =
+            deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+        assert_eq!(c, deserialized);
+    }
+}
+
+#[test]
+fn truncated_char() {
+    // '€' is encoded in 3 bytes, '😀' in 4 bytes
+    let three: &[u8] = &[0xE2, 0x82];
+    let four: &[u8] = &[0xF0, 0x9F, 0x98];
+
+    for buffer in [three, four] {
+        match deserialize::<char, _, byteorder::NetworkEndian>(buffer) {
+            Err(DeserializeError::Read(())) => {}
+            other => panic!("Expected a read error, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn truncated_slice() {
+    let buffer: &[u8] = &[0, 5, b'a', b'b'];
+    match deserialize::<&str, _, byteorder::NetworkEndian>(buffer) {
+        Err(DeserializeError::Read(())) => {}
+        other => panic!("Expected a read error, got {:?}", other),
+    }
+}
+
+#[test]
+fn duration() {
+    use core::time::Duration;
+
+    let cases = [
+        (Duration::ZERO, [0u8; 12]),
+        (
+            Duration::from_micros(1_500),
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0x16, 0xE3, 0x60],
+        ),
+        (
+            Duration::new(90_061, 7),
+            [0, 0, 0, 0, 0, 0x01, 0x5F, 0xCD, 0, 0, 0, 7],
+        ),
+    ];
+
+    for (duration, expected) in cases {
+        // A duration is a struct of `secs: u64` and `nanos: u32`
+        let mut buffer = [0u8; 12];
+        let mut writer = BufferWriter::new(&mut buffer);
+        serialize::<_, _, byteorder::NetworkEndian>(&duration, &mut writer).unwrap();
+        assert_eq!(&expected, writer.written_buffer());
+
+        let deserialized: Duration =
+            deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+        assert_eq!(duration, deserialized);
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct MultiBorrow<'a> {
+    name: &'a str,
+    data: &'a [u8],
+    tag: &'a str,
+}
+
+#[test]
+fn multiple_borrows() {
+    let value = MultiBorrow {
+        name: "sensor",
+        data: &[1, 2, 3, 4],
+        tag: "temp",
+    };
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    let len = writer.written_len();
+
+    let input = &buffer[..len];
+    let deserialized: MultiBorrow = deserialize::<_, _, byteorder::NetworkEndian>(input).unwrap();
+    assert_eq!(value, deserialized);
+
+    // Every field points into the input buffer, right after its length prefix
+    let start = input.as_ptr() as usize;
+    assert_eq!(start + 2, deserialized.name.as_ptr() as usize);
+    assert_eq!(start + 10, deserialized.data.as_ptr() as usize);
+    assert_eq!(start + 16, deserialized.tag.as_ptr() as usize);
+}
+
+#[test]
+fn unprefixed_str() {
+    use serde::{Deserialize, Serialize};
+
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let mut serializer = Serializer::<_, byteorder::NetworkEndian>::new(&mut writer, Config::new());
+    1u8.serialize(&mut serializer).unwrap();
+    serializer.serialize_str_unprefixed("CALLSIGN").unwrap();
+    0x0203u16.serialize(&mut serializer).unwrap();
+    serializer.finish().unwrap();
+    assert_eq!(b"\x01CALLSIGN\x02\x03", writer.written_buffer());
+    let len = writer.written_len();
+
+    let mut deserializer =
+        Deserializer::<_, byteorder::NetworkEndian>::new(&buffer[..len], Config::new());
+    assert_eq!(1, u8::deserialize(&mut deserializer).unwrap());
+    assert_eq!("CALLSIGN", deserializer.deserialize_str_len(8).unwrap());
+    assert_eq!(0x0203, u16::deserialize(&mut deserializer).unwrap());
+    assert!(deserializer.into_reader().is_empty());
+
+    // An empty string ends the byte of packed tags on both sides
+    let config = Config::new().bit_pack_tags();
+    let mut writer = BufferWriter::new(&mut buffer);
+    let mut serializer = Serializer::<_, byteorder::NetworkEndian>::new(&mut writer, config);
+    true.serialize(&mut serializer).unwrap();
+    serializer.serialize_str_unprefixed("").unwrap();
+    false.serialize(&mut serializer).unwrap();
+    serializer.finish().unwrap();
+    assert_eq!(&[1, 0], writer.written_buffer());
+
+    let mut deserializer = Deserializer::<_, byteorder::NetworkEndian>::new(&buffer[..2], config);
+    assert!(bool::deserialize(&mut deserializer).unwrap());
+    assert_eq!("", deserializer.deserialize_str_len(0).unwrap());
+    assert!(!bool::deserialize(&mut deserializer).unwrap());
+    assert!(deserializer.into_reader().is_empty());
+}
+
+#[test]
+fn read_until() {
+    let buffer = b"key\0a longer value that spans words\0\x07";
+    let mut deserializer =
+        Deserializer::<_, byteorder::NetworkEndian>::new(&buffer[..], Config::new());
+    assert_eq!(b"key", deserializer.deserialize_until(0).unwrap());
+    assert_eq!(
+        &b"a longer value that spans words"[..],
+        deserializer.deserialize_until(0).unwrap()
+    );
+    match deserializer.deserialize_until(0) {
+        Err(DeserializeError::DelimiterNotFound) => {}
+        other => panic!("Expected DelimiterNotFound, got {:?}", other),
+    }
+}
+
+#[test]
+fn read_until_every_position() {
+    let mut buffer = [b'x'; 40];
+    for index in 0..buffer.len() {
+        buffer[index] = b'\n';
+        let mut reader = &buffer[..];
+        assert_eq!(index, reader.read_until(b'\n').unwrap().unwrap().len());
+        assert_eq!(buffer.len() - index - 1, reader.len());
+        buffer[index] = b'x';
+    }
+    assert_eq!(None, (&buffer[..]).read_until(b'\n').unwrap());
+}
+
+/// Collects the elements of a sequence.
+struct CollectVisitor;
+
+impl<'de> serde::de::Visitor<'de> for CollectVisitor {
+    type Value = Vec<u16>;
+
+    fn expecting(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt, "a sequence")
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+#[test]
+fn seq_without_len() {
+    use serde::{Deserialize, Serialize};
+
+    // The element count is stored in a header field instead of a length prefix
+    let values = [0x0102u16, 0x0304, 0x0506];
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let mut serializer = Serializer::<_, byteorder::NetworkEndian>::new(&mut writer, Config::new());
+    (values.len() as u8).serialize(&mut serializer).unwrap();
+    serializer.serialize_seq_no_len(values.iter()).unwrap();
+    0xFFu8.serialize(&mut serializer).unwrap();
+    serializer.finish().unwrap();
+    assert_eq!(&[3, 1, 2, 3, 4, 5, 6, 0xFF], writer.written_buffer());
+    let len = writer.written_len();
+
+    let mut deserializer =
+        Deserializer::<_, byteorder::NetworkEndian>::new(&buffer[..len], Config::new());
+    let count = u8::deserialize(&mut deserializer).unwrap() as usize;
+    let deserialized = deserializer
+        .deserialize_seq_with_len(count, CollectVisitor)
+        .unwrap();
+    assert_eq!(&values[..], &deserialized[..]);
+    assert_eq!(0xFF, u8::deserialize(&mut deserializer).unwrap());
+    assert!(deserializer.into_reader().is_empty());
+}
+
+/// Only reads the first element of a sequence.
+#[derive(Debug)]
+struct FirstOnly(u16);
+
+impl<'de> serde::Deserialize<'de> for FirstOnly {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FirstVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for FirstVisitor {
+            type Value = FirstOnly;
+
+            fn expecting(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(fmt, "a sequence")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                Ok(FirstOnly(seq.next_element()?.unwrap_or(0)))
+            }
+        }
+
+        deserializer.deserialize_seq(FirstVisitor)
+    }
+}
+
+#[test]
+fn seq_visitor_stops_early() {
+    use serde::Deserialize;
+
+    let buffer = [0, 3, 0, 1, 0, 2, 0, 3];
+    let mut deserializer =
+        Deserializer::<_, byteorder::NetworkEndian>::new(&buffer[..], Config::new());
+    match FirstOnly::deserialize(&mut deserializer) {
+        Err(DeserializeError::UnconsumedElements(2)) => {}
+        other => panic!("Expected UnconsumedElements, got {:?}", other),
+    }
+    // The reader is left after the first element
+    assert_eq!(&[0, 2, 0, 3], deserializer.into_reader());
+
+    let buffer = [0, 1, 0, 7];
+    let value: FirstOnly = deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+    assert_eq!(7, value.0);
+}
+
+#[test]
+fn special_floats() {
+    let nan = f32::from_bits(0xFFC0_0001);
+    let value = (f32::INFINITY, f32::NEG_INFINITY, -0.0f32, nan, f64::NAN);
+
+    let mut buffer = [0u8; 24];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(
+        &[
+            0x7F, 0x80, 0, 0, // +inf
+            0xFF, 0x80, 0, 0, // -inf
+            0x80, 0, 0, 0, // -0.0
+            0xFF, 0xC0, 0, 1, // NaN with a payload
+            0x7F, 0xF8, 0, 0, 0, 0, 0, 0, // NaN
+        ],
+        writer.written_buffer()
+    );
+
+    let deserialized: (f32, f32, f32, f32, f64) =
+        deserialize::<_, _, byteorder::NetworkEndian>(&buffer[..]).unwrap();
+    assert_eq!(value.0, deserialized.0);
+    assert_eq!(value.1, deserialized.1);
+    assert_eq!(value.2.to_bits(), deserialized.2.to_bits());
+    assert_eq!(nan.to_bits(), deserialized.3.to_bits());
+    assert!(deserialized.4.is_nan());
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Large {
+    id: u32,
+    samples: [u16; 16],
+    label: Option<u8>,
+}
+
+#[test]
+fn deserialize_in_place() {
+    let value = Large {
+        id: 7,
+        samples: [0x0102; 16],
+        label: None,
+    };
+    let mut buffer = [0u8; 64];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+
+    let mut target = Large {
+        id: 1,
+        samples: [0xFFFF; 16],
+        label: Some(3),
+    };
+    bincode_embedded::deserialize_in_place::<_, _, byteorder::NetworkEndian>(
+        &buffer[..],
+        &mut target,
+    )
+    .unwrap();
+    assert_eq!(value, target);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct StatusV1 {
+    battery: u8,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct StatusV2 {
+    battery: u8,
+    temperature: i16,
+}
+
+#[test]
+fn versioned() {
+    let value = StatusV2 {
+        battery: 80,
+        temperature: -5,
+    };
+    let mut buffer = [0u8; 8];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_versioned::<_, _, byteorder::NetworkEndian>(2, &value, &mut writer).unwrap();
+    assert_eq!(&[2, 80, 0xFF, 0xFB], writer.written_buffer());
+
+    let (version, deserialized) =
+        deserialize_versioned::<StatusV2, _, byteorder::NetworkEndian>(&buffer[..4]).unwrap();
+    assert_eq!(2, version);
+    assert_eq!(value, deserialized);
+
+    // An older message takes a different branch
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_versioned::<_, _, byteorder::NetworkEndian>(
+        1,
+        &StatusV1 { battery: 50 },
+        &mut writer,
+    )
+    .unwrap();
+    let len = writer.written_len();
+    let (version, reader) =
+        deserialize_and_return::<u8, _, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+    let upgraded = match version {
+        1 => {
+            let old: StatusV1 = deserialize::<_, _, byteorder::NetworkEndian>(reader).unwrap();
+            StatusV2 {
+                battery: old.battery,
+                temperature: 0,
+            }
+        }
+        _ => panic!("Unexpected version {}", version),
+    };
+    assert_eq!(50, upgraded.battery);
+}
+
+#[test]
+fn magic() {
+    let value = StatusV2 {
+        battery: 80,
+        temperature: -5,
+    };
+    let mut buffer = [0u8; 8];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_magic::<_, _, byteorder::NetworkEndian>(b"BE1", &value, &mut writer).unwrap();
+    assert_eq!(b"BE1\x50\xFF\xFB", writer.written_buffer());
+
+    let deserialized =
+        deserialize_with_magic::<StatusV2, _, byteorder::NetworkEndian>(b"BE1", &buffer[..6])
+            .unwrap();
+    assert_eq!(value, deserialized);
+
+    // A corrupted magic is rejected before the body is read, even if the body is cut off
+    buffer[1] = b'X';
+    let result =
+        deserialize_with_magic::<StatusV2, _, byteorder::NetworkEndian>(b"BE1", &buffer[..3]);
+    assert!(matches!(result, Err(DeserializeError::BadMagic)));
+
+    // A frame that is shorter than the magic is a read error
+    let result =
+        deserialize_with_magic::<StatusV2, _, byteorder::NetworkEndian>(b"BE1", &buffer[..2]);
+    assert!(matches!(result, Err(DeserializeError::Read(()))));
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct ByteFrame {
+    kind: u8,
+    flags: [u8; 3],
+    ack: bool,
+    offset: i8,
+}
+
+#[test]
+fn bytes_only() {
+    let value = ByteFrame {
+        kind: 1,
+        flags: [2, 3, 4],
+        ack: true,
+        offset: -1,
+    };
+
+    let mut buffer = [0u8; 8];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_bytes_only(&value, &mut writer).unwrap();
+    assert_eq!(&[1, 2, 3, 4, 1, 0xFF], writer.written_buffer());
+
+    let deserialized: ByteFrame = deserialize_bytes_only(&buffer[..6]).unwrap();
+    assert_eq!(value, deserialized);
+
+    let mut writer = BufferWriter::new(&mut buffer);
+    match serialize_bytes_only(&(1u8, 2u16), &mut writer) {
+        Err(SerializeError::ByteOrderRequired) => {}
+        other => panic!("Expected ByteOrderRequired, got {:?}", other),
+    }
+    match deserialize_bytes_only::<&str, _>(&buffer[..]) {
+        Err(DeserializeError::ByteOrderRequired) => {}
+        other => panic!("Expected ByteOrderRequired, got {:?}", other),
+    }
+}
+
+#[test]
+fn f32_slice() {
+    let mut samples = [0f32; 256];
+    for (i, sample) in samples.iter_mut().enumerate() {
+        *sample = i as f32 * -0.25;
+    }
+
+    let mut buffer = [0u8; 1024];
+    let mut expected = [0u8; 1024];
+    for order in [RuntimeEndian::Big, RuntimeEndian::Little] {
+        let config = Config::new().value_endian(order);
+        let mut writer = BufferWriter::new(&mut buffer);
+        let mut serializer = Serializer::<_, byteorder::NetworkEndian>::new(&mut writer, config);
+        serializer.serialize_f32_slice(&samples).unwrap();
+        serializer.finish().unwrap();
+        let len = writer.written_len();
+        assert_eq!(1024, len);
+
+        // The bulk path writes the same bytes as serializing the values one by one. serde only
+        // implements arrays up to 32 elements, so larger arrays are done by hand.
+        let mut writer = BufferWriter::new(&mut expected);
+        let mut serializer = Serializer::<_, byteorder::NetworkEndian>::new(&mut writer, config);
+        for sample in &samples {
+            serde::Serialize::serialize(sample, &mut serializer).unwrap();
+        }
+        serializer.finish().unwrap();
+        assert_eq!(writer.written_buffer(), &buffer[..len]);
+
+        let mut value = [0f32; 256];
+        let mut deserializer =
+            Deserializer::<_, byteorder::NetworkEndian>::new(&buffer[..len], config);
+        for sample in value.iter_mut() {
+            *sample = serde::Deserialize::deserialize(&mut deserializer).unwrap();
+        }
+        assert_eq!(samples, value);
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Inner {
+    b: u8,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Flattened {
+    a: u8,
+    #[serde(flatten)]
+    inner: Inner,
+}
+
+#[test]
+fn flatten_not_supported() {
+    let value = Flattened {
+        a: 1,
+        inner: Inner { b: 2 },
+    };
+    let mut buffer = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer);
+    assert!(matches!(
+        result,
+        Err(SerializeError::NotSupported("flatten"))
+    ));
+
+    let buffer = [1u8, 2];
+    let result = deserialize::<Flattened, _, byteorder::NetworkEndian>(&buffer[..]);
+    assert!(matches!(
+        result,
+        Err(DeserializeError::NotSupported("flatten"))
+    ));
+}
+
+/// Multiplies every decoded `u16` by a factor that is only known at runtime.
+struct Scaled(u32);
+
+impl<'de> serde::de::DeserializeSeed<'de> for Scaled {
+    type Value = u32;
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<u32, D::Error> {
+        let raw = <u16 as serde::Deserialize>::deserialize(deserializer)?;
+        Ok(u32::from(raw) * self.0)
+    }
+}
+
+#[test]
+fn deserialize_seed_scales() {
+    let buffer = [0x01, 0x02];
+    let value =
+        deserialize_seed::<_, _, byteorder::NetworkEndian>(&buffer[..], Scaled(10)).unwrap();
+    assert_eq!(0x0102 * 10, value);
+    let value = deserialize_seed::<_, _, byteorder::LittleEndian>(&buffer[..], Scaled(3)).unwrap();
+    assert_eq!(0x0201 * 3, value);
+
+    // Errors of the reader are returned as usual
+    let result = deserialize_seed::<_, _, byteorder::NetworkEndian>(&buffer[..1], Scaled(1));
+    assert!(matches!(result, Err(DeserializeError::Read(_))));
+}
+
+/// A marker type that does not implement serde's traits itself.
+#[derive(PartialEq, Debug)]
+struct Celsius;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Unit;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Reading<U> {
+    value: u32,
+    unit: std::marker::PhantomData<U>,
+    marker: Unit,
+    flag: u8,
+}
+
+#[test]
+fn zero_sized_fields() {
+    let value = Reading::<Celsius> {
+        value: 0x0102_0304,
+        unit: std::marker::PhantomData,
+        marker: Unit,
+        flag: 5,
+    };
+    let mut buffer = [0u8; 8];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(&[1, 2, 3, 4, 5], writer.written_buffer());
+    assert_eq!(Ok(5), serialized_size(&value).map_err(|_| ()));
+
+    let deserialized: Reading<Celsius> =
+        deserialize_exact::<_, _, byteorder::NetworkEndian>(&buffer[..5]).unwrap();
+    assert_eq!(value, deserialized);
+
+    // Nothing is read for the markers on their own
+    let (marker, reader) =
+        deserialize_and_return::<std::marker::PhantomData<u32>, _, byteorder::NetworkEndian>(
+            &buffer[..1],
+        )
+        .unwrap();
+    assert_eq!(std::marker::PhantomData, marker);
+    assert_eq!(1, reader.len());
+}
@@ -0,0 +1,288 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+/// A reader that copies every read into a small scratch buffer, similar to a reader that is backed
+/// by a ring buffer. This reader can not return persistent data from `read_range`.
+struct ScratchReader<'s> {
+    source: &'s [u8],
+    scratch: [u8; 16],
+}
+
+impl<'s> ScratchReader<'s> {
+    fn new(source: &'s [u8]) -> Self {
+        Self {
+            source,
+            scratch: [0u8; 16],
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ScratchReaderError {
+    NotPersistent,
+    EndOfInput,
+}
+
+impl<'a> CoreRead<'a> for ScratchReader<'_> {
+    type Error = ScratchReaderError;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        Ok(self.read_transient(1)?[0])
+    }
+
+    fn read_range(&mut self, _len: usize) -> Result<&'a [u8], Self::Error> {
+        Err(ScratchReaderError::NotPersistent)
+    }
+
+    fn read_transient<'r>(&'r mut self, len: usize) -> Result<&'r [u8], Self::Error>
+    where
+        'a: 'r,
+    {
+        if len > self.source.len() {
+            return Err(ScratchReaderError::EndOfInput);
+        }
+        let scratch = &mut self.scratch[..len];
+        scratch.copy_from_slice(&self.source[..len]);
+        self.source = &self.source[len..];
+        Ok(scratch)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Named {
+    id: u8,
+    name: String,
+}
+
+#[test]
+fn copy_strings_from_scratch_reader() {
+    let value = Named {
+        id: 3,
+        name: String::from("sensor"),
+    };
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(
+        &value,
+        &mut writer,
+        Config::new().copy_strings(),
+    )
+    .unwrap();
+    let len = writer.written_len();
+
+    let deserialized: Named = deserialize_with_config::<_, _, byteorder::NetworkEndian>(
+        ScratchReader::new(&buffer[..len]),
+        Config::new().copy_strings(),
+    )
+    .unwrap();
+    assert_eq!(value, deserialized);
+}
+
+#[test]
+fn borrowed_strings_need_persistent_reader() {
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&"sensor", &mut writer).unwrap();
+    let len = writer.written_len();
+
+    let result =
+        deserialize::<String, _, byteorder::NetworkEndian>(ScratchReader::new(&buffer[..len]));
+    match result {
+        Err(DeserializeError::Read(ScratchReaderError::NotPersistent)) => {}
+        other => panic!("Expected a NotPersistent read error, got {:?}", other),
+    }
+}
+
+#[test]
+fn integers_from_scratch_reader() {
+    let value = (1u32, 0x0203_0405u32, u32::MAX, -5i64);
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    let len = writer.written_len();
+
+    let deserialized: (u32, u32, u32, i64) =
+        deserialize::<_, _, byteorder::NetworkEndian>(ScratchReader::new(&buffer[..len])).unwrap();
+    assert_eq!(value, deserialized);
+}
+
+/// A reader that only supports reading fixed-size arrays, and counts how often it is used.
+struct ArrayReader<'s> {
+    source: &'s [u8],
+    array_reads: usize,
+}
+
+impl<'a> CoreRead<'a> for ArrayReader<'_> {
+    type Error = ScratchReaderError;
+
+    fn read_range(&mut self, _len: usize) -> Result<&'a [u8], Self::Error> {
+        Err(ScratchReaderError::NotPersistent)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], Self::Error> {
+        if N > self.source.len() {
+            return Err(ScratchReaderError::EndOfInput);
+        }
+        let mut result = [0u8; N];
+        result.copy_from_slice(&self.source[..N]);
+        self.source = &self.source[N..];
+        self.array_reads += 1;
+        Ok(result)
+    }
+}
+
+#[test]
+fn integers_use_read_array() {
+    let value = [10u32, 20, 30];
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::LittleEndian>(&value, &mut writer).unwrap();
+    let len = writer.written_len();
+
+    let reader = ArrayReader {
+        source: &buffer[..len],
+        array_reads: 0,
+    };
+    let (deserialized, reader): ([u32; 3], _) =
+        deserialize_and_return::<_, _, byteorder::LittleEndian>(reader).unwrap();
+    assert_eq!(value, deserialized);
+    assert_eq!(3, reader.array_reads);
+}
+
+/// A reader that can only read one byte at a time, like a UART without a buffer.
+struct ByteReader<'s> {
+    source: &'s [u8],
+}
+
+impl<'a> CoreRead<'a> for ByteReader<'_> {
+    type Error = ScratchReaderError;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        let (first, rest) = self
+            .source
+            .split_first()
+            .ok_or(ScratchReaderError::EndOfInput)?;
+        self.source = rest;
+        Ok(*first)
+    }
+
+    fn read_range(&mut self, _len: usize) -> Result<&'a [u8], Self::Error> {
+        Err(ScratchReaderError::NotPersistent)
+    }
+
+    fn read_transient<'r>(&'r mut self, _len: usize) -> Result<&'r [u8], Self::Error>
+    where
+        'a: 'r,
+    {
+        Err(ScratchReaderError::NotPersistent)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Primitives {
+    a: u16,
+    b: i32,
+    c: f64,
+    d: Option<u64>,
+    e: bool,
+    f: [i16; 2],
+}
+
+#[test]
+fn primitives_from_byte_reader() {
+    let value = Primitives {
+        a: 1,
+        b: -2,
+        c: 3.5,
+        d: Some(4),
+        e: true,
+        f: [5, -6],
+    };
+
+    let mut buffer = [0u8; 64];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    let len = writer.written_len();
+
+    let deserialized: Primitives = deserialize::<_, _, byteorder::NetworkEndian>(ByteReader {
+        source: &buffer[..len],
+    })
+    .unwrap();
+    assert_eq!(value, deserialized);
+
+    // Sequences only need their length prefix, which is read byte by byte as well
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&vec![1u32, 2, 3], &mut writer).unwrap();
+    let len = writer.written_len();
+    let deserialized: Vec<u32> = deserialize::<_, _, byteorder::NetworkEndian>(ByteReader {
+        source: &buffer[..len],
+    })
+    .unwrap();
+    assert_eq!(vec![1, 2, 3], deserialized);
+
+    // Borrowed data still needs a persistent reader
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&"abc", &mut writer).unwrap();
+    let len = writer.written_len();
+    let result = deserialize::<&str, _, byteorder::NetworkEndian>(ByteReader {
+        source: &buffer[..len],
+    });
+    assert!(matches!(
+        result,
+        Err(DeserializeError::Read(ScratchReaderError::NotPersistent))
+    ));
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Packet {
+    id: u8,
+    name: String,
+    #[serde(with = "serde_bytes")]
+    payload: Vec<u8>,
+}
+
+#[test]
+fn scratch_from_byte_reader() {
+    let payload = [1u8, 2, 3, 4, 5];
+    let value = Packet {
+        id: 7,
+        name: String::from("abc"),
+        payload: payload.to_vec(),
+    };
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    let len = writer.written_len();
+
+    // The same scratch buffer is used for the string and the byte slice
+    let mut scratch = [0u8; 5];
+    let deserialized: Packet = deserialize_with_scratch::<_, _, byteorder::NetworkEndian>(
+        ByteReader {
+            source: &buffer[..len],
+        },
+        Config::default(),
+        &mut scratch,
+    )
+    .unwrap();
+    assert_eq!(value, deserialized);
+    assert_eq!(payload, scratch);
+
+    let mut scratch = [0u8; 4];
+    let result = deserialize_with_scratch::<Packet, _, byteorder::NetworkEndian>(
+        ByteReader {
+            source: &buffer[..len],
+        },
+        Config::default(),
+        &mut scratch,
+    );
+    assert!(matches!(
+        result,
+        Err(DeserializeError::ScratchTooSmall { len: 5, scratch: 4 })
+    ));
+}
@@ -0,0 +1,634 @@
+#[macro_use]
+extern crate serde_derive;
+
+use bincode_embedded::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Small {
+    a: u8,
+    b: u16,
+    c: bool,
+}
+
+const SMALL: Small = Small {
+    a: 0xAB,
+    b: 0x0102,
+    c: true,
+};
+
+#[test]
+fn hex_writer() {
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&SMALL, HexWriter::new(&mut writer)).unwrap();
+    assert_eq!(b"ab010201", writer.written_buffer());
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&SMALL, HexWriter::with_spaces(&mut writer))
+        .unwrap();
+    assert_eq!(b"ab 01 02 01", writer.written_buffer());
+}
+
+#[test]
+fn hex_writer_forwards_errors() {
+    let mut buffer = [0u8; 5];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&SMALL, HexWriter::new(&mut writer));
+    match result {
+        Err(SerializeError::Write(BufferWriterError::BufferTooSmall)) => {}
+        other => panic!("Expected BufferTooSmall, got {:?}", other),
+    }
+}
+
+#[test]
+fn reserve_and_patch_header() {
+    let mut buffer = [0xFFu8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+
+    let header = writer.reserve(4).unwrap();
+    assert_eq!(0, header);
+    assert_eq!(&[0, 0, 0, 0], writer.written_buffer());
+
+    serialize::<_, _, byteorder::NetworkEndian>(&SMALL, &mut writer).unwrap();
+    let body_len = (writer.written_len() - 4) as u32;
+    writer.patch(header, &body_len.to_be_bytes()).unwrap();
+
+    assert_eq!(&[0, 0, 0, 4, 0xAB, 1, 2, 1], writer.written_buffer());
+}
+
+#[test]
+fn patch_out_of_bounds() {
+    let mut buffer = [0u8; 8];
+    let mut writer = BufferWriter::new(&mut buffer);
+    writer.reserve(2).unwrap();
+
+    assert_eq!(
+        Err(BufferWriterError::OutOfBounds),
+        writer.patch(1, &[1, 2])
+    );
+    assert_eq!(Err(BufferWriterError::BufferTooSmall), writer.reserve(7));
+    assert_eq!(Ok(()), writer.patch(0, &[1, 2]));
+    assert_eq!(&[1, 2], writer.written_buffer());
+}
+
+#[test]
+fn unused_tail() {
+    let payload: Vec<u8> = (0..100).collect();
+    let mut buffer = [0u8; 128];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&SMALL, &mut writer).unwrap();
+    assert_eq!(124, writer.unused_mut().len());
+
+    writer.unused_mut()[..payload.len()].copy_from_slice(&payload);
+    writer.advance(payload.len()).unwrap();
+    serialize::<_, _, byteorder::NetworkEndian>(&0xEEu8, &mut writer).unwrap();
+
+    let written = writer.written_buffer();
+    assert_eq!(4 + 100 + 1, written.len());
+    assert_eq!(&[0xAB, 1, 2, 1], &written[..4]);
+    assert_eq!(&payload[..], &written[4..104]);
+    assert_eq!(0xEE, written[104]);
+
+    // Advancing past the end of the buffer is rejected and does not move the writer
+    assert_eq!(23, writer.unused_mut().len());
+    assert_eq!(Err(BufferWriterError::BufferTooSmall), writer.advance(24));
+    assert_eq!(105, writer.written_len());
+    assert_eq!(Ok(()), writer.advance(23));
+    assert!(writer.unused_mut().is_empty());
+}
+
+#[test]
+fn cursor() {
+    let mut buffer = [0xFFu8; 16];
+    let mut cursor = Cursor::new(&mut buffer);
+
+    // Forward writes
+    serialize::<_, _, byteorder::NetworkEndian>(&(0u16, SMALL), &mut cursor).unwrap();
+    assert_eq!(6, cursor.position());
+    assert_eq!(&[0, 0, 0xAB, 1, 2, 1], cursor.written_buffer());
+
+    // Seek back and patch the header, which does not change the written length
+    cursor.seek(0).unwrap();
+    serialize::<_, _, byteorder::NetworkEndian>(&4u16, &mut cursor).unwrap();
+    assert_eq!(2, cursor.position());
+    assert_eq!(6, cursor.written_len());
+    cursor.seek(6).unwrap();
+    serialize::<_, _, byteorder::NetworkEndian>(&0x7Fu8, &mut cursor).unwrap();
+
+    // Read the patched region back
+    assert_eq!(&[0, 4, 0xAB, 1, 2, 1, 0x7F], cursor.written_buffer());
+    let (header, small): (u16, Small) =
+        deserialize::<_, _, byteorder::NetworkEndian>(cursor.written_buffer()).unwrap();
+    assert_eq!(4, header);
+    assert_eq!(SMALL, small);
+    assert_eq!(0xFF, cursor.get_ref()[7]);
+    cursor.get_mut()[6] = 0x7E;
+    assert_eq!(&[0, 4, 0xAB, 1, 2, 1, 0x7E], &cursor.into_inner()[..7]);
+}
+
+#[test]
+fn cursor_bounds() {
+    let mut buffer = [0u8; 4];
+    let mut cursor = Cursor::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&1u16, &mut cursor).unwrap();
+
+    // Seeking past the written bytes would leave a gap of stale bytes
+    assert_eq!(Err(BufferWriterError::OutOfBounds), cursor.seek(3));
+    assert_eq!(Ok(()), cursor.seek(2));
+
+    // Overwriting at the end can not grow the buffer
+    cursor.seek(1).unwrap();
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&1u32, &mut cursor);
+    assert!(matches!(
+        result,
+        Err(SerializeError::Write(BufferWriterError::BufferTooSmall))
+    ));
+    assert_eq!(1, cursor.position());
+    assert_eq!(&[0, 1], cursor.written_buffer());
+}
+
+#[derive(Serialize)]
+enum Payload<'a> {
+    Empty,
+    Text(&'a str),
+    Pair { left: Small, right: Option<Small> },
+}
+
+#[derive(Serialize)]
+struct Nested<'a> {
+    id: u32,
+    payloads: [Payload<'a>; 3],
+    bytes: &'a [u8],
+    ratio: f64,
+}
+
+#[test]
+fn null_writer() {
+    let value = Nested {
+        id: 7,
+        payloads: [
+            Payload::Empty,
+            Payload::Text("abc"),
+            Payload::Pair {
+                left: SMALL,
+                right: Some(SMALL),
+            },
+        ],
+        bytes: &[1, 2, 3],
+        ratio: 0.5,
+    };
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&value, NullWriter);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn serialized_size_and_fits_in() {
+    // a: 1, b: 2, c: 1
+    assert_eq!(4, serialized_size(&SMALL).unwrap());
+    assert!(fits_in(&SMALL, 4));
+    assert!(!fits_in(&SMALL, 3));
+
+    for size in 0..8 {
+        let mut buffer = [0u8; 8];
+        let mut writer = BufferWriter::new(&mut buffer[..size]);
+        let result = serialize::<_, _, byteorder::NetworkEndian>(&SMALL, &mut writer);
+        assert_eq!(fits_in(&SMALL, size), result.is_ok());
+    }
+
+    let config = Config::new().bit_pack_tags();
+    let flags = (true, false, true);
+    assert_eq!(3, serialized_size(&flags).unwrap());
+    assert_eq!(1, serialized_size_with_config(&flags, config).unwrap());
+}
+
+#[test]
+fn to_bytes_and_from_bytes() {
+    let value = (SMALL, "abc");
+    let mut buffer = [0u8; 16];
+    let len = to_bytes::<_, byteorder::NetworkEndian>(&value, &mut buffer).unwrap();
+
+    let mut expected = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut expected);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(writer.written_buffer().len(), len);
+    assert_eq!(writer.written_buffer(), &buffer[..len]);
+
+    let (small, text): (Small, &str) =
+        from_bytes::<_, byteorder::NetworkEndian>(&buffer[..len]).unwrap();
+    assert_eq!(SMALL, small);
+    assert_eq!("abc", text);
+
+    let result = to_bytes::<_, byteorder::NetworkEndian>(&value, &mut buffer[..len - 1]);
+    assert!(matches!(
+        result,
+        Err(SerializeError::Write(BufferWriterError::BufferTooSmall))
+    ));
+}
+
+#[test]
+fn serialize_with_chunks() {
+    let value = (SMALL, "abc", 0x0102_0304u32);
+
+    let mut chunks: Vec<Vec<u8>> = Vec::new();
+    serialize_with::<_, byteorder::NetworkEndian, (), _>(&value, |chunk| {
+        chunks.push(chunk.to_vec());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(
+        vec![
+            vec![0xAB],
+            vec![1, 2],
+            vec![1],
+            vec![0, 3],
+            b"abc".to_vec(),
+            vec![1, 2, 3, 4]
+        ],
+        chunks
+    );
+
+    let mut buffer = [0u8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(writer.written_buffer(), chunks.concat().as_slice());
+
+    let result = serialize_with::<_, byteorder::NetworkEndian, _, _>(&value, |_| Err("closed"));
+    assert!(matches!(result, Err(SerializeError::Write("closed"))));
+}
+
+#[test]
+fn align_to() {
+    let mut buffer = [0xFFu8; 32];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&1u8, &mut writer).unwrap();
+    writer.align_to(4).unwrap();
+    assert_eq!(4, writer.written_len());
+    serialize::<_, _, byteorder::NetworkEndian>(&2u16, &mut writer).unwrap();
+    writer.align_to(4).unwrap();
+    assert_eq!(8, writer.written_len());
+    serialize::<_, _, byteorder::NetworkEndian>(&3u32, &mut writer).unwrap();
+    // Already aligned, nothing is written
+    writer.align_to(4).unwrap();
+    writer.align_to(0).unwrap();
+    assert_eq!(
+        &[1, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 3],
+        writer.written_buffer()
+    );
+    let len = writer.written_len();
+
+    let mut deserializer =
+        Deserializer::<_, byteorder::NetworkEndian>::new(&buffer[..len], Config::new());
+    let a: u8 = serde::Deserialize::deserialize(&mut deserializer).unwrap();
+    deserializer.align_to(4).unwrap();
+    let b: u16 = serde::Deserialize::deserialize(&mut deserializer).unwrap();
+    deserializer.align_to(4).unwrap();
+    let c: u32 = serde::Deserialize::deserialize(&mut deserializer).unwrap();
+    deserializer.align_to(4).unwrap();
+    assert_eq!((1, 2, 3), (a, b, c));
+    assert!(deserializer.into_reader().is_empty());
+
+    // The padding has to fit in the buffer
+    let mut buffer = [0u8; 3];
+    let mut writer = BufferWriter::new(&mut buffer);
+    serialize::<_, _, byteorder::NetworkEndian>(&1u8, &mut writer).unwrap();
+    assert_eq!(Err(BufferWriterError::BufferTooSmall), writer.align_to(4));
+}
+
+#[test]
+fn limit_writer() {
+    let value = SMALL;
+    let mut buffer = [0u8; 32];
+
+    let mut writer = BufferWriter::new(&mut buffer);
+    let mut limited = LimitWriter::new(&mut writer, 4);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut limited).unwrap();
+    assert_eq!(4, limited.written());
+
+    // The frame does not fit, even though the backing buffer is large enough
+    let mut writer = BufferWriter::new(&mut buffer);
+    let mut limited = LimitWriter::new(&mut writer, 2);
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&value, &mut limited);
+    assert!(matches!(
+        result,
+        Err(SerializeError::Write(LimitWriterError::Exceeded))
+    ));
+    assert_eq!(2, limited.written());
+    assert_eq!(&[0xAB, 0x01], writer.written_buffer());
+
+    // Errors of the inner writer are passed through
+    let mut small = [0u8; 1];
+    let mut writer = BufferWriter::new(&mut small);
+    let result =
+        serialize::<_, _, byteorder::NetworkEndian>(&value, LimitWriter::new(&mut writer, 8));
+    assert!(matches!(
+        result,
+        Err(SerializeError::Write(LimitWriterError::Write(
+            BufferWriterError::BufferTooSmall
+        )))
+    ));
+}
+
+/// Bit-by-bit CRC-32, to check the table-driven implementation of [MeteredWriter].
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[test]
+fn metered_writer() {
+    let value = (SMALL, "metered", [0xFFu8; 32], Some(-1i64));
+    let mut buffer = [0u8; 128];
+    let mut writer = BufferWriter::new(&mut buffer);
+    let mut metered = MeteredWriter::new(&mut writer);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut metered).unwrap();
+    let written = metered.written();
+    let (writer, len, crc) = metered.into_parts();
+
+    assert_eq!(written, len);
+    assert_eq!(writer.written_len(), len);
+    assert_eq!(serialized_size(&value).unwrap(), len);
+    assert_eq!(crc32(writer.written_buffer()), crc);
+
+    // An empty writer has the CRC of no bytes
+    assert_eq!(0, MeteredWriter::new(NullWriter).crc());
+}
+
+#[test]
+fn scatter_writer() {
+    let value = (SMALL, "spill");
+    let mut expected = [0u8; 16];
+    let mut writer = BufferWriter::new(&mut expected);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    let expected = writer.written_buffer().to_vec();
+    assert_eq!(11, expected.len());
+
+    let mut first = [0u8; 3];
+    let mut empty = [0u8; 0];
+    let mut second = [0u8; 16];
+    // The amount of buffers is only known at runtime
+    let mut buffers = vec![&mut first[..], &mut empty[..], &mut second[..]];
+    let mut segments = vec![0; buffers.len()];
+    let mut writer = ScatterWriter::new(&mut buffers, &mut segments);
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(&[3, 0, 8], writer.segments());
+    assert_eq!(11, writer.written_len());
+    assert_eq!(&expected[..3], &first);
+    assert_eq!(&expected[3..], &second[..8]);
+
+    // An error is only returned when every buffer is full
+    let mut first = [0u8; 4];
+    let mut second = [0u8; 4];
+    let mut buffers = [&mut first[..], &mut second[..]];
+    let mut segments = [0; 2];
+    let mut writer = ScatterWriter::new(&mut buffers, &mut segments);
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer);
+    assert!(matches!(
+        result,
+        Err(SerializeError::Write(BufferWriterError::BufferTooSmall))
+    ));
+    assert_eq!(&[4, 4], writer.segments());
+}
+
+/// A writer that sends every `write_all` call as a packet, and does not support single bytes.
+#[derive(Default)]
+struct PacketWriter {
+    packets: Vec<Vec<u8>>,
+}
+
+impl CoreWrite for &'_ mut PacketWriter {
+    type Error = ();
+
+    fn write(&mut self, _val: u8) -> Result<(), Self::Error> {
+        unreachable!("every write goes through write_all")
+    }
+
+    fn write_all(&mut self, val: &[u8]) -> Result<(), Self::Error> {
+        self.packets.push(val.to_vec());
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct Registers {
+    r0: u8,
+    r1: u16,
+    r2: u32,
+    r3: u16,
+    r4: u8,
+    r5: u16,
+    r6: u32,
+    r7: u16,
+    r8: u8,
+    r9: u16,
+    r10: u32,
+    r11: u16,
+    r12: u8,
+    r13: u16,
+    r14: u32,
+    r15: u16,
+}
+
+const REGISTERS: Registers = Registers {
+    r0: 0x01,
+    r1: 0x0203,
+    r2: 0x0405_0607,
+    r3: 0x0809,
+    r4: 0x0A,
+    r5: 0x0B0C,
+    r6: 0x0D0E_0F10,
+    r7: 0x1112,
+    r8: 0x13,
+    r9: 0x1415,
+    r10: 0x1617_1819,
+    r11: 0x1A1B,
+    r12: 0x1C,
+    r13: 0x1D1E,
+    r14: 0x1F20_2122,
+    r15: 0x2324,
+};
+
+#[test]
+fn coalescing_writer() {
+    let expected: Vec<u8> = (1..=0x24).collect();
+
+    let mut writer = PacketWriter::default();
+    serialize::<_, _, byteorder::NetworkEndian>(&REGISTERS, &mut writer).unwrap();
+    assert_eq!(16, writer.packets.len());
+    assert_eq!(expected, writer.packets.concat());
+
+    // Values are not split, the buffer is written when the next value does not fit. The rest is
+    // written by `finish`.
+    let mut writer = PacketWriter::default();
+    let mut coalescing = CoalescingWriter::<_>::new(&mut writer);
+    serialize::<_, _, byteorder::NetworkEndian>(&REGISTERS, &mut coalescing).unwrap();
+    coalescing.finish().unwrap();
+    assert_eq!(vec![&expected[..30], &expected[30..]], writer.packets);
+
+    // Packed tags are collected as well, and large writes skip the buffer
+    let long = "a string that does not fit in the buffer";
+    let value = (true, 1u8, false, long, 2u16);
+    let config = Config::new().bit_pack_tags();
+    let mut writer = PacketWriter::default();
+    let mut coalescing = CoalescingWriter::<_>::new(&mut writer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut coalescing, config)
+        .unwrap();
+    // `flush` writes the rest as well
+    coalescing.flush().unwrap();
+    assert_eq!(
+        vec![&[0b1, 1, 0b0, 0, 40][..], long.as_bytes(), &[0, 2]],
+        writer.packets
+    );
+
+    // The bytes are the same as without the option
+    let mut buffer = [0u8; 64];
+    let mut buffer_writer = BufferWriter::new(&mut buffer);
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut buffer_writer, config)
+        .unwrap();
+    assert_eq!(writer.packets.concat(), buffer_writer.written_buffer());
+}
+
+#[test]
+fn write_all_only() {
+    let value = (SMALL, -1i8, Some(()), None::<u8>, 'a');
+    let mut writer = PacketWriter::default();
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(
+        vec![
+            vec![0xAB],
+            vec![0x01, 0x02],
+            vec![1],
+            vec![0xFF],
+            vec![1],
+            vec![0],
+            vec![b'a']
+        ],
+        writer.packets
+    );
+
+    // Packed tags are written with write_all as well
+    let mut writer = PacketWriter::default();
+    let config = Config::new().bit_pack_tags();
+    serialize_with_config::<_, _, byteorder::NetworkEndian>(&value, &mut writer, config).unwrap();
+    assert_eq!(
+        vec![
+            vec![0xAB],
+            vec![0x01, 0x02],
+            vec![0b1],
+            vec![0xFF],
+            vec![0b01],
+            vec![b'a']
+        ],
+        writer.packets
+    );
+}
+
+#[test]
+fn growable_writer() {
+    let value = (SMALL, "a string that does not fit", [0xFFu8; 8]);
+    let mut expected = [0u8; 64];
+    let len = to_bytes::<_, byteorder::NetworkEndian>(&value, &mut expected).unwrap();
+
+    // A pool with a medium and a large block
+    let mut small = [0u8; 4];
+    let mut medium = [0u8; 8];
+    let mut large = [0u8; 64];
+    let mut pool = [Some(&mut medium[..]), Some(&mut large[..])];
+    let mut requests = Vec::new();
+    let mut writer = GrowableWriter::new(&mut small, |needed| {
+        requests.push(needed);
+        pool.iter_mut()
+            .find(|block| block.as_ref().is_some_and(|block| block.len() >= needed))
+            .and_then(Option::take)
+    });
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(&expected[..len], writer.written_buffer());
+    assert_eq!(len, writer.written_len());
+    // The length prefix of the string, and the string itself
+    assert_eq!(vec![6, 32], requests);
+
+    // The pool is empty
+    let mut small = [0u8; 4];
+    let mut writer = GrowableWriter::new(&mut small, |_| None);
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer);
+    assert!(matches!(
+        result,
+        Err(SerializeError::Write(BufferWriterError::BufferTooSmall))
+    ));
+    assert_eq!(&expected[..4], writer.written_buffer());
+
+    // A buffer that is smaller than requested is not used
+    let mut small = [0u8; 4];
+    let mut other = [0u8; 5];
+    let mut other = Some(&mut other[..]);
+    let mut writer = GrowableWriter::new(&mut small, |_| other.take());
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer);
+    assert!(matches!(
+        result,
+        Err(SerializeError::Write(BufferWriterError::BufferTooSmall))
+    ));
+}
+
+#[test]
+fn array_writer() {
+    let mut writer = ArrayWriter::<64>::new();
+    assert!(writer.is_empty());
+    let value = (SMALL, "array");
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+    assert_eq!(b"\xAB\x01\x02\x01\x00\x05array", writer.as_slice());
+    assert_eq!(11, writer.len());
+    let (small, text): (Small, &str) =
+        deserialize::<_, _, byteorder::NetworkEndian>(writer.as_slice()).unwrap();
+    assert_eq!((SMALL, "array"), (small, text));
+
+    // The writer can be reused
+    writer.clear();
+    serialize::<_, _, byteorder::NetworkEndian>(&7u8, &mut writer).unwrap();
+    assert_eq!(&[7], writer.as_slice());
+
+    // The writer can be returned from a function, as it owns its buffer
+    fn encode(value: u32) -> ArrayWriter<4> {
+        let mut writer = ArrayWriter::new();
+        serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer).unwrap();
+        writer
+    }
+    assert_eq!(&[0, 0, 1, 0], encode(0x100).as_slice());
+
+    let mut writer = ArrayWriter::<4>::new();
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&value, &mut writer);
+    assert!(matches!(
+        result,
+        Err(SerializeError::Write(BufferWriterError::BufferTooSmall))
+    ));
+}
+
+#[test]
+fn loopback() {
+    let value = (SMALL, "loop", 0x0102_0304u32);
+    let mut buffer = [0u8; 16];
+    let mut loopback = Loopback::new(&mut buffer);
+
+    // Nothing is written yet
+    assert!(deserialize::<u8, _, byteorder::NetworkEndian>(&mut loopback).is_err());
+
+    serialize::<_, _, byteorder::NetworkEndian>(&value, &mut loopback).unwrap();
+    assert_eq!(14, loopback.written_len());
+    loopback.reset_to_read();
+    let (small, text, number): (Small, &str, u32) =
+        deserialize::<_, _, byteorder::NetworkEndian>(&mut loopback).unwrap();
+    assert_eq!(value, (small, text, number));
+    assert_eq!(0, loopback.remaining());
+
+    // The buffer is borrowed by the values that are read, so it can not be written again
+    let result = serialize::<_, _, byteorder::NetworkEndian>(&1u8, &mut loopback);
+    assert!(matches!(
+        result,
+        Err(SerializeError::Write(BufferWriterError::BufferTooSmall))
+    ));
+}